@@ -5,7 +5,10 @@ use rand_xoshiro::{rand_core::SeedableRng, Xoshiro256PlusPlus};
 use std::mem;
 use std::time::Duration;
 
-use cw_storage_plus::IntKey;
+use cosmwasm_std::testing::MockStorage;
+#[cfg(feature = "iterator")]
+use cosmwasm_std::{Order, StdResult};
+use cw_storage_plus::{IntKey, Item, Map};
 
 fn bench_signed_int_key(c: &mut Criterion) {
     let mut group = c.benchmark_group("Signed int keys");
@@ -141,6 +144,111 @@ fn bench_unsigned_int_key(c: &mut Criterion) {
     group.finish();
 }
 
+#[cfg(feature = "iterator")]
+fn bench_map_range(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Map range");
+
+    const MAP: Map<u32, u64> = Map::new("map");
+    let mut store = MockStorage::new();
+    for i in 0..1000u32 {
+        MAP.save(&mut store, i, &(i as u64)).unwrap();
+    }
+
+    group.bench_function("range item by item", |b| {
+        b.iter(|| {
+            let mut sum = 0u64;
+            for item in MAP.range(&store, None, None, Order::Ascending) {
+                sum += item.unwrap().1;
+            }
+            black_box(sum);
+        });
+    });
+
+    for chunk in [10, 100] {
+        group.bench_function(format!("range_chunked ({chunk})"), |b| {
+            b.iter(|| {
+                let mut sum = 0u64;
+                for items in MAP.range_chunked(&store, None, None, Order::Ascending, chunk) {
+                    let items: StdResult<Vec<_>> = items;
+                    sum += items.unwrap().iter().map(|(_, v)| v).sum::<u64>();
+                }
+                black_box(sum);
+            });
+        });
+    }
+
+    group.bench_function("range collect (10, repeated)", |b| {
+        b.iter(|| {
+            let items: Vec<_> = MAP
+                .range(&store, None, None, Order::Ascending)
+                .take(10)
+                .collect::<StdResult<_>>()
+                .unwrap();
+            black_box(items);
+        });
+    });
+
+    group.bench_function("collect_into (10, repeated)", |b| {
+        let mut items = vec![];
+        b.iter(|| {
+            MAP.collect_into(&store, None, None, Order::Ascending, Some(10), &mut items)
+                .unwrap();
+            black_box(&items);
+        });
+    });
+
+    group.finish();
+}
+
+fn bench_item_load(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Item load");
+
+    const AMOUNTS: Item<Vec<u64>> = Item::new("amounts");
+    const NAMES: Item<Vec<String>> = Item::new("names");
+    let mut store = MockStorage::new();
+    AMOUNTS
+        .save(&mut store, &(0..1000u64).collect::<Vec<_>>())
+        .unwrap();
+    NAMES
+        .save(
+            &mut store,
+            &(0..1000).map(|i| format!("name {i}")).collect::<Vec<_>>(),
+        )
+        .unwrap();
+
+    group.bench_function("load (numbers)", |b| {
+        b.iter(|| {
+            black_box(AMOUNTS.load(&store).unwrap());
+        });
+    });
+
+    #[cfg(feature = "load-into")]
+    group.bench_function("load_into (numbers)", |b| {
+        let mut amounts = vec![];
+        b.iter(|| {
+            AMOUNTS.load_into(&store, &mut amounts).unwrap();
+            black_box(&amounts);
+        });
+    });
+
+    group.bench_function("load (strings)", |b| {
+        b.iter(|| {
+            black_box(NAMES.load(&store).unwrap());
+        });
+    });
+
+    #[cfg(feature = "load-into")]
+    group.bench_function("load_into (strings)", |b| {
+        let mut names = vec![];
+        b.iter(|| {
+            NAMES.load_into(&store, &mut names).unwrap();
+            black_box(&names);
+        });
+    });
+
+    group.finish();
+}
+
 fn make_config() -> Criterion {
     Criterion::default()
         .without_plots()
@@ -159,4 +267,18 @@ criterion_group!(
     config = make_config();
     targets = bench_unsigned_int_key
 );
-criterion_main!(signed_int_key, unsigned_int_key);
+#[cfg(feature = "iterator")]
+criterion_group!(
+    name = map_range;
+    config = make_config();
+    targets = bench_map_range
+);
+criterion_group!(
+    name = item_load;
+    config = make_config();
+    targets = bench_item_load
+);
+#[cfg(feature = "iterator")]
+criterion_main!(signed_int_key, unsigned_int_key, map_range, item_load);
+#[cfg(not(feature = "iterator"))]
+criterion_main!(signed_int_key, unsigned_int_key, item_load);
@@ -7,32 +7,52 @@ For more information on this package, please check out the
 
 use proc_macro::TokenStream;
 use syn::{
-    Ident,
-    __private::{quote::quote, Span},
-    parse_macro_input, ItemStruct,
+    __private::{
+        quote::{quote, quote_spanned},
+        Span, TokenStream2,
+    },
+    parse_macro_input, parse_quote,
+    spanned::Spanned,
+    Data, DeriveInput, Error, Fields, Ident, Index, ItemStruct, Member, Type,
 };
 
 #[proc_macro_attribute]
 pub fn index_list(attr: TokenStream, item: TokenStream) -> TokenStream {
     let input = parse_macro_input!(item as ItemStruct);
+    let ty = parse_macro_input!(attr as Type);
 
-    let ty = Ident::new(&attr.to_string(), Span::call_site());
-    let struct_ty = input.ident.clone();
+    let struct_ty = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
 
     let names = input
         .fields
-        .clone()
-        .into_iter()
-        .map(|e| {
-            let name = e.ident.unwrap();
-            quote! { &self.#name }
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            };
+            quote! { &self.#member }
         })
         .collect::<Vec<_>>();
 
+    // every field must be an index of the stored type, which needs to be spelled out when
+    // the field types depend on the generics of the struct
+    let mut where_clause = where_clause
+        .cloned()
+        .unwrap_or_else(|| parse_quote! { where });
+    for field in &input.fields {
+        let field_ty = &field.ty;
+        where_clause
+            .predicates
+            .push(parse_quote! { #field_ty: cw_storage_plus::Index<#ty> });
+    }
+
     let expanded = quote! {
         #input
 
-        impl cw_storage_plus::IndexList<#ty> for #struct_ty<'_> {
+        impl #impl_generics cw_storage_plus::IndexList<#ty> for #struct_ty #ty_generics #where_clause {
             fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn cw_storage_plus::Index<#ty>> + '_> {
                 let v: Vec<&dyn cw_storage_plus::Index<#ty>> = vec![#(#names),*];
                 Box::new(v.into_iter())
@@ -42,3 +62,280 @@ pub fn index_list(attr: TokenStream, item: TokenStream) -> TokenStream {
 
     TokenStream::from(expanded)
 }
+
+#[proc_macro_derive(CompositeKey)]
+pub fn composite_key(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let name = &input.ident;
+
+    let fields = match &input.data {
+        Data::Struct(data) => &data.fields,
+        _ => {
+            return Error::new_spanned(name, "CompositeKey can only be derived for structs")
+                .to_compile_error()
+                .into()
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Error::new_spanned(
+            &input.generics,
+            "CompositeKey cannot be derived for generic structs",
+        )
+        .to_compile_error()
+        .into();
+    }
+    if fields.is_empty() {
+        return Error::new_spanned(name, "CompositeKey requires at least one field")
+            .to_compile_error()
+            .into();
+    }
+
+    let members = fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| match &field.ident {
+            Some(ident) => Member::Named(ident.clone()),
+            None => Member::Unnamed(Index::from(i)),
+        })
+        .collect::<Vec<_>>();
+    let types = fields.iter().map(|field| &field.ty).collect::<Vec<_>>();
+    let vars = (0..members.len())
+        .map(|i| Ident::new(&format!("field{}", i), Span::call_site()))
+        .collect::<Vec<_>>();
+
+    // errors on fields that are not keys point to the field type
+    let keys = members.iter().zip(&types).map(|(member, ty)| {
+        quote_spanned! {ty.span()=>
+            keys.extend(<#ty as cw_storage_plus::PrimaryKey>::key(&self.#member));
+        }
+    });
+    let prefixes = members.iter().zip(&types).map(|(member, ty)| {
+        quote_spanned! {ty.span()=>
+            keys.extend(<#ty as cw_storage_plus::Prefixer>::prefix(&self.#member));
+        }
+    });
+    let key_elems = types.iter().map(|ty| {
+        quote_spanned! {ty.span()=>
+            <#ty as cw_storage_plus::KeyDeserialize>::KEY_ELEMS
+        }
+    });
+
+    // the fields are deserialized as nested pairs `(A, (B, C))`, which share the layout
+    // of the flat key built above
+    let (last_ty, last_var) = (types[types.len() - 1], &vars[vars.len() - 1]);
+    let (nested_ty, nested_pat) = types.iter().zip(&vars).rev().skip(1).fold(
+        (quote! { #last_ty }, quote! { #last_var }),
+        |(ty_acc, pat_acc), (ty, var)| (quote! { (#ty, #ty_acc) }, quote! { (#var, #pat_acc) }),
+    );
+
+    let ref_de = ref_key_deserialize(name);
+
+    let expanded = quote! {
+        impl<'a> cw_storage_plus::PrimaryKey<'a> for #name {
+            type Prefix = ();
+            type SubPrefix = ();
+            type Suffix = Self;
+            type SuperSuffix = Self;
+
+            fn key(&self) -> Vec<cw_storage_plus::Key> {
+                let mut keys = Vec::new();
+                #(#keys)*
+                keys
+            }
+        }
+
+        impl<'a> cw_storage_plus::Prefixer<'a> for #name {
+            fn prefix(&self) -> Vec<cw_storage_plus::Key> {
+                let mut keys = Vec::new();
+                #(#prefixes)*
+                keys
+            }
+        }
+
+        impl cw_storage_plus::KeyDeserialize for #name {
+            type Output = Self;
+
+            const KEY_ELEMS: u16 = #(#key_elems)+*;
+
+            #[inline(always)]
+            fn from_vec(value: Vec<u8>) -> cosmwasm_std::StdResult<Self::Output> {
+                let #nested_pat =
+                    <#nested_ty as cw_storage_plus::KeyDeserialize>::from_vec(value)?;
+                Ok(Self {
+                    #(#members: #vars),*
+                })
+            }
+        }
+
+        #ref_de
+    };
+
+    TokenStream::from(expanded)
+}
+
+#[proc_macro_derive(CwKey)]
+pub fn cw_key(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let name = &input.ident;
+
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Error::new_spanned(name, "CwKey can only be derived for enums")
+                .to_compile_error()
+                .into()
+        }
+    };
+    if !input.generics.params.is_empty() {
+        return Error::new_spanned(&input.generics, "CwKey cannot be derived for generic enums")
+            .to_compile_error()
+            .into();
+    }
+    if variants.is_empty() {
+        return Error::new_spanned(name, "CwKey requires at least one variant")
+            .to_compile_error()
+            .into();
+    }
+    if variants.len() > 256 {
+        return Error::new_spanned(name, "CwKey supports at most 256 variants")
+            .to_compile_error()
+            .into();
+    }
+
+    let mut idents = Vec::new();
+    let mut types = Vec::new();
+    for variant in variants {
+        match &variant.fields {
+            Fields::Unnamed(fields) if fields.unnamed.len() == 1 => {
+                idents.push(&variant.ident);
+                types.push(&fields.unnamed[0].ty);
+            }
+            _ => {
+                return Error::new_spanned(
+                    variant,
+                    "CwKey requires every variant to wrap exactly one unnamed field",
+                )
+                .to_compile_error()
+                .into()
+            }
+        }
+    }
+    let discriminants = (0..idents.len()).map(|i| i as u8).collect::<Vec<_>>();
+
+    let keys = idents
+        .iter()
+        .zip(&types)
+        .zip(&discriminants)
+        .map(|((ident, ty), d)| {
+            quote_spanned! {ty.span()=>
+                Self::#ident(inner) => {
+                    let mut keys = vec![cw_storage_plus::Key::Val8([#d])];
+                    keys.extend(<#ty as cw_storage_plus::PrimaryKey>::key(inner));
+                    keys
+                }
+            }
+        });
+    let prefixes = idents
+        .iter()
+        .zip(&types)
+        .zip(&discriminants)
+        .map(|((ident, ty), d)| {
+            quote_spanned! {ty.span()=>
+                Self::#ident(inner) => {
+                    let mut keys = vec![cw_storage_plus::Key::Val8([#d])];
+                    keys.extend(<#ty as cw_storage_plus::Prefixer>::prefix(inner));
+                    keys
+                }
+            }
+        });
+    let variants_de = idents
+        .iter()
+        .zip(&types)
+        .zip(&discriminants)
+        .map(|((ident, ty), d)| {
+            quote_spanned! {ty.span()=>
+                #d => Ok(Self::#ident(
+                    <#ty as cw_storage_plus::KeyDeserialize>::from_vec(inner)?,
+                )),
+            }
+        });
+
+    // all variants must take the same number of key elements, so the enum can be
+    // part of a composite key
+    let first_ty = types[0];
+    let same_elems = types.iter().skip(1).map(|ty| {
+        quote_spanned! {ty.span()=>
+            const _: () = assert!(
+                <#ty as cw_storage_plus::KeyDeserialize>::KEY_ELEMS
+                    == <#first_ty as cw_storage_plus::KeyDeserialize>::KEY_ELEMS,
+                "CwKey requires all variants to have the same number of key elements"
+            );
+        }
+    });
+    let err = format!("Invalid discriminant for {}", name);
+    let ref_de = ref_key_deserialize(name);
+
+    let expanded = quote! {
+        #(#same_elems)*
+
+        impl<'a> cw_storage_plus::PrimaryKey<'a> for #name {
+            type Prefix = u8;
+            type SubPrefix = ();
+            type Suffix = Vec<u8>;
+            type SuperSuffix = Self;
+
+            fn key(&self) -> Vec<cw_storage_plus::Key> {
+                match self {
+                    #(#keys)*
+                }
+            }
+        }
+
+        impl<'a> cw_storage_plus::Prefixer<'a> for #name {
+            fn prefix(&self) -> Vec<cw_storage_plus::Key> {
+                match self {
+                    #(#prefixes)*
+                }
+            }
+        }
+
+        impl cw_storage_plus::KeyDeserialize for #name {
+            type Output = Self;
+
+            const KEY_ELEMS: u16 =
+                1 + <#first_ty as cw_storage_plus::KeyDeserialize>::KEY_ELEMS;
+
+            #[inline(always)]
+            fn from_vec(value: Vec<u8>) -> cosmwasm_std::StdResult<Self::Output> {
+                let (discriminant, inner) =
+                    <(u8, Vec<u8>) as cw_storage_plus::KeyDeserialize>::from_vec(value)?;
+                match discriminant {
+                    #(#variants_de)*
+                    _ => Err(cosmwasm_std::StdError::generic_err(#err)),
+                }
+            }
+        }
+
+        #ref_de
+    };
+
+    TokenStream::from(expanded)
+}
+
+/// `PrimaryKey` and `Prefixer` are implemented for references to keys in general, but
+/// `KeyDeserialize` is not, so it is added for `&Name` to make `Map<&Name, T>` iterable,
+/// just like `Map<&Addr, T>`.
+fn ref_key_deserialize(name: &Ident) -> TokenStream2 {
+    quote! {
+        impl cw_storage_plus::KeyDeserialize for &#name {
+            type Output = #name;
+
+            const KEY_ELEMS: u16 = <#name as cw_storage_plus::KeyDeserialize>::KEY_ELEMS;
+
+            #[inline(always)]
+            fn from_vec(value: Vec<u8>) -> cosmwasm_std::StdResult<Self::Output> {
+                <#name as cw_storage_plus::KeyDeserialize>::from_vec(value)
+            }
+        }
+    }
+}
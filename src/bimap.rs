@@ -0,0 +1,179 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::keys::PrimaryKey;
+use crate::map::Map;
+use crate::namespace::Namespace;
+
+/// `BiMap` stores a one-to-one mapping between left and right values, and can be queried from
+/// both sides. It keeps two `Map`s in sync: one from left to right, one from right to left.
+///
+/// Both sides are used as keys and as values, so they must be owned types like `Addr` or `u64`.
+pub struct BiMap<L, R> {
+    left: Map<L, R>,
+    right: Map<R, L>,
+}
+
+impl<L, R> BiMap<L, R> {
+    /// Creates a new [`BiMap`] with the given storage keys for both directions. This is a const fn
+    /// only suitable when all the storage keys provided are static strings.
+    pub const fn new(left_namespace: &'static str, right_namespace: &'static str) -> Self {
+        BiMap {
+            left: Map::new(left_namespace),
+            right: Map::new(right_namespace),
+        }
+    }
+
+    /// Creates a new [`BiMap`] with the given storage keys for both directions. Use this if you
+    /// might need to handle dynamic strings. Otherwise, you might prefer [`BiMap::new`].
+    pub fn new_dyn(
+        left_namespace: impl Into<Namespace>,
+        right_namespace: impl Into<Namespace>,
+    ) -> Self {
+        BiMap {
+            left: Map::new_dyn(left_namespace),
+            right: Map::new_dyn(right_namespace),
+        }
+    }
+
+    /// Returns the `Map` from left to right values, to be used for read access like ranges.
+    pub fn left(&self) -> &Map<L, R> {
+        &self.left
+    }
+
+    /// Returns the `Map` from right to left values, to be used for read access like ranges.
+    pub fn right(&self) -> &Map<R, L> {
+        &self.right
+    }
+}
+
+impl<'a, L, R> BiMap<L, R>
+where
+    L: PrimaryKey<'a> + Serialize + DeserializeOwned + PartialEq,
+    R: PrimaryKey<'a> + Serialize + DeserializeOwned + PartialEq,
+{
+    /// Links `l` and `r`. Returns an error if either of them is already linked to another value,
+    /// in which case nothing is written. Saving a pair that already exists is a no-op.
+    pub fn save(&self, store: &mut dyn Storage, l: L, r: R) -> StdResult<()> {
+        if let Some(existing) = self.left.may_load(store, l.clone())? {
+            if existing != r {
+                return Err(StdError::generic_err("Left value is already mapped"));
+            }
+        }
+        if let Some(existing) = self.right.may_load(store, r.clone())? {
+            if existing != l {
+                return Err(StdError::generic_err("Right value is already mapped"));
+            }
+        }
+        self.left.save(store, l.clone(), &r)?;
+        self.right.save(store, r, &l)
+    }
+
+    /// Returns the right value linked to `l`, if any.
+    pub fn by_left(&self, store: &dyn Storage, l: L) -> StdResult<Option<R>> {
+        self.left.may_load(store, l)
+    }
+
+    /// Returns the left value linked to `r`, if any.
+    pub fn by_right(&self, store: &dyn Storage, r: R) -> StdResult<Option<L>> {
+        self.right.may_load(store, r)
+    }
+
+    /// Removes the link of `l` in both directions. Returns the right value it was linked to, if any.
+    pub fn remove_by_left(&self, store: &mut dyn Storage, l: L) -> StdResult<Option<R>> {
+        let r = self.left.may_load(store, l.clone())?;
+        if let Some(r) = &r {
+            self.right.remove(store, r.clone());
+            self.left.remove(store, l);
+        }
+        Ok(r)
+    }
+
+    /// Removes the link of `r` in both directions. Returns the left value it was linked to, if any.
+    pub fn remove_by_right(&self, store: &mut dyn Storage, r: R) -> StdResult<Option<L>> {
+        let l = self.right.may_load(store, r.clone())?;
+        if let Some(l) = &l {
+            self.left.remove(store, l.clone());
+            self.right.remove(store, r);
+        }
+        Ok(l)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::Addr;
+
+    const IDS: BiMap<Addr, u64> = BiMap::new("addr_to_id", "id_to_addr");
+
+    #[test]
+    fn save_and_lookup() {
+        let mut store = MockStorage::new();
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        IDS.save(&mut store, alice.clone(), 1).unwrap();
+        IDS.save(&mut store, bob.clone(), 2).unwrap();
+        // saving the same pair again is fine
+        IDS.save(&mut store, alice.clone(), 1).unwrap();
+
+        assert_eq!(IDS.by_left(&store, alice.clone()).unwrap(), Some(1));
+        assert_eq!(IDS.by_right(&store, 2).unwrap(), Some(bob.clone()));
+        assert_eq!(IDS.by_right(&store, 3).unwrap(), None);
+        assert_eq!(IDS.left().load(&store, bob).unwrap(), 2);
+        assert_eq!(IDS.right().load(&store, 1).unwrap(), alice);
+    }
+
+    #[test]
+    fn conflicting_save_fails() {
+        let mut store = MockStorage::new();
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        IDS.save(&mut store, alice.clone(), 1).unwrap();
+
+        let err = IDS.save(&mut store, alice.clone(), 2).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Left value is already mapped"));
+        let err = IDS.save(&mut store, bob.clone(), 1).unwrap_err();
+        assert_eq!(err, StdError::generic_err("Right value is already mapped"));
+
+        // nothing was written
+        assert_eq!(IDS.by_right(&store, 2).unwrap(), None);
+        assert_eq!(IDS.by_left(&store, bob).unwrap(), None);
+        assert_eq!(IDS.by_left(&store, alice).unwrap(), Some(1));
+    }
+
+    #[test]
+    fn remove_from_both_sides() {
+        let mut store = MockStorage::new();
+        let alice = Addr::unchecked("alice");
+        let bob = Addr::unchecked("bob");
+
+        IDS.save(&mut store, alice.clone(), 1).unwrap();
+        IDS.save(&mut store, bob.clone(), 2).unwrap();
+
+        assert_eq!(
+            IDS.remove_by_left(&mut store, alice.clone()).unwrap(),
+            Some(1)
+        );
+        assert_eq!(IDS.by_left(&store, alice.clone()).unwrap(), None);
+        assert_eq!(IDS.by_right(&store, 1).unwrap(), None);
+        assert_eq!(IDS.remove_by_left(&mut store, alice.clone()).unwrap(), None);
+
+        assert_eq!(
+            IDS.remove_by_right(&mut store, 2).unwrap(),
+            Some(bob.clone())
+        );
+        assert_eq!(IDS.by_left(&store, bob.clone()).unwrap(), None);
+        assert_eq!(IDS.by_right(&store, 2).unwrap(), None);
+
+        // both sides are free again
+        IDS.save(&mut store, alice.clone(), 2).unwrap();
+        IDS.save(&mut store, bob, 1).unwrap();
+        assert_eq!(IDS.by_right(&store, 2).unwrap(), Some(alice));
+    }
+}
@@ -1,10 +1,11 @@
 #![cfg(feature = "iterator")]
 
 use cosmwasm_std::Addr;
+use std::borrow::Cow;
 use std::marker::PhantomData;
 
 use crate::de::KeyDeserialize;
-use crate::{Prefixer, PrimaryKey};
+use crate::{PaddedString, Prefixer, PrimaryKey};
 
 /// `RawBound` is used to define the two ends of a range, more explicit than `Option<u8>`.
 /// `None` means that we don't limit that side of the range at all.
@@ -38,6 +39,18 @@ impl<'a, K: PrimaryKey<'a>> Bound<'a, K> {
         Self::Exclusive((k.into(), PhantomData))
     }
 
+    /// Returns the `(min, max)` bounds matching all keys whose raw bytes start with `prefix`.
+    /// This is useful to match the start of the last element of a key, after fixing the
+    /// others with `prefix()`, e.g. all `Vec<u8>` suffixes beginning with some bytes.
+    ///
+    /// As this works on raw bytes, it only makes sense for keys that are a single element,
+    /// as all but the last element of a composite key are length-prefixed.
+    pub fn prefix(prefix: &[u8]) -> (Option<Self>, Option<Self>) {
+        let min = Bound::InclusiveRaw(prefix.to_vec());
+        let max = prefix_end(prefix).map(Bound::ExclusiveRaw);
+        (Some(min), max)
+    }
+
     pub fn to_raw_bound(&self) -> RawBound {
         match self {
             Bound::Inclusive((k, _)) => RawBound::Inclusive(k.joined_key()),
@@ -48,6 +61,47 @@ impl<'a, K: PrimaryKey<'a>> Bound<'a, K> {
     }
 }
 
+impl<'a, K: PrimaryKey<'a> + KeyDeserialize> Bound<'a, K> {
+    /// Builds an inclusive bound from a deserialized key, e.g. the last key of a page.
+    /// The owned output is serialized again, which yields the same bytes as the original key,
+    /// also for composite keys like `(&str, u32)` with an output of `(String, u32)`.
+    pub fn inclusive_from_output<'o>(output: &'o K::Output) -> Self
+    where
+        K::Output: PrimaryKey<'o>,
+    {
+        Bound::InclusiveRaw(output.joined_key())
+    }
+
+    /// Builds an exclusive bound from a deserialized key, to continue pagination after it.
+    /// See [`Bound::inclusive_from_output`].
+    pub fn exclusive_from_output<'o>(output: &'o K::Output) -> Self
+    where
+        K::Output: PrimaryKey<'o>,
+    {
+        Bound::ExclusiveRaw(output.joined_key())
+    }
+}
+
+/// Returns the first byte string after all the ones starting with `prefix`,
+/// or `None` if there is no such string (`prefix` is empty or all 255).
+fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return Some(end);
+        }
+    }
+    None
+}
+
+/// `PrefixBound` is used to define the two ends of a `prefix_range`, bounding the leading
+/// elements of a composite key rather than the whole key.
+///
+/// Integer prefixes of any width (including `i128` and `u128`) are stored in big-endian,
+/// with the sign bit flipped for signed types, so they are bounded by numeric value:
+/// negative prefixes come before positive ones. An exclusive bound skips all keys under
+/// the given prefix, even at the maximum value of the type.
 #[derive(Clone, Debug)]
 pub enum PrefixBound<'a, K: Prefixer<'a>> {
     Inclusive((K, PhantomData<&'a bool>)),
@@ -63,6 +117,23 @@ impl<'a, K: Prefixer<'a>> PrefixBound<'a, K> {
         Self::Exclusive((k.into(), PhantomData))
     }
 
+    /// Cursor for resuming an ascending `prefix_range` after the given prefix, to be passed as
+    /// the lower bound. All keys under the prefix are skipped, not only the prefix itself.
+    ///
+    /// This is the same as [`PrefixBound::exclusive`]: an exclusive prefix bound excludes
+    /// everything stored under that (length-prefixed) prefix, on either side of the range.
+    pub fn after<T: Into<K>>(k: T) -> Self {
+        Self::exclusive(k)
+    }
+
+    /// Cursor for resuming a descending `prefix_range` before the given prefix, to be passed as
+    /// the upper bound. All keys under the prefix are skipped, not only the prefix itself.
+    ///
+    /// This is the same as [`PrefixBound::exclusive`].
+    pub fn before<T: Into<K>>(k: T) -> Self {
+        Self::exclusive(k)
+    }
+
     pub fn to_raw_bound(&self) -> RawBound {
         match self {
             PrefixBound::Exclusive((k, _)) => RawBound::Exclusive(k.joined_prefix()),
@@ -123,6 +194,22 @@ impl<
     }
 }
 
+impl<
+        'a,
+        T: PrimaryKey<'a> + Prefixer<'a> + Clone,
+        U: PrimaryKey<'a> + Prefixer<'a> + Clone,
+        V: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize + Clone,
+        W: PrimaryKey<'a> + KeyDeserialize + Clone,
+    > Bounder<'a> for (T, U, V, W)
+{
+    fn inclusive_bound(self) -> Option<Bound<'a, Self>> {
+        Some(Bound::inclusive(self))
+    }
+    fn exclusive_bound(self) -> Option<Bound<'a, Self>> {
+        Some(Bound::exclusive(self))
+    }
+}
+
 impl<'a> Bounder<'a> for &'a str {
     fn inclusive_bound(self) -> Option<Bound<'a, Self>> {
         Some(Bound::inclusive(self))
@@ -141,6 +228,24 @@ impl<'a> Bounder<'a> for String {
     }
 }
 
+impl<'a> Bounder<'a> for Cow<'a, str> {
+    fn inclusive_bound(self) -> Option<Bound<'a, Self>> {
+        Some(Bound::inclusive(self))
+    }
+    fn exclusive_bound(self) -> Option<Bound<'a, Self>> {
+        Some(Bound::exclusive(self))
+    }
+}
+
+impl<'a> Bounder<'a> for Cow<'a, [u8]> {
+    fn inclusive_bound(self) -> Option<Bound<'a, Self>> {
+        Some(Bound::inclusive(self))
+    }
+    fn exclusive_bound(self) -> Option<Bound<'a, Self>> {
+        Some(Bound::exclusive(self))
+    }
+}
+
 impl<'a> Bounder<'a> for Vec<u8> {
     fn inclusive_bound(self) -> Option<Bound<'a, Self>> {
         Some(Bound::inclusive(self))
@@ -168,6 +273,15 @@ impl<'a> Bounder<'a> for Addr {
     }
 }
 
+impl<'a, const N: usize> Bounder<'a> for PaddedString<N> {
+    fn inclusive_bound(self) -> Option<Bound<'a, Self>> {
+        Some(Bound::inclusive(self))
+    }
+    fn exclusive_bound(self) -> Option<Bound<'a, Self>> {
+        Some(Bound::exclusive(self))
+    }
+}
+
 macro_rules! integer_bound {
     (for $($t:ty),+) => {
         $(impl<'a> Bounder<'a> for $t {
@@ -181,4 +295,4 @@ macro_rules! integer_bound {
     }
 }
 
-integer_bound!(for i8, u8, i16, u16, i32, u32, i64, u64);
+integer_bound!(for bool, char, i8, u8, i16, u16, i32, u32, i64, u64);
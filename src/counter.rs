@@ -0,0 +1,111 @@
+use cosmwasm_std::{OverflowError, OverflowOperation, StdError, StdResult, Storage};
+
+use crate::item::Item;
+use crate::namespace::Namespace;
+
+/// `Counter` stores a single `u64`, like an `Item<u64>`, but only allows changing it by one step
+/// at a time with checked arithmetic. A counter that was never written reads as `0`.
+pub struct Counter {
+    item: Item<u64>,
+}
+
+impl Counter {
+    /// Creates a new [`Counter`] with the given storage key. This is a const fn only suitable
+    /// when you have the storage key in the form of a static string slice.
+    pub const fn new(storage_key: &'static str) -> Self {
+        Counter {
+            item: Item::new(storage_key),
+        }
+    }
+
+    /// Creates a new [`Counter`] with the given storage key. Use this if you might need to handle
+    /// a dynamic string. Otherwise, you might prefer [`Counter::new`].
+    pub fn new_dyn(storage_key: impl Into<Namespace>) -> Self {
+        Counter {
+            item: Item::new_dyn(storage_key),
+        }
+    }
+
+    /// Returns the underlying `Item`, which shares the storage layout of the counter.
+    pub fn item(&self) -> &Item<u64> {
+        &self.item
+    }
+
+    /// Returns the current value, or `0` if the counter was never written.
+    pub fn current(&self, store: &dyn Storage) -> StdResult<u64> {
+        Ok(self.item.may_load(store)?.unwrap_or_default())
+    }
+
+    /// Adds one to the counter and returns the new value.
+    /// Returns an overflow error (and leaves the counter untouched) at `u64::MAX`.
+    pub fn increment(&self, store: &mut dyn Storage) -> StdResult<u64> {
+        let value = self
+            .current(store)?
+            .checked_add(1)
+            .ok_or_else(|| StdError::overflow(OverflowError::new(OverflowOperation::Add)))?;
+        self.item.save(store, &value)?;
+        Ok(value)
+    }
+
+    /// Subtracts one from the counter and returns the new value.
+    /// Returns an overflow error (and leaves the counter untouched) at `0`.
+    pub fn decrement(&self, store: &mut dyn Storage) -> StdResult<u64> {
+        let value = self
+            .current(store)?
+            .checked_sub(1)
+            .ok_or_else(|| StdError::overflow(OverflowError::new(OverflowOperation::Sub)))?;
+        self.item.save(store, &value)?;
+        Ok(value)
+    }
+
+    /// Returns the current value and increments the counter, which is handy for assigning
+    /// sequential ids starting at `0`.
+    pub fn next_id(&self, store: &mut dyn Storage) -> StdResult<u64> {
+        let id = self.current(store)?;
+        self.increment(store)?;
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+
+    const COUNTER: Counter = Counter::new("counter");
+
+    #[test]
+    fn counting_works() {
+        let mut store = MockStorage::new();
+
+        assert_eq!(COUNTER.current(&store).unwrap(), 0);
+        assert_eq!(COUNTER.next_id(&mut store).unwrap(), 0);
+        assert_eq!(COUNTER.next_id(&mut store).unwrap(), 1);
+        assert_eq!(COUNTER.current(&store).unwrap(), 2);
+
+        assert_eq!(COUNTER.increment(&mut store).unwrap(), 3);
+        assert_eq!(COUNTER.decrement(&mut store).unwrap(), 2);
+        assert_eq!(COUNTER.item().load(&store).unwrap(), 2);
+    }
+
+    #[test]
+    fn overflow_and_underflow_error() {
+        let mut store = MockStorage::new();
+
+        let err = COUNTER.decrement(&mut store).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::overflow(OverflowError::new(OverflowOperation::Sub))
+        );
+        assert_eq!(COUNTER.current(&store).unwrap(), 0);
+
+        COUNTER.item().save(&mut store, &u64::MAX).unwrap();
+        let err = COUNTER.increment(&mut store).unwrap_err();
+        assert_eq!(
+            err,
+            StdError::overflow(OverflowError::new(OverflowOperation::Add))
+        );
+        assert!(COUNTER.next_id(&mut store).is_err());
+        assert_eq!(COUNTER.current(&store).unwrap(), u64::MAX);
+    }
+}
@@ -1,7 +1,9 @@
-use std::array::TryFromSliceError;
+use std::borrow::Cow;
 use std::convert::TryInto;
 
-use cosmwasm_std::{Addr, Int128, Int64, StdError, StdResult, Uint128, Uint64};
+use cosmwasm_std::{
+    Addr, Binary, Decimal, Int128, Int256, Int64, StdError, StdResult, Uint128, Uint256, Uint64,
+};
 
 use crate::int_key::IntKey;
 
@@ -14,6 +16,8 @@ pub trait KeyDeserialize {
 
     fn from_vec(value: Vec<u8>) -> StdResult<Self::Output>;
 
+    /// Deserializes a borrowed key. The default copies it into a `Vec` for `from_vec`,
+    /// so fixed-size keys override this to parse the bytes in place.
     fn from_slice(value: &[u8]) -> StdResult<Self::Output> {
         Self::from_vec(value.to_vec())
     }
@@ -52,6 +56,28 @@ impl KeyDeserialize for &Vec<u8> {
     }
 }
 
+impl KeyDeserialize for Binary {
+    type Output = Binary;
+
+    const KEY_ELEMS: u16 = 1;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        Ok(Binary::new(value))
+    }
+}
+
+impl KeyDeserialize for &Binary {
+    type Output = Binary;
+
+    const KEY_ELEMS: u16 = 1;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        Self::Output::from_vec(value)
+    }
+}
+
 impl KeyDeserialize for &[u8] {
     type Output = Vec<u8>;
 
@@ -72,6 +98,13 @@ impl<const N: usize> KeyDeserialize for [u8; N] {
     fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
         <[u8; N]>::try_from(value).map_err(|v: Vec<_>| StdError::invalid_data_size(N, v.len()))
     }
+
+    #[inline(always)]
+    fn from_slice(value: &[u8]) -> StdResult<Self::Output> {
+        value
+            .try_into()
+            .map_err(|_| StdError::invalid_data_size(N, value.len()))
+    }
 }
 
 impl<const N: usize> KeyDeserialize for &[u8; N] {
@@ -83,6 +116,11 @@ impl<const N: usize> KeyDeserialize for &[u8; N] {
     fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
         <[u8; N]>::from_vec(value)
     }
+
+    #[inline(always)]
+    fn from_slice(value: &[u8]) -> StdResult<Self::Output> {
+        <[u8; N]>::from_slice(value)
+    }
 }
 
 impl KeyDeserialize for String {
@@ -118,6 +156,28 @@ impl KeyDeserialize for &str {
     }
 }
 
+impl KeyDeserialize for Cow<'_, [u8]> {
+    type Output = Vec<u8>;
+
+    const KEY_ELEMS: u16 = 1;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        Ok(value)
+    }
+}
+
+impl KeyDeserialize for Cow<'_, str> {
+    type Output = String;
+
+    const KEY_ELEMS: u16 = 1;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        String::from_vec(value)
+    }
+}
+
 impl KeyDeserialize for Addr {
     type Output = Addr;
 
@@ -149,14 +209,103 @@ macro_rules! integer_de {
 
             #[inline(always)]
             fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
-                Ok(<$t>::from_cw_bytes(value.as_slice().try_into()
-                    .map_err(|err: TryFromSliceError| StdError::generic_err(err.to_string()))?))
+                Self::from_slice(&value)
+            }
+
+            #[inline(always)]
+            fn from_slice(value: &[u8]) -> StdResult<Self::Output> {
+                // an over-long key means the tuple split went wrong or the data is corrupt,
+                // so don't just read the first bytes
+                Ok(<$t>::from_cw_bytes(value.try_into()
+                    .map_err(|_| StdError::invalid_data_size(std::mem::size_of::<$t>(), value.len()))?))
             }
         })*
     }
 }
 
-integer_de!(for i8, u8, i16, u16, i32, u32, i64, u64, i128, u128, Uint64, Uint128, Int64, Int128);
+integer_de!(for i8, u8, i16, u16, i32, u32, i64, u64, i128, u128, Uint64, Uint128, Int64, Int128, Uint256, Int256);
+
+impl<K: KeyDeserialize> KeyDeserialize for Option<K> {
+    type Output = Option<K::Output>;
+
+    const KEY_ELEMS: u16 = 1;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        Self::from_slice(&value)
+    }
+
+    #[inline(always)]
+    fn from_slice(value: &[u8]) -> StdResult<Self::Output> {
+        match value.split_first() {
+            Some((0, [])) => Ok(None),
+            Some((1, inner)) => K::from_slice(inner).map(Some),
+            Some((0, _)) => Err(StdError::invalid_data_size(1, value.len())),
+            Some((b, _)) => Err(StdError::generic_err(format!(
+                "Invalid option key: expected 0 or 1, got {}",
+                b
+            ))),
+            None => Err(StdError::invalid_data_size(1, 0)),
+        }
+    }
+}
+
+impl KeyDeserialize for Decimal {
+    type Output = Decimal;
+
+    const KEY_ELEMS: u16 = 1;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        Self::from_slice(&value)
+    }
+
+    #[inline(always)]
+    fn from_slice(value: &[u8]) -> StdResult<Self::Output> {
+        Ok(Decimal::raw(u128::from_slice(value)?))
+    }
+}
+
+impl KeyDeserialize for bool {
+    type Output = bool;
+
+    const KEY_ELEMS: u16 = 1;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        match value.as_slice() {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            [b] => Err(StdError::generic_err(format!(
+                "Invalid bool key: expected 0 or 1, got {}",
+                b
+            ))),
+            _ => Err(StdError::invalid_data_size(1, value.len())),
+        }
+    }
+}
+
+impl KeyDeserialize for char {
+    type Output = char;
+
+    const KEY_ELEMS: u16 = 1;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        let code = u32::from_be_bytes(
+            value
+                .as_slice()
+                .try_into()
+                .map_err(|_| StdError::invalid_data_size(4, value.len()))?,
+        );
+        char::from_u32(code).ok_or_else(|| {
+            StdError::generic_err(format!(
+                "Invalid char key: {:#x} is not a Unicode scalar value",
+                code
+            ))
+        })
+    }
+}
 
 fn parse_length(value: &[u8]) -> StdResult<usize> {
     Ok(u16::from_be_bytes(
@@ -170,13 +319,15 @@ fn parse_length(value: &[u8]) -> StdResult<usize> {
 /// Splits the first key from the value based on the provided number of key elements.
 /// The return value is ordered as (first_key, remainder).
 ///
-fn split_first_key(key_elems: u16, value: &[u8]) -> StdResult<(Vec<u8>, &[u8])> {
+pub(crate) fn split_first_key(key_elems: u16, value: &[u8]) -> StdResult<(Vec<u8>, &[u8])> {
     let mut index = 0;
     let mut first_key = Vec::new();
 
     // Iterate over the sub keys
     for i in 0..key_elems {
-        let len_slice = &value[index..index + 2];
+        let len_slice = value
+            .get(index..index + 2)
+            .ok_or_else(|| StdError::generic_err("Key is too short for its number of elements"))?;
         index += 2;
         let is_last_key = i == key_elems - 1;
 
@@ -185,7 +336,10 @@ fn split_first_key(key_elems: u16, value: &[u8]) -> StdResult<(Vec<u8>, &[u8])>
         }
 
         let subkey_len = parse_length(len_slice)?;
-        first_key.extend_from_slice(&value[index..index + subkey_len]);
+        let subkey = value
+            .get(index..index + subkey_len)
+            .ok_or_else(|| StdError::generic_err("Key is shorter than its encoded length"))?;
+        first_key.extend_from_slice(subkey);
         index += subkey_len;
     }
 
@@ -218,6 +372,27 @@ impl<T: KeyDeserialize, U: KeyDeserialize, V: KeyDeserialize> KeyDeserialize for
     }
 }
 
+impl<T: KeyDeserialize, U: KeyDeserialize, V: KeyDeserialize, W: KeyDeserialize> KeyDeserialize
+    for (T, U, V, W)
+{
+    type Output = (T::Output, U::Output, V::Output, W::Output);
+
+    const KEY_ELEMS: u16 = T::KEY_ELEMS + U::KEY_ELEMS + V::KEY_ELEMS + W::KEY_ELEMS;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        let (t, remainder) = split_first_key(T::KEY_ELEMS, value.as_ref())?;
+        let (u, remainder) = split_first_key(U::KEY_ELEMS, remainder)?;
+        let (v, w) = split_first_key(V::KEY_ELEMS, remainder)?;
+        Ok((
+            T::from_vec(t)?,
+            U::from_vec(u)?,
+            V::from_vec(v)?,
+            W::from_vec(w.to_vec())?,
+        ))
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -239,6 +414,111 @@ mod test {
         assert_eq!(<&[u8]>::from_slice(BYTES).unwrap(), BYTES);
         assert_eq!(<[u8; 5]>::from_slice(BYTES).unwrap(), BYTES);
         assert_eq!(<&[u8; 5]>::from_slice(BYTES).unwrap(), BYTES);
+        assert_eq!(<Cow<[u8]>>::from_slice(BYTES).unwrap(), BYTES);
+    }
+
+    #[test]
+    fn deserialize_bool_works() {
+        assert!(!<bool>::from_vec(false.joined_key()).unwrap());
+        assert!(<bool>::from_vec(true.joined_key()).unwrap());
+        assert_eq!(
+            <bool>::from_slice(&[2]).unwrap_err(),
+            StdError::generic_err("Invalid bool key: expected 0 or 1, got 2")
+        );
+        assert!(matches!(
+            <bool>::from_slice(&[]).unwrap_err(),
+            StdError::InvalidDataSize { .. }
+        ));
+        assert!(matches!(
+            <bool>::from_slice(&[0, 1]).unwrap_err(),
+            StdError::InvalidDataSize { .. }
+        ));
+        assert_eq!(
+            <(bool, String)>::from_vec((true, "x").joined_key()).unwrap(),
+            (true, "x".to_string())
+        );
+    }
+
+    #[test]
+    fn deserialize_decimal_works() {
+        let price = Decimal::from_ratio(1234u128, 1000u128);
+        assert_eq!(<Decimal>::from_vec(price.joined_key()).unwrap(), price);
+        assert_eq!(
+            <Decimal>::from_vec(Decimal::MAX.joined_key()).unwrap(),
+            Decimal::MAX
+        );
+        assert!(matches!(
+            <Decimal>::from_slice(&[1, 2]).unwrap_err(),
+            StdError::InvalidDataSize { .. }
+        ));
+    }
+
+    #[test]
+    fn deserialize_option_works() {
+        assert_eq!(
+            <Option<u32>>::from_vec(Some(7u32).joined_key()).unwrap(),
+            Some(7)
+        );
+        assert_eq!(
+            <Option<u32>>::from_vec(Some(0u32).joined_key()).unwrap(),
+            Some(0)
+        );
+        assert_eq!(
+            <Option<u32>>::from_vec(None::<u32>.joined_key()).unwrap(),
+            None
+        );
+        assert_eq!(
+            <Option<String>>::from_vec(Some("").joined_key()).unwrap(),
+            Some(String::new())
+        );
+        assert_eq!(
+            <Option<&Addr>>::from_vec(Some(&Addr::unchecked("addr")).joined_key()).unwrap(),
+            Some(Addr::unchecked("addr"))
+        );
+        assert_eq!(
+            <Option<(String, u8)>>::from_vec(Some(("a", 1u8)).joined_key()).unwrap(),
+            Some(("a".to_string(), 1))
+        );
+        assert!(matches!(
+            <Option<u32>>::from_slice(&[1, 2]).unwrap_err(),
+            StdError::InvalidDataSize { .. }
+        ));
+        assert!(matches!(
+            <Option<u32>>::from_slice(&[]).unwrap_err(),
+            StdError::InvalidDataSize { .. }
+        ));
+        assert!(matches!(
+            <Option<u32>>::from_slice(&[2]).unwrap_err(),
+            StdError::GenericErr { .. }
+        ));
+        assert_eq!(
+            <(String, Option<u64>)>::from_vec(("addr", None::<u64>).joined_key()).unwrap(),
+            ("addr".to_string(), None)
+        );
+        assert_eq!(
+            <(Option<u64>, String)>::from_vec((Some(3u64), "addr").joined_key()).unwrap(),
+            (Some(3), "addr".to_string())
+        );
+        assert_eq!(
+            <(Addr, Option<String>)>::from_vec(("addr", Some("x")).joined_key()).unwrap(),
+            (Addr::unchecked("addr"), Some("x".to_string()))
+        );
+    }
+
+    #[test]
+    fn deserialize_char_works() {
+        assert_eq!(<char>::from_vec('a'.joined_key()).unwrap(), 'a');
+        assert_eq!(<char>::from_vec('🦀'.joined_key()).unwrap(), '🦀');
+        // surrogates are not chars
+        assert_eq!(
+            <char>::from_slice(&0xD800u32.to_be_bytes()).unwrap_err(),
+            StdError::generic_err("Invalid char key: 0xd800 is not a Unicode scalar value")
+        );
+        assert!(<char>::from_slice(&0x110000u32.to_be_bytes()).is_err());
+        assert!(matches!(
+            <char>::from_slice(b"a").unwrap_err(),
+            StdError::InvalidDataSize { .. }
+        ));
     }
 
     #[test]
@@ -246,6 +526,7 @@ mod test {
         assert_eq!(<String>::from_slice(BYTES).unwrap(), STRING);
         assert_eq!(<&String>::from_slice(BYTES).unwrap(), STRING);
         assert_eq!(<&str>::from_slice(BYTES).unwrap(), STRING);
+        assert_eq!(<Cow<str>>::from_slice(BYTES).unwrap(), STRING);
     }
 
     #[test]
@@ -262,6 +543,17 @@ mod test {
         assert_eq!(<&Addr>::from_slice(BYTES).unwrap(), Addr::unchecked(STRING));
     }
 
+    #[test]
+    fn deserialize_binary_works() {
+        let data = Binary::from(BYTES);
+        assert_eq!(<Binary>::from_vec(data.joined_key()).unwrap(), data);
+        assert_eq!(<&Binary>::from_slice(BYTES).unwrap(), data);
+        assert_eq!(
+            <(Binary, u32)>::from_vec((&data, 7u32).joined_key()).unwrap(),
+            (data, 7)
+        );
+    }
+
     #[test]
     fn deserialize_broken_addr_errs() {
         assert!(matches!(
@@ -318,6 +610,62 @@ mod test {
         );
     }
 
+    #[test]
+    fn deserialize_std_256_integers_works() {
+        for value in [Uint256::zero(), Uint256::from(4242u128), Uint256::MAX] {
+            assert_eq!(Uint256::from_slice(&value.joined_key()).unwrap(), value);
+        }
+        for value in [Int256::MIN, Int256::from(-1), Int256::zero(), Int256::MAX] {
+            assert_eq!(Int256::from_slice(&value.joined_key()).unwrap(), value);
+        }
+
+        let mut bytes = [0u8; 32];
+        bytes[0] = 0x80;
+        assert_eq!(Int256::from_slice(&bytes).unwrap(), Int256::zero());
+        assert!(Uint256::from_slice(&[0u8; 16]).is_err());
+    }
+
+    #[test]
+    fn from_slice_matches_from_vec_for_fixed_size_keys() {
+        let bytes = 0x0102030405060708u64.to_cw_bytes();
+        assert_eq!(
+            u64::from_slice(&bytes).unwrap(),
+            u64::from_vec(bytes.to_vec()).unwrap()
+        );
+        assert_eq!(i32::from_slice(&[128, 0, 0, 1]).unwrap(), 1);
+        assert_eq!(
+            Uint128::from_slice(&7u128.to_cw_bytes()).unwrap(),
+            Uint128::new(7)
+        );
+        assert_eq!(
+            Decimal::from_slice(&Decimal::percent(5).atomics().u128().to_cw_bytes()).unwrap(),
+            Decimal::percent(5)
+        );
+        assert_eq!(<[u8; 3]>::from_slice(b"abc").unwrap(), *b"abc");
+        assert_eq!(<&[u8; 3]>::from_slice(b"abc").unwrap(), *b"abc");
+        assert_eq!(<Option<u16>>::from_slice(&[0]).unwrap(), None);
+        assert_eq!(<Option<u16>>::from_slice(&[1, 1, 0]).unwrap(), Some(256));
+
+        // the size is still checked
+        assert!(matches!(
+            u64::from_slice(&[1, 2, 3]).unwrap_err(),
+            StdError::InvalidDataSize {
+                expected: 8,
+                actual: 3,
+                ..
+            }
+        ));
+        assert!(u16::from_slice(&[1, 2, 3]).is_err());
+        assert!(<[u8; 3]>::from_slice(b"abcd").is_err());
+
+        // and composite keys still split before parsing the elements
+        let key = (7u32, 1u64, [9u8; 2]).joined_key();
+        assert_eq!(
+            <(u32, u64, [u8; 2])>::from_slice(&key).unwrap(),
+            (7, 1, [9; 2])
+        );
+    }
+
     #[test]
     fn deserialize_tuple_works() {
         assert_eq!(
@@ -326,6 +674,43 @@ mod test {
         );
     }
 
+    #[test]
+    fn deserialize_tuple_rejects_malformed_keys() {
+        let mut key = (1u32, 2u32).joined_key();
+        assert_eq!(<(u32, u32)>::from_slice(&key).unwrap(), (1, 2));
+
+        // trailing bytes after the fixed-width last element
+        key.extend_from_slice(&[0, 0]);
+        assert_eq!(
+            <(u32, u32)>::from_slice(&key).unwrap_err(),
+            StdError::invalid_data_size(4, 6)
+        );
+
+        // missing bytes in the last element
+        key.truncate(key.len() - 3);
+        assert_eq!(
+            <(u32, u32)>::from_slice(&key).unwrap_err(),
+            StdError::invalid_data_size(4, 3)
+        );
+
+        // truncated within the first element errors instead of panicking
+        assert!(<(u32, u32)>::from_slice(&key[..4]).is_err());
+        assert!(<(u32, u32)>::from_slice(&key[..1]).is_err());
+        assert!(<(String, u32, u32)>::from_slice(&[0, 1, b'a', 0]).is_err());
+    }
+
+    #[test]
+    fn deserialize_quadruple_works() {
+        assert_eq!(
+            <(&[u8], u64, u8, &str)>::from_slice(
+                (BYTES, 42u64, 7u8, STRING).joined_key().as_slice()
+            )
+            .unwrap(),
+            (BYTES.to_vec(), 42, 7, STRING.to_string())
+        );
+        assert_eq!(<(&[u8], u64, u8, &str)>::KEY_ELEMS, 4);
+    }
+
     #[test]
     fn deserialize_tuple_of_tuples_works() {
         assert_eq!(
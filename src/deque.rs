@@ -1,5 +1,7 @@
 use std::{any::type_name, convert::TryInto, marker::PhantomData};
 
+#[cfg(feature = "iterator")]
+use cosmwasm_std::Order;
 use cosmwasm_std::{
     from_json, storage_keys::namespace_with_key, to_json_vec, StdError, StdResult, Storage,
 };
@@ -8,9 +10,22 @@ use serde::{de::DeserializeOwned, Serialize};
 use crate::namespace::Namespace;
 
 // metadata keys need to have different length than the position type (4 bytes) to prevent collisions
+const META_KEY: &[u8] = b"m";
+// separate head and tail keys written by older versions, replaced by `META_KEY` on the next write
 const TAIL_KEY: &[u8] = b"t";
 const HEAD_KEY: &[u8] = b"h";
 
+/// The head and tail positions of a deque, stored together under `META_KEY`
+#[derive(Clone, Copy)]
+struct Meta {
+    /// Unless the deque is empty, this points to the first element
+    head: u32,
+    /// This points to the first empty position after the last element
+    tail: u32,
+    /// Whether the positions were read from the separate keys of older versions
+    legacy: bool,
+}
+
 /// A deque stores multiple items at the given key. It provides efficient FIFO and LIFO access,
 /// as well as direct index access.
 ///
@@ -47,10 +62,17 @@ impl<T: Serialize + DeserializeOwned> Deque<T> {
     /// Adds the given value to the end of the deque
     pub fn push_back(&self, storage: &mut dyn Storage, value: &T) -> StdResult<()> {
         // save value
-        let pos = self.tail(storage)?;
+        let meta = self.meta(storage)?;
+        let pos = meta.tail;
         self.set_unchecked(storage, pos, value)?;
         // update tail
-        self.set_tail(storage, pos.wrapping_add(1));
+        self.set_meta(
+            storage,
+            Meta {
+                tail: pos.wrapping_add(1),
+                ..meta
+            },
+        );
 
         Ok(())
     }
@@ -58,10 +80,11 @@ impl<T: Serialize + DeserializeOwned> Deque<T> {
     /// Adds the given value to the front of the deque
     pub fn push_front(&self, storage: &mut dyn Storage, value: &T) -> StdResult<()> {
         // need to subtract first, because head potentially points to existing element
-        let pos = self.head(storage)?.wrapping_sub(1);
+        let meta = self.meta(storage)?;
+        let pos = meta.head.wrapping_sub(1);
         self.set_unchecked(storage, pos, value)?;
         // update head
-        self.set_head(storage, pos);
+        self.set_meta(storage, Meta { head: pos, ..meta });
 
         Ok(())
     }
@@ -69,12 +92,13 @@ impl<T: Serialize + DeserializeOwned> Deque<T> {
     /// Removes the last element of the deque and returns it
     pub fn pop_back(&self, storage: &mut dyn Storage) -> StdResult<Option<T>> {
         // get position
-        let pos = self.tail(storage)?.wrapping_sub(1);
+        let meta = self.meta(storage)?;
+        let pos = meta.tail.wrapping_sub(1);
         let value = self.get_unchecked(storage, pos)?;
         if value.is_some() {
             self.remove_unchecked(storage, pos);
             // only update tail if a value was popped
-            self.set_tail(storage, pos);
+            self.set_meta(storage, Meta { tail: pos, ..meta });
         }
         Ok(value)
     }
@@ -82,12 +106,19 @@ impl<T: Serialize + DeserializeOwned> Deque<T> {
     /// Removes the first element of the deque and returns it
     pub fn pop_front(&self, storage: &mut dyn Storage) -> StdResult<Option<T>> {
         // get position
-        let pos = self.head(storage)?;
+        let meta = self.meta(storage)?;
+        let pos = meta.head;
         let value = self.get_unchecked(storage, pos)?;
         if value.is_some() {
             self.remove_unchecked(storage, pos);
             // only update head if a value was popped
-            self.set_head(storage, pos.wrapping_add(1));
+            self.set_meta(
+                storage,
+                Meta {
+                    head: pos.wrapping_add(1),
+                    ..meta
+                },
+            );
         }
         Ok(value)
     }
@@ -105,9 +136,14 @@ impl<T: Serialize + DeserializeOwned> Deque<T> {
     }
 
     /// Gets the length of the deque.
+    ///
+    /// This reads the head and tail indices, which are stored together under a single key,
+    /// so it costs a single read and never touches the elements. Deques last written by an older
+    /// version store them under separate keys, which are read as a fallback until the next write.
     #[allow(clippy::len_without_is_empty)]
     pub fn len(&self, storage: &dyn Storage) -> StdResult<u32> {
-        Ok(calc_len(self.head(storage)?, self.tail(storage)?))
+        let Meta { head, tail, .. } = self.meta(storage)?;
+        Ok(calc_len(head, tail))
     }
 
     /// Returns `true` if the deque contains no elements.
@@ -115,12 +151,55 @@ impl<T: Serialize + DeserializeOwned> Deque<T> {
         Ok(self.len(storage)? == 0)
     }
 
+    /// Removes up to `limit` elements (or all if `limit == None`) from the front of the deque,
+    /// and returns how many were removed, so clearing in chunks can stop once this is `0`.
+    /// Positions are known, so no elements are loaded, but every element is removed individually.
+    ///
+    /// Once the deque is empty, the head and tail positions are removed as well, so it is in the
+    /// same state as a fresh one.
+    pub fn clear(&self, storage: &mut dyn Storage, limit: Option<u32>) -> StdResult<u32> {
+        let meta = self.meta(storage)?;
+        let len = calc_len(meta.head, meta.tail);
+        let removed = limit.map_or(len, |limit| limit.min(len));
+        for i in 0..removed {
+            self.remove_unchecked(storage, meta.head.wrapping_add(i));
+        }
+        if removed == len {
+            for key in [META_KEY, HEAD_KEY, TAIL_KEY] {
+                storage.remove(&namespace_with_key(&[self.namespace.as_slice()], key));
+            }
+        } else {
+            self.set_meta(
+                storage,
+                Meta {
+                    head: meta.head.wrapping_add(removed),
+                    ..meta
+                },
+            );
+        }
+        Ok(removed)
+    }
+
+    /// Returns the storage position of the first element, for custom iteration or debugging.
+    ///
+    /// Positions are `u32` values that wrap around, so the element at index `i` is stored at
+    /// `head_index().wrapping_add(i)`, and the length is `tail_index().wrapping_sub(head_index())`.
+    pub fn head_index(&self, storage: &dyn Storage) -> StdResult<u32> {
+        self.head(storage)
+    }
+
+    /// Returns the storage position after the last element, for custom iteration or debugging.
+    /// See [`Deque::head_index`] for how positions relate to indices.
+    pub fn tail_index(&self, storage: &dyn Storage) -> StdResult<u32> {
+        self.tail(storage)
+    }
+
     /// Gets the head position from storage.
     ///
     /// Unless the deque is empty, this points to the first element.
     #[inline]
     fn head(&self, storage: &dyn Storage) -> StdResult<u32> {
-        self.read_meta_key(storage, HEAD_KEY)
+        Ok(self.meta(storage)?.head)
     }
 
     /// Gets the tail position from storage.
@@ -128,21 +207,78 @@ impl<T: Serialize + DeserializeOwned> Deque<T> {
     /// This points to the first empty position after the last element.
     #[inline]
     fn tail(&self, storage: &dyn Storage) -> StdResult<u32> {
-        self.read_meta_key(storage, TAIL_KEY)
+        Ok(self.meta(storage)?.tail)
     }
 
-    #[inline]
+    #[cfg(test)]
     fn set_head(&self, storage: &mut dyn Storage, value: u32) {
-        self.set_meta_key(storage, HEAD_KEY, value);
+        let meta = self.meta(storage).unwrap();
+        self.set_meta(
+            storage,
+            Meta {
+                head: value,
+                ..meta
+            },
+        );
     }
 
-    #[inline]
+    #[cfg(test)]
     fn set_tail(&self, storage: &mut dyn Storage, value: u32) {
-        self.set_meta_key(storage, TAIL_KEY, value);
+        let meta = self.meta(storage).unwrap();
+        self.set_meta(
+            storage,
+            Meta {
+                tail: value,
+                ..meta
+            },
+        );
+    }
+
+    /// Reads the head and tail positions with a single read, falling back to the separate keys
+    /// written by older versions if the combined key is not set.
+    fn meta(&self, storage: &dyn Storage) -> StdResult<Meta> {
+        let full_key = namespace_with_key(&[self.namespace.as_slice()], META_KEY);
+        if let Some(value) = storage.get(&full_key) {
+            let value: [u8; 8] = value
+                .as_slice()
+                .try_into()
+                .map_err(|e| StdError::parse_err("deque metadata", e))?;
+            let (head, tail) = value.split_at(4);
+            return Ok(Meta {
+                head: u32::from_be_bytes(head.try_into().unwrap()),
+                tail: u32::from_be_bytes(tail.try_into().unwrap()),
+                legacy: false,
+            });
+        }
+
+        let head = self.read_meta_key(storage, HEAD_KEY)?;
+        let tail = self.read_meta_key(storage, TAIL_KEY)?;
+        Ok(Meta {
+            head: head.unwrap_or(0),
+            tail: tail.unwrap_or(0),
+            legacy: head.is_some() || tail.is_some(),
+        })
+    }
+
+    /// Writes the head and tail positions under the combined key, removing the separate keys
+    /// of older versions if they were read.
+    fn set_meta(&self, storage: &mut dyn Storage, meta: Meta) {
+        let mut value = [0u8; 8];
+        value[..4].copy_from_slice(&meta.head.to_be_bytes());
+        value[4..].copy_from_slice(&meta.tail.to_be_bytes());
+        storage.set(
+            &namespace_with_key(&[self.namespace.as_slice()], META_KEY),
+            &value,
+        );
+        if meta.legacy {
+            for key in [HEAD_KEY, TAIL_KEY] {
+                storage.remove(&namespace_with_key(&[self.namespace.as_slice()], key));
+            }
+        }
     }
 
-    /// Helper method for `tail` and `head` methods to handle reading the value from storage
-    fn read_meta_key(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<u32> {
+    /// Reads one of the separate head and tail keys written by older versions
+    fn read_meta_key(&self, storage: &dyn Storage, key: &[u8]) -> StdResult<Option<u32>> {
         let full_key = namespace_with_key(&[self.namespace.as_slice()], key);
         storage
             .get(&full_key)
@@ -153,20 +289,12 @@ impl<T: Serialize + DeserializeOwned> Deque<T> {
                         .map_err(|e| StdError::parse_err("u32", e))?,
                 ))
             })
-            .unwrap_or(Ok(0))
-    }
-
-    /// Helper method for `set_tail` and `set_head` methods to write to storage
-    #[inline]
-    fn set_meta_key(&self, storage: &mut dyn Storage, key: &[u8], value: u32) {
-        let full_key = namespace_with_key(&[self.namespace.as_slice()], key);
-        storage.set(&full_key, &value.to_be_bytes());
+            .transpose()
     }
 
     /// Returns the value at the given position in the queue or `None` if the index is out of bounds
     pub fn get(&self, storage: &dyn Storage, pos: u32) -> StdResult<Option<T>> {
-        let head = self.head(storage)?;
-        let tail = self.tail(storage)?;
+        let Meta { head, tail, .. } = self.meta(storage)?;
 
         if pos >= calc_len(head, tail) {
             // out of bounds
@@ -179,17 +307,173 @@ impl<T: Serialize + DeserializeOwned> Deque<T> {
             .map(Some)
     }
 
+    /// Returns `true` if the given position is within the bounds of the queue.
+    /// This only reads the head and tail, so it is cheaper than `get` and does not fail
+    /// if the stored value cannot be deserialized.
+    pub fn has(&self, storage: &dyn Storage, pos: u32) -> StdResult<bool> {
+        Ok(pos < self.len(storage)?)
+    }
+
     /// Sets the value at the given position in the queue. Returns [`StdError::NotFound`] if index is out of bounds
     pub fn set(&self, storage: &mut dyn Storage, pos: u32, value: &T) -> StdResult<()> {
-        let head = self.head(storage)?;
-        let tail = self.tail(storage)?;
+        let Meta { head, tail, .. } = self.meta(storage)?;
 
         if pos >= calc_len(head, tail) {
             // out of bounds
             return Err(StdError::not_found(format!("deque position {}", pos)));
         }
 
-        self.set_unchecked(storage, pos, value)
+        self.set_unchecked(storage, head.wrapping_add(pos), value)
+    }
+
+    /// Exchanges the values at the given positions in the queue, without deserializing them.
+    /// Returns [`StdError::NotFound`] if either index is out of bounds.
+    pub fn swap(&self, storage: &mut dyn Storage, i: u32, j: u32) -> StdResult<()> {
+        let Meta { head, tail, .. } = self.meta(storage)?;
+        let len = calc_len(head, tail);
+
+        for pos in [i, j] {
+            if pos >= len {
+                // out of bounds
+                return Err(StdError::not_found(format!("deque position {}", pos)));
+            }
+        }
+        if i == j {
+            return Ok(());
+        }
+
+        let key_i = namespace_with_key(
+            &[self.namespace.as_slice()],
+            &head.wrapping_add(i).to_be_bytes(),
+        );
+        let key_j = namespace_with_key(
+            &[self.namespace.as_slice()],
+            &head.wrapping_add(j).to_be_bytes(),
+        );
+        let value_i = storage
+            .get(&key_i)
+            .ok_or_else(|| StdError::not_found(format!("deque position {}", i)))?;
+        let value_j = storage
+            .get(&key_j)
+            .ok_or_else(|| StdError::not_found(format!("deque position {}", j)))?;
+        storage.set(&key_i, &value_j);
+        storage.set(&key_j, &value_i);
+
+        Ok(())
+    }
+
+    /// Inserts the given value at the given position in the queue. Inserting at `len` is
+    /// equivalent to `push_back`. Returns [`StdError::NotFound`] if the position is greater
+    /// than the length.
+    ///
+    /// The elements on the shorter side of `pos` are moved one step outwards, so this is O(n)
+    /// in `min(pos, len - pos)`.
+    pub fn insert(&self, storage: &mut dyn Storage, pos: u32, value: &T) -> StdResult<()> {
+        let meta = self.meta(storage)?;
+        let Meta { head, tail, .. } = meta;
+        let len = calc_len(head, tail);
+
+        if pos > len {
+            // out of bounds
+            return Err(StdError::not_found(format!("deque position {}", pos)));
+        }
+
+        if pos < len - pos {
+            // shift the front towards the front, starting with the first element,
+            // so nothing gets overwritten before it was moved
+            for i in 0..pos {
+                let from = head.wrapping_add(i);
+                self.move_unchecked(storage, from, from.wrapping_sub(1))?;
+            }
+            let head = head.wrapping_sub(1);
+            self.set_unchecked(storage, head.wrapping_add(pos), value)?;
+            self.set_meta(storage, Meta { head, ..meta });
+        } else {
+            // shift the back towards the back, starting with the last element
+            for i in (pos..len).rev() {
+                let from = head.wrapping_add(i);
+                self.move_unchecked(storage, from, from.wrapping_add(1))?;
+            }
+            self.set_unchecked(storage, head.wrapping_add(pos), value)?;
+            self.set_meta(
+                storage,
+                Meta {
+                    tail: tail.wrapping_add(1),
+                    ..meta
+                },
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Removes the element at the given position and returns it, or `None` if the index is
+    /// out of bounds.
+    ///
+    /// The elements on the shorter side of `pos` are moved one step inwards, so this is O(n)
+    /// in `min(pos, len - pos)`.
+    pub fn remove(&self, storage: &mut dyn Storage, pos: u32) -> StdResult<Option<T>> {
+        let meta = self.meta(storage)?;
+        let Meta { head, tail, .. } = meta;
+        let len = calc_len(head, tail);
+
+        if pos >= len {
+            // out of bounds
+            return Ok(None);
+        }
+
+        let removed = head.wrapping_add(pos);
+        let value = self
+            .get_unchecked(storage, removed)?
+            .ok_or_else(|| StdError::not_found(format!("deque position {}", removed)))?;
+
+        if pos < len - pos - 1 {
+            // shift the front towards the back, starting next to the removed element
+            for i in (0..pos).rev() {
+                let from = head.wrapping_add(i);
+                self.move_unchecked(storage, from, from.wrapping_add(1))?;
+            }
+            self.remove_unchecked(storage, head);
+            self.set_meta(
+                storage,
+                Meta {
+                    head: head.wrapping_add(1),
+                    ..meta
+                },
+            );
+        } else {
+            // shift the back towards the front, starting next to the removed element
+            for i in pos + 1..len {
+                let from = head.wrapping_add(i);
+                self.move_unchecked(storage, from, from.wrapping_sub(1))?;
+            }
+            let tail = tail.wrapping_sub(1);
+            self.remove_unchecked(storage, tail);
+            self.set_meta(storage, Meta { tail, ..meta });
+        }
+
+        Ok(Some(value))
+    }
+
+    /// Applies `f` to every element of the queue, from front to back, and stores the results in place.
+    /// The length and positions of the elements are unchanged.
+    ///
+    /// Stops at the first error returned by `f`, in which case the elements before it have
+    /// already been updated.
+    pub fn map_in_place<F>(&self, storage: &mut dyn Storage, mut f: F) -> StdResult<()>
+    where
+        F: FnMut(T) -> StdResult<T>,
+    {
+        let Meta { head, tail, .. } = self.meta(storage)?;
+
+        for i in 0..calc_len(head, tail) {
+            let pos = head.wrapping_add(i);
+            let value = self
+                .get_unchecked(storage, pos)?
+                .ok_or_else(|| StdError::not_found(format!("deque position {}", pos)))?;
+            self.set_unchecked(storage, pos, &f(value)?)?;
+        }
+        Ok(())
     }
 
     /// Tries to get the value at the given position
@@ -207,6 +491,19 @@ impl<T: Serialize + DeserializeOwned> Deque<T> {
         storage.remove(&prefixed_key);
     }
 
+    /// Copies the raw value at `from` to `to`, without deserializing it
+    /// Used internally when shifting elements
+    fn move_unchecked(&self, storage: &mut dyn Storage, from: u32, to: u32) -> StdResult<()> {
+        let from_key = namespace_with_key(&[self.namespace.as_slice()], &from.to_be_bytes());
+        let raw = storage
+            .get(&from_key)
+            .ok_or_else(|| StdError::not_found(format!("deque position {}", from)))?;
+        let to_key = namespace_with_key(&[self.namespace.as_slice()], &to.to_be_bytes());
+        storage.set(&to_key, &raw);
+
+        Ok(())
+    }
+
     /// Tries to set the value at the given position
     /// Used internally when pushing
     fn set_unchecked(&self, storage: &mut dyn Storage, pos: u32, value: &T) -> StdResult<()> {
@@ -225,11 +522,46 @@ fn calc_len(head: u32, tail: u32) -> u32 {
 
 impl<T: Serialize + DeserializeOwned> Deque<T> {
     pub fn iter<'a>(&'a self, storage: &'a dyn Storage) -> StdResult<DequeIter<'a, T>> {
+        let Meta { head, tail, .. } = self.meta(storage)?;
         Ok(DequeIter {
             deque: self,
             storage,
-            start: self.head(storage)?,
-            end: self.tail(storage)?,
+            start: head,
+            end: tail,
+        })
+    }
+
+    /// Iterates over the elements with logical indices from `start` (inclusive) to `end`
+    /// (exclusive), in the given order. Both are clamped to the current length,
+    /// and `None` means the front or back of the deque respectively.
+    ///
+    /// With `Order::Descending`, the element at `end - 1` comes first, which is handy
+    /// for "most recent first" reads of the last few elements.
+    #[cfg(feature = "iterator")]
+    pub fn range<'a>(
+        &'a self,
+        storage: &'a dyn Storage,
+        start: Option<u32>,
+        end: Option<u32>,
+        order: Order,
+    ) -> StdResult<Box<dyn Iterator<Item = StdResult<T>> + 'a>>
+    where
+        T: 'a,
+    {
+        let Meta { head, tail, .. } = self.meta(storage)?;
+        let len = calc_len(head, tail);
+        let end = end.map_or(len, |end| end.min(len));
+        let start = start.map_or(0, |start| start.min(end));
+
+        let iter = DequeIter {
+            deque: self,
+            storage,
+            start: head.wrapping_add(start),
+            end: head.wrapping_add(end),
+        };
+        Ok(match order {
+            Order::Ascending => Box::new(iter),
+            Order::Descending => Box::new(iter.rev()),
         })
     }
 }
@@ -320,7 +652,7 @@ mod tests {
     use crate::deque::Deque;
 
     use cosmwasm_std::testing::MockStorage;
-    use cosmwasm_std::{StdError, StdResult};
+    use cosmwasm_std::{StdError, StdResult, Storage};
     use serde::{Deserialize, Serialize};
 
     #[test]
@@ -435,6 +767,52 @@ mod tests {
         assert!(deque.is_empty(&store).unwrap());
     }
 
+    #[test]
+    fn head_and_tail_index() {
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        assert_eq!(deque.head_index(&store).unwrap(), 0);
+        assert_eq!(deque.tail_index(&store).unwrap(), 0);
+
+        deque.push_back(&mut store, &1u32).unwrap();
+        deque.push_front(&mut store, &0).unwrap();
+        deque.push_front(&mut store, &9).unwrap();
+
+        let head = deque.head_index(&store).unwrap();
+        let tail = deque.tail_index(&store).unwrap();
+        assert_eq!(head, u32::MAX - 1);
+        assert_eq!(tail, 1);
+        assert_eq!(tail.wrapping_sub(head), deque.len(&store).unwrap());
+    }
+
+    #[test]
+    fn has() {
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        assert!(!deque.has(&store, 0).unwrap());
+
+        deque.push_back(&mut store, &1u32).unwrap();
+        deque.push_front(&mut store, &0).unwrap();
+        assert!(deque.has(&store, 0).unwrap());
+        assert!(deque.has(&store, 1).unwrap());
+        assert!(!deque.has(&store, 2).unwrap());
+        assert!(!deque.has(&store, u32::MAX).unwrap());
+
+        // the value is not read
+        let head = deque.head_index(&store).unwrap();
+        store.set(
+            &cosmwasm_std::storage_keys::namespace_with_key(&[b"test"], &head.to_be_bytes()),
+            b"not json",
+        );
+        assert!(deque.has(&store, 0).unwrap());
+        deque.get(&store, 0).unwrap_err();
+
+        deque.pop_back(&mut store).unwrap();
+        assert!(!deque.has(&store, 1).unwrap());
+    }
+
     #[test]
     fn iterator() {
         let deque: Deque<u32> = Deque::new("test");
@@ -559,6 +937,15 @@ mod tests {
         deque.push_front(&mut store, &3).unwrap();
         assert_eq!(deque.back(&store).unwrap(), Some(2));
         assert_eq!(deque.front(&store).unwrap(), Some(3));
+
+        // peeking after pops
+        deque.pop_front(&mut store).unwrap();
+        deque.pop_back(&mut store).unwrap();
+        assert_eq!(deque.front(&store).unwrap(), Some(1));
+        assert_eq!(deque.back(&store).unwrap(), Some(1));
+        deque.pop_front(&mut store).unwrap();
+        assert_eq!(deque.front(&store).unwrap(), None);
+        assert_eq!(deque.back(&store).unwrap(), None);
     }
 
     #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
@@ -720,4 +1107,405 @@ mod tests {
             "setting value at an out of bounds index should error"
         );
     }
+
+    #[test]
+    fn get_and_set_after_pop_front() {
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        for i in 0..4u32 {
+            deque.push_back(&mut store, &i).unwrap();
+        }
+        // advance the head
+        assert_eq!(deque.pop_front(&mut store).unwrap(), Some(0));
+        assert_eq!(deque.pop_front(&mut store).unwrap(), Some(1));
+
+        assert_eq!(deque.get(&store, 0).unwrap(), Some(2));
+        assert_eq!(deque.get(&store, 1).unwrap(), Some(3));
+        assert_eq!(deque.get(&store, 2).unwrap(), None);
+        assert_eq!(deque.get(&store, u32::MAX).unwrap(), None);
+
+        deque.set(&mut store, 0, &20).unwrap();
+        deque.set(&mut store, 1, &30).unwrap();
+        assert_eq!(deque.front(&store).unwrap(), Some(20));
+        assert_eq!(deque.back(&store).unwrap(), Some(30));
+        assert!(matches!(
+            deque.set(&mut store, 2, &40),
+            Err(StdError::NotFound { .. })
+        ));
+
+        // empty again, with the head past its initial position
+        deque.pop_front(&mut store).unwrap();
+        deque.pop_front(&mut store).unwrap();
+        assert_eq!(deque.get(&store, 0).unwrap(), None);
+        assert!(matches!(
+            deque.set(&mut store, 0, &0),
+            Err(StdError::NotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn clear() {
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        assert_eq!(deque.clear(&mut store, None).unwrap(), 0);
+        assert!(deque.is_empty(&store).unwrap());
+
+        for i in 0..5u32 {
+            deque.push_front(&mut store, &i).unwrap();
+        }
+        deque.pop_back(&mut store).unwrap();
+        assert_ne!(deque.head_index(&store).unwrap(), 0);
+
+        assert_eq!(deque.clear(&mut store, None).unwrap(), 4);
+        assert_eq!(deque.len(&store).unwrap(), 0);
+        assert!(deque.is_empty(&store).unwrap());
+        assert_eq!(deque.front(&store).unwrap(), None);
+        // nothing is left in storage
+        #[cfg(feature = "iterator")]
+        assert_eq!(
+            store
+                .range(None, None, cosmwasm_std::Order::Ascending)
+                .count(),
+            0
+        );
+
+        // starts from a clean index space
+        deque.push_back(&mut store, &7).unwrap();
+        assert_eq!(deque.head_index(&store).unwrap(), 0);
+        assert_eq!(deque.tail_index(&store).unwrap(), 1);
+        assert_eq!(deque.get(&store, 0).unwrap(), Some(7));
+    }
+
+    #[test]
+    fn clear_in_chunks() {
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        for i in 0..5u32 {
+            deque.push_back(&mut store, &i).unwrap();
+        }
+        deque.pop_front(&mut store).unwrap();
+
+        // removes from the front
+        assert_eq!(deque.clear(&mut store, Some(2)).unwrap(), 2);
+        assert_eq!(deque.len(&store).unwrap(), 2);
+        assert_eq!(deque.front(&store).unwrap(), Some(3));
+        assert_eq!(deque.clear(&mut store, Some(2)).unwrap(), 2);
+        assert_eq!(deque.clear(&mut store, Some(2)).unwrap(), 0);
+        assert!(deque.is_empty(&store).unwrap());
+        #[cfg(feature = "iterator")]
+        assert_eq!(
+            store
+                .range(None, None, cosmwasm_std::Order::Ascending)
+                .count(),
+            0
+        );
+
+        // starts from a clean index space
+        deque.push_back(&mut store, &7).unwrap();
+        assert_eq!(deque.head_index(&store).unwrap(), 0);
+        assert_eq!(deque.tail_index(&store).unwrap(), 1);
+        assert_eq!(deque.get(&store, 0).unwrap(), Some(7));
+    }
+
+    #[test]
+    fn migrates_legacy_metadata() {
+        use cosmwasm_std::storage_keys::namespace_with_key;
+
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        // older versions stored head and tail under separate keys
+        for (pos, value) in [(1u32, 1u32), (2, 2)] {
+            let key = namespace_with_key(&[b"test"], &pos.to_be_bytes());
+            store.set(&key, &cosmwasm_std::to_json_vec(&value).unwrap());
+        }
+        let head_key = namespace_with_key(&[b"test"], b"h");
+        let tail_key = namespace_with_key(&[b"test"], b"t");
+        store.set(&head_key, &1u32.to_be_bytes());
+        store.set(&tail_key, &3u32.to_be_bytes());
+
+        // reads fall back to them
+        assert_eq!(deque.len(&store).unwrap(), 2);
+        assert_eq!(deque.front(&store).unwrap(), Some(1));
+
+        // and the next write replaces them
+        deque.push_back(&mut store, &3).unwrap();
+        assert_eq!(store.get(&head_key), None);
+        assert_eq!(store.get(&tail_key), None);
+        assert_eq!(deque.len(&store).unwrap(), 3);
+        assert_eq!(deque.head_index(&store).unwrap(), 1);
+        assert_eq!(deque.pop_front(&mut store).unwrap(), Some(1));
+        assert_eq!(deque.pop_back(&mut store).unwrap(), Some(3));
+        assert_eq!(deque.pop_back(&mut store).unwrap(), Some(2));
+        assert!(deque.is_empty(&store).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn len_is_a_single_read() {
+        use crate::MeteredStorage;
+
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+        for i in 0..3u32 {
+            deque.push_back(&mut store, &i).unwrap();
+        }
+
+        let metered = MeteredStorage::new(&mut store);
+        assert_eq!(deque.len(&metered).unwrap(), 3);
+        assert_eq!(metered.metrics().reads, 1);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range() {
+        use cosmwasm_std::Order;
+
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        for i in 0..8u32 {
+            deque.push_back(&mut store, &i).unwrap();
+        }
+        // advance the head, so logical and storage positions differ
+        deque.pop_front(&mut store).unwrap();
+        deque.pop_front(&mut store).unwrap();
+        deque.push_back(&mut store, &8).unwrap();
+        deque.push_front(&mut store, &1).unwrap();
+        // now: 1, 2, 3, 4, 5, 6, 7, 8
+
+        let range = |start, end, order| {
+            deque
+                .range(&store, start, end, order)
+                .unwrap()
+                .collect::<StdResult<Vec<u32>>>()
+                .unwrap()
+        };
+
+        assert_eq!(
+            range(None, None, Order::Ascending),
+            (1..=8).collect::<Vec<_>>()
+        );
+        assert_eq!(range(Some(2), Some(5), Order::Ascending), vec![3, 4, 5]);
+        assert_eq!(range(Some(2), Some(5), Order::Descending), vec![5, 4, 3]);
+        // last 3, most recent first
+        assert_eq!(range(Some(5), None, Order::Descending), vec![8, 7, 6]);
+        assert_eq!(range(None, Some(2), Order::Descending), vec![2, 1]);
+
+        // clamped to the length
+        assert_eq!(range(Some(6), Some(100), Order::Ascending), vec![7, 8]);
+        assert_eq!(range(Some(100), None, Order::Ascending), Vec::<u32>::new());
+        assert_eq!(
+            range(Some(5), Some(3), Order::Descending),
+            Vec::<u32>::new()
+        );
+    }
+
+    #[test]
+    fn insert() {
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        // make the positions wrap around
+        deque.push_front(&mut store, &2u32).unwrap();
+        deque.push_front(&mut store, &0).unwrap();
+        deque.push_back(&mut store, &4).unwrap();
+
+        deque.insert(&mut store, 1, &1).unwrap();
+        deque.insert(&mut store, 3, &3).unwrap();
+        // at the end, like push_back
+        deque.insert(&mut store, 5, &5).unwrap();
+        // at the front, like push_front
+        deque.insert(&mut store, 0, &9).unwrap();
+
+        assert_eq!(deque.len(&store).unwrap(), 7);
+        assert_eq!(
+            deque.iter(&store).unwrap().collect::<StdResult<Vec<_>>>(),
+            Ok(vec![9, 0, 1, 2, 3, 4, 5])
+        );
+        assert_eq!(deque.back(&store).unwrap(), Some(5));
+        assert_eq!(deque.pop_front(&mut store).unwrap(), Some(9));
+
+        assert!(
+            matches!(
+                deque.insert(&mut store, 7, &3),
+                Err(StdError::NotFound { .. })
+            ),
+            "inserting after the end should error"
+        );
+        assert_eq!(deque.len(&store).unwrap(), 6);
+
+        // inserting into an empty deque
+        let empty: Deque<u32> = Deque::new("empty");
+        empty.insert(&mut store, 0, &1).unwrap();
+        assert_eq!(empty.front(&store).unwrap(), Some(1));
+        assert_eq!(empty.len(&store).unwrap(), 1);
+    }
+
+    #[test]
+    fn insert_shifts_shorter_side() {
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+        for i in [0u32, 1, 2, 4, 5, 6] {
+            deque.push_back(&mut store, &i).unwrap();
+        }
+
+        // near the back, so the head stays in place
+        deque.insert(&mut store, 5, &9).unwrap();
+        assert_eq!(deque.head_index(&store).unwrap(), 0);
+        // near the front, so the tail stays in place
+        deque.insert(&mut store, 1, &8).unwrap();
+        assert_eq!(deque.head_index(&store).unwrap(), u32::MAX);
+        assert_eq!(deque.tail_index(&store).unwrap(), 7);
+
+        assert_eq!(
+            deque.iter(&store).unwrap().collect::<StdResult<Vec<_>>>(),
+            Ok(vec![0, 8, 1, 2, 4, 5, 9, 6])
+        );
+    }
+
+    #[test]
+    fn swap() {
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        // make the positions wrap around
+        for i in (0..3u32).rev() {
+            deque.push_front(&mut store, &i).unwrap();
+        }
+        for i in 3..6u32 {
+            deque.push_back(&mut store, &i).unwrap();
+        }
+
+        // front and back
+        deque.swap(&mut store, 0, 5).unwrap();
+        assert_eq!(
+            deque.iter(&store).unwrap().collect::<StdResult<Vec<_>>>(),
+            Ok(vec![5, 1, 2, 3, 4, 0])
+        );
+
+        // two middle elements, in either order
+        deque.swap(&mut store, 3, 1).unwrap();
+        assert_eq!(
+            deque.iter(&store).unwrap().collect::<StdResult<Vec<_>>>(),
+            Ok(vec![5, 3, 2, 1, 4, 0])
+        );
+        deque.swap(&mut store, 2, 2).unwrap();
+        assert_eq!(deque.get(&store, 2).unwrap(), Some(2));
+
+        // out of bounds
+        assert!(matches!(
+            deque.swap(&mut store, 0, 6),
+            Err(StdError::NotFound { .. })
+        ));
+        assert!(matches!(
+            deque.swap(&mut store, 6, 6),
+            Err(StdError::NotFound { .. })
+        ));
+        assert_eq!(deque.len(&store).unwrap(), 6);
+        assert_eq!(deque.front(&store).unwrap(), Some(5));
+        assert_eq!(deque.back(&store).unwrap(), Some(0));
+    }
+
+    #[test]
+    fn remove() {
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        // make the positions wrap around
+        for i in (0..4u32).rev() {
+            deque.push_front(&mut store, &i).unwrap();
+        }
+        for i in 4..8u32 {
+            deque.push_back(&mut store, &i).unwrap();
+        }
+
+        // front, back and middle
+        assert_eq!(deque.remove(&mut store, 0).unwrap(), Some(0));
+        assert_eq!(deque.remove(&mut store, 6).unwrap(), Some(7));
+        assert_eq!(deque.remove(&mut store, 3).unwrap(), Some(4));
+        // next to the front and the back
+        assert_eq!(deque.remove(&mut store, 1).unwrap(), Some(2));
+        assert_eq!(deque.remove(&mut store, 2).unwrap(), Some(5));
+
+        assert_eq!(deque.len(&store).unwrap(), 3);
+        assert_eq!(
+            deque.iter(&store).unwrap().collect::<StdResult<Vec<_>>>(),
+            Ok(vec![1, 3, 6])
+        );
+        assert_eq!(
+            deque
+                .iter(&store)
+                .unwrap()
+                .rev()
+                .collect::<StdResult<Vec<_>>>(),
+            Ok(vec![6, 3, 1])
+        );
+
+        // out of bounds
+        assert_eq!(deque.remove(&mut store, 3).unwrap(), None);
+        assert_eq!(deque.len(&store).unwrap(), 3);
+
+        // no stale elements are left behind, only the metadata
+        #[cfg(feature = "iterator")]
+        assert_eq!(
+            store
+                .range(None, None, cosmwasm_std::Order::Ascending)
+                .count(),
+            3 + 1
+        );
+
+        // removing the last element
+        deque.remove(&mut store, 0).unwrap();
+        deque.remove(&mut store, 0).unwrap();
+        assert_eq!(deque.remove(&mut store, 0).unwrap(), Some(6));
+        assert!(deque.is_empty(&store).unwrap());
+        assert_eq!(deque.pop_front(&mut store).unwrap(), None);
+    }
+
+    #[test]
+    fn map_in_place() {
+        let mut store = MockStorage::new();
+        let deque = Deque::new("test");
+
+        // make the positions wrap around
+        deque.push_front(&mut store, &2u32).unwrap();
+        deque.push_front(&mut store, &1).unwrap();
+        deque.push_back(&mut store, &3).unwrap();
+
+        deque.map_in_place(&mut store, |v| Ok(v * 10)).unwrap();
+
+        assert_eq!(deque.len(&store).unwrap(), 3);
+        assert_eq!(
+            deque.iter(&store).unwrap().collect::<StdResult<Vec<_>>>(),
+            Ok(vec![10, 20, 30])
+        );
+
+        // elements are visited in order, and the first error stops the iteration
+        let mut seen = vec![];
+        let err = deque
+            .map_in_place(&mut store, |v| {
+                seen.push(v);
+                if v == 20 {
+                    return Err(StdError::generic_err("stop"));
+                }
+                Ok(v + 1)
+            })
+            .unwrap_err();
+        assert_eq!(err, StdError::generic_err("stop"));
+        assert_eq!(seen, vec![10, 20]);
+        assert_eq!(
+            deque.iter(&store).unwrap().collect::<StdResult<Vec<_>>>(),
+            Ok(vec![11, 20, 30])
+        );
+
+        // nothing to do on an empty deque
+        let empty: Deque<u32> = Deque::new("empty");
+        empty
+            .map_in_place(&mut store, |_| panic!("should not be called"))
+            .unwrap();
+    }
 }
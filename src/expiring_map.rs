@@ -0,0 +1,219 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use cosmwasm_std::{StdResult, Storage};
+
+use crate::helpers::not_found_error;
+use crate::keys::PrimaryKey;
+use crate::map::Map;
+use crate::namespace::Namespace;
+
+/// A value stored by [`ExpiringMap`], together with the moment it expires.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Expiring<T> {
+    /// Block height or timestamp (as chosen by the caller) from which the value is expired
+    pub expires: u64,
+    pub value: T,
+}
+
+impl<T> Expiring<T> {
+    /// Returns `true` if the value is expired at the given height or timestamp.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires <= now
+    }
+}
+
+/// `ExpiringMap` works like a `Map`, but every entry carries an expiration height or timestamp,
+/// from which on it is treated as absent. Reads never delete expired entries, so they stay
+/// in storage until overwritten, removed or pruned with [`ExpiringMap::prune_expired`].
+///
+/// Whether `expires` and `now` are block heights or timestamps is up to the caller,
+/// as long as they are used consistently.
+pub struct ExpiringMap<K, T> {
+    primary: Map<K, Expiring<T>>,
+}
+
+impl<K, T> ExpiringMap<K, T> {
+    /// Creates a new [`ExpiringMap`] with the given storage key. This is a const fn only suitable
+    /// when you have the storage key in the form of a static string slice.
+    pub const fn new(namespace: &'static str) -> Self {
+        ExpiringMap {
+            primary: Map::new(namespace),
+        }
+    }
+
+    /// Creates a new [`ExpiringMap`] with the given storage key. Use this if you might need to handle
+    /// a dynamic string. Otherwise, you might prefer [`ExpiringMap::new`].
+    pub fn new_dyn(namespace: impl Into<Namespace>) -> Self {
+        ExpiringMap {
+            primary: Map::new_dyn(namespace),
+        }
+    }
+
+    /// Returns the underlying `Map`, which also contains the expired entries.
+    pub fn map(&self) -> &Map<K, Expiring<T>> {
+        &self.primary
+    }
+}
+
+impl<'a, K, T> ExpiringMap<K, T>
+where
+    T: Serialize + DeserializeOwned,
+    K: PrimaryKey<'a>,
+{
+    /// save will serialize the model and store it until `expires`,
+    /// returns an error on serialization issues
+    pub fn save(&self, store: &mut dyn Storage, k: K, data: T, expires: u64) -> StdResult<()> {
+        self.primary.save(
+            store,
+            k,
+            &Expiring {
+                expires,
+                value: data,
+            },
+        )
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage, k: K) {
+        self.primary.remove(store, k)
+    }
+
+    /// load will return an error if no data is set at the given key, if it is expired at `now`,
+    /// or on parse error
+    pub fn load(&self, store: &dyn Storage, k: K, now: u64) -> StdResult<T> {
+        let key = k.clone();
+        self.may_load(store, k, now)?
+            .ok_or_else(|| not_found_error::<T>(&self.primary.key(key)))
+    }
+
+    /// may_load will parse the data stored at the key if present and not expired at `now`,
+    /// returns Ok(None) otherwise. Returns an error on issues parsing
+    pub fn may_load(&self, store: &dyn Storage, k: K, now: u64) -> StdResult<Option<T>> {
+        Ok(self
+            .primary
+            .may_load(store, k)?
+            .filter(|entry| !entry.is_expired(now))
+            .map(|entry| entry.value))
+    }
+
+    /// Returns true if there is a value at this key that is not expired at `now`.
+    /// Unlike `Map::has`, this needs to parse the stored data.
+    pub fn has(&self, store: &dyn Storage, k: K, now: u64) -> StdResult<bool> {
+        Ok(self.may_load(store, k, now)?.is_some())
+    }
+}
+
+#[cfg(feature = "iterator")]
+impl<'a, K, T> ExpiringMap<K, T>
+where
+    T: Serialize + DeserializeOwned,
+    K: PrimaryKey<'a>,
+{
+    /// Removes up to `limit` entries (or all if `limit == None`) that are expired at `now`,
+    /// and returns how many were removed.
+    ///
+    /// Entries are not indexed by expiration, so this scans the map in key order
+    /// until enough expired entries are found.
+    pub fn prune_expired(
+        &self,
+        store: &mut dyn Storage,
+        now: u64,
+        limit: Option<usize>,
+    ) -> StdResult<usize> {
+        use cosmwasm_std::storage_keys::namespace_with_key;
+        use cosmwasm_std::Order;
+
+        let expired = self
+            .primary
+            .range_raw(store, None, None, Order::Ascending)
+            .filter_map(|item| match item {
+                Ok((key, entry)) if entry.is_expired(now) => Some(Ok(key)),
+                Ok(_) => None,
+                Err(e) => Some(Err(e)),
+            })
+            .take(limit.unwrap_or(usize::MAX))
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let namespace = self.primary.namespace_bytes();
+        for key in &expired {
+            store.remove(&namespace_with_key(&[namespace], key));
+        }
+        Ok(expired.len())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::StdError;
+
+    const SESSIONS: ExpiringMap<&str, String> = ExpiringMap::new("sessions");
+
+    #[test]
+    fn expired_entries_are_absent() {
+        let mut store = MockStorage::new();
+
+        SESSIONS
+            .save(&mut store, "alice", "token".to_string(), 10)
+            .unwrap();
+
+        assert_eq!(SESSIONS.load(&store, "alice", 9).unwrap(), "token");
+        assert!(SESSIONS.has(&store, "alice", 9).unwrap());
+
+        // expired from the given height on
+        assert_eq!(SESSIONS.may_load(&store, "alice", 10).unwrap(), None);
+        assert!(!SESSIONS.has(&store, "alice", 11).unwrap());
+        let err = SESSIONS.load(&store, "alice", 10).unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }));
+        // reports the key like `Map::load`
+        assert_eq!(
+            err.to_string(),
+            not_found_error::<String>(&SESSIONS.map().key("alice")).to_string()
+        );
+
+        // reading does not delete
+        assert_eq!(
+            SESSIONS.map().load(&store, "alice").unwrap(),
+            Expiring {
+                expires: 10,
+                value: "token".to_string()
+            }
+        );
+
+        // saving again extends the entry
+        SESSIONS
+            .save(&mut store, "alice", "token2".to_string(), 20)
+            .unwrap();
+        assert_eq!(SESSIONS.load(&store, "alice", 10).unwrap(), "token2");
+
+        SESSIONS.remove(&mut store, "alice");
+        assert_eq!(SESSIONS.may_load(&store, "alice", 0).unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn prune_expired_works() {
+        let mut store = MockStorage::new();
+
+        for (user, expires) in [("a", 5), ("b", 15), ("c", 3), ("d", 8), ("e", 30)] {
+            SESSIONS
+                .save(&mut store, user, user.to_string(), expires)
+                .unwrap();
+        }
+
+        // a and c are expired at 5, but only one is removed
+        assert_eq!(SESSIONS.prune_expired(&mut store, 5, Some(1)).unwrap(), 1);
+        assert!(!SESSIONS.map().has(&store, "a"));
+        assert!(SESSIONS.map().has(&store, "c"));
+
+        assert_eq!(SESSIONS.prune_expired(&mut store, 10, None).unwrap(), 2);
+        assert!(!SESSIONS.map().has(&store, "c"));
+        assert!(!SESSIONS.map().has(&store, "d"));
+        assert!(SESSIONS.map().has(&store, "b"));
+        assert!(SESSIONS.map().has(&store, "e"));
+
+        assert_eq!(SESSIONS.prune_expired(&mut store, 10, None).unwrap(), 0);
+        assert_eq!(SESSIONS.load(&store, "b", 10).unwrap(), "b");
+    }
+}
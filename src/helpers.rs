@@ -4,6 +4,8 @@
 //! Everything in this file is only responsible for building such keys
 //! and is in no way specific to any kind of storage.
 
+#[cfg(feature = "load-into")]
+use serde::de::DeserializeOwned;
 use std::any::type_name;
 
 use cosmwasm_std::{
@@ -48,6 +50,25 @@ pub(crate) fn not_found_object_info<T>(key: &[u8]) -> String {
     format!("type: {type_name}; key: {:02X?}", key)
 }
 
+/// Builds the error returned when loading a missing key. This is kept out of line,
+/// so the formatting in `not_found_object_info` only ever runs on the error path.
+#[cold]
+pub(crate) fn not_found_error<T>(key: &[u8]) -> StdError {
+    StdError::not_found(not_found_object_info::<T>(key))
+}
+
+/// Deserializes a JSON value into an existing `T`, letting types implementing in-place
+/// deserialization (e.g. `Vec` or `String`) reuse their allocations. This uses `serde_json`
+/// instead of the parser behind `from_json`, which may accept inputs `from_json` rejects.
+/// The content of `out` is unspecified if an error is returned.
+#[cfg(feature = "load-into")]
+pub(crate) fn from_json_in_place<T: DeserializeOwned>(value: &[u8], out: &mut T) -> StdResult<()> {
+    let mut deserializer = serde_json::Deserializer::from_slice(value);
+    T::deserialize_in_place(&mut deserializer, out)
+        .and_then(|_| deserializer.end())
+        .map_err(|e| StdError::parse_err(type_name::<T>(), e))
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -0,0 +1,165 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use cosmwasm_std::{StdResult, Storage};
+
+use crate::keys::PrimaryKey;
+use crate::map::Map;
+use crate::namespace::Namespace;
+use crate::path::Path;
+
+/// `HasPrimaryKey` is implemented by values that carry their own canonical key,
+/// e.g. a struct with an `id` field. The key may borrow from the value.
+pub trait HasPrimaryKey<'a, K: PrimaryKey<'a>> {
+    /// returns the key this value must be stored under
+    fn primary_key(&'a self) -> K;
+}
+
+/// `IdentifiedMap` works like a `Map`, but the key is always derived from the value
+/// through [`HasPrimaryKey`], so a value can never be stored under a different key than its own.
+pub struct IdentifiedMap<K, T> {
+    primary: Map<K, T>,
+}
+
+impl<K, T> IdentifiedMap<K, T> {
+    /// Creates a new [`IdentifiedMap`] with the given storage key. This is a const fn only suitable
+    /// when you have the storage key in the form of a static string slice.
+    pub const fn new(namespace: &'static str) -> Self {
+        IdentifiedMap {
+            primary: Map::new(namespace),
+        }
+    }
+
+    /// Creates a new [`IdentifiedMap`] with the given storage key. Use this if you might need to handle
+    /// a dynamic string. Otherwise, you might prefer [`IdentifiedMap::new`].
+    pub fn new_dyn(namespace: impl Into<Namespace>) -> Self {
+        IdentifiedMap {
+            primary: Map::new_dyn(namespace),
+        }
+    }
+
+    /// Returns the underlying `Map`, to be used for read access like ranges or prefixes.
+    pub fn map(&self) -> &Map<K, T> {
+        &self.primary
+    }
+}
+
+impl<'a, K, T> IdentifiedMap<K, T>
+where
+    T: Serialize + DeserializeOwned + HasPrimaryKey<'a, K>,
+    K: PrimaryKey<'a>,
+{
+    pub fn key(&self, k: K) -> Path<T> {
+        self.primary.key(k)
+    }
+
+    /// save will serialize the model and store it under its own primary key,
+    /// returns an error on serialization issues
+    pub fn save(&self, store: &mut dyn Storage, data: &'a T) -> StdResult<()> {
+        self.primary.save(store, data.primary_key(), data)
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage, k: K) {
+        self.primary.remove(store, k)
+    }
+
+    /// load will return an error if no data is set at the given key, or on parse error
+    pub fn load(&self, store: &dyn Storage, k: K) -> StdResult<T> {
+        self.primary.load(store, k)
+    }
+
+    /// may_load will parse the data stored at the key if present, returns Ok(None) if no data there.
+    /// returns an error on issues parsing
+    pub fn may_load(&self, store: &dyn Storage, k: K) -> StdResult<Option<T>> {
+        self.primary.may_load(store, k)
+    }
+
+    /// has returns true or false if any data is at this key, without parsing or interpreting the
+    /// contents.
+    pub fn has(&self, store: &dyn Storage, k: K) -> bool {
+        self.primary.has(store, k)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use cosmwasm_std::testing::MockStorage;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Token {
+        pub id: String,
+        pub supply: u64,
+    }
+
+    impl<'a> HasPrimaryKey<'a, &'a str> for Token {
+        fn primary_key(&'a self) -> &'a str {
+            &self.id
+        }
+    }
+
+    #[cfg(feature = "iterator")]
+    #[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+    struct Proposal {
+        pub id: u64,
+        pub title: String,
+    }
+
+    #[cfg(feature = "iterator")]
+    impl<'a> HasPrimaryKey<'a, u64> for Proposal {
+        fn primary_key(&self) -> u64 {
+            self.id
+        }
+    }
+
+    const TOKENS: IdentifiedMap<&str, Token> = IdentifiedMap::new("tokens");
+    #[cfg(feature = "iterator")]
+    const PROPOSALS: IdentifiedMap<u64, Proposal> = IdentifiedMap::new("proposals");
+
+    #[test]
+    fn save_uses_own_key() {
+        let mut store = MockStorage::new();
+
+        let token = Token {
+            id: "atom".to_string(),
+            supply: 100,
+        };
+        TOKENS.save(&mut store, &token).unwrap();
+
+        assert!(TOKENS.has(&store, "atom"));
+        assert_eq!(TOKENS.load(&store, "atom").unwrap(), token);
+        assert_eq!(TOKENS.may_load(&store, "osmo").unwrap(), None);
+
+        // same layout as a plain map
+        let map: Map<&str, Token> = Map::new("tokens");
+        assert_eq!(map.load(&store, "atom").unwrap(), token);
+
+        TOKENS.remove(&mut store, "atom");
+        assert!(!TOKENS.has(&store, "atom"));
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn owned_keys_work() {
+        use cosmwasm_std::Order;
+
+        let mut store = MockStorage::new();
+
+        for id in [3u64, 1, 2] {
+            let proposal = Proposal {
+                id,
+                title: format!("proposal {}", id),
+            };
+            PROPOSALS.save(&mut store, &proposal).unwrap();
+        }
+
+        let ids = PROPOSALS
+            .map()
+            .keys(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(PROPOSALS.load(&store, 2).unwrap().title, "proposal 2");
+    }
+}
@@ -8,7 +8,7 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use crate::de::KeyDeserialize;
-use crate::indexes::Index;
+use crate::indexes::{debug_assert_distinct_namespaces, Index, IndexError, MultiIndex};
 use crate::iter_helpers::{deserialize_kv, deserialize_v};
 use crate::keys::{Prefixer, PrimaryKey};
 use crate::map::Map;
@@ -19,6 +19,10 @@ pub trait IndexList<T> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<T>> + '_>;
 }
 
+/// A primary key and value, together with the raw key of every index entry pointing to it
+/// (`None` for indexes that do not expose their keys)
+type IndexedRecord<K, T> = (K, T, Vec<Option<Vec<u8>>>);
+
 /// `IndexedMap` works like a `Map` but has a secondary index
 pub struct IndexedMap<K, T, I> {
     pk_namespace: Namespace,
@@ -36,6 +40,9 @@ where
 {
     /// Creates a new [`IndexedMap`] with the given storage key. This is a constant function only suitable
     /// when you have a prefix in the form of a static string slice.
+    ///
+    /// In debug builds, the first write panics if an index shares its namespace with the primary
+    /// map or another index. [`IndexedMap::new_dyn`] already checks this on construction.
     pub const fn new(pk_namespace: &'static str, indexes: I) -> Self {
         IndexedMap {
             pk_namespace: Namespace::from_static_str(pk_namespace),
@@ -48,6 +55,7 @@ where
     /// a dynamic string. Otherwise, you should probably prefer [`IndexedMap::new`].
     pub fn new_dyn(pk_namespace: impl Into<Namespace>, indexes: I) -> Self {
         let pk_namespace = pk_namespace.into();
+        debug_assert_distinct_namespaces(pk_namespace.as_slice(), indexes.get_indexes());
 
         IndexedMap {
             pk_namespace: pk_namespace.clone(),
@@ -75,11 +83,52 @@ where
         self.replace(store, key, Some(data), old_data.as_ref())
     }
 
+    /// Removes the data at the key, along with its index entries, and returns it,
+    /// or `None` if there was none.
+    pub fn take(&self, store: &mut dyn Storage, key: K) -> StdResult<Option<T>> {
+        let old_data = self.may_load(store, key.clone())?;
+        if old_data.is_some() {
+            self.replace(store, key, None, old_data.as_ref())?;
+        }
+        Ok(old_data)
+    }
+
+    /// Works like `save`, but checks all indexes for uniqueness violations first, and returns
+    /// them as `IndexError::UniqueViolation` instead of a generic error. Nothing is written
+    /// in that case.
+    pub fn try_save(&self, store: &mut dyn Storage, key: K, data: &T) -> Result<(), IndexError> {
+        let pk = key.joined_key();
+        for index in self.idx.get_indexes() {
+            if index.conflicts(store, &pk, data)? {
+                return Err(IndexError::UniqueViolation {
+                    index: String::from_utf8_lossy(index.namespace().unwrap_or_default())
+                        .into_owned(),
+                });
+            }
+        }
+        Ok(self.save(store, key, data)?)
+    }
+
     pub fn remove(&self, store: &mut dyn Storage, key: K) -> StdResult<()> {
         let old_data = self.may_load(store, key.clone())?;
         self.replace(store, key, None, old_data.as_ref())
     }
 
+    /// Saves `data` like `save`, but takes the currently stored value as `old_data` instead of
+    /// loading it, which saves a storage read when the caller already has it.
+    ///
+    /// `old_data` must be exactly the value stored at `key` (or `None` if there is none),
+    /// as it is used to remove the old index entries. Passing anything else corrupts the indexes.
+    pub fn save_with_old(
+        &self,
+        store: &mut dyn Storage,
+        key: K,
+        data: &T,
+        old_data: Option<&T>,
+    ) -> StdResult<()> {
+        self.replace(store, key, Some(data), old_data)
+    }
+
     /// replace writes data to key. old_data must be the current stored value (from a previous load)
     /// and is used to properly update the index. This is used by save, replace, and update
     /// and can be called directly if you want to optimize
@@ -90,6 +139,7 @@ where
         data: Option<&T>,
         old_data: Option<&T>,
     ) -> StdResult<()> {
+        debug_assert_distinct_namespaces(self.pk_namespace.as_slice(), self.idx.get_indexes());
         // this is the key *relative* to the primary map namespace
         let pk = key.joined_key();
         if let Some(old) = old_data {
@@ -113,6 +163,22 @@ where
     ///
     /// If the data exists, `action(Some(value))` is called. Otherwise `action(None)` is called.
     pub fn update<A, E>(&self, store: &mut dyn Storage, key: K, action: A) -> Result<T, E>
+    where
+        A: FnOnce(Option<T>) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        self.update_returning_old(store, key, action)
+            .map(|(_, output)| output)
+    }
+
+    /// Works like `update`, but returns the value before the update along with the new one,
+    /// e.g. to emit both in an event.
+    pub fn update_returning_old<A, E>(
+        &self,
+        store: &mut dyn Storage,
+        key: K,
+        action: A,
+    ) -> Result<(Option<T>, T), E>
     where
         A: FnOnce(Option<T>) -> Result<T, E>,
         E: From<StdError>,
@@ -121,7 +187,7 @@ where
         let old_val = input.clone();
         let output = action(input)?;
         self.replace(store, key, Some(&output), old_val.as_ref())?;
-        Ok(output)
+        Ok((old_val, output))
     }
 
     // Everything else, that doesn't touch indexers, is just pass-through from self.core,
@@ -143,15 +209,54 @@ where
         self.primary.key(k).has(store)
     }
 
+    /// Removes up to `limit` elements (or all if `limit == None`) referenced by the given
+    /// `MultiIndex` value, together with their entries in all indexes, and returns how many
+    /// index entries were processed. Call it until it returns less than `limit` to remove
+    /// everything in batches.
+    ///
+    /// The scanned entries are always removed from `index` itself, so repeated calls make
+    /// progress even if an entry is stale (its element is missing, or its value now maps to a
+    /// different index value). Every remaining element is loaded, as its entries in the other
+    /// indexes can only be found from its value.
+    ///
+    /// `index` is expected to be one of the indexes of this map. If it is not, its scanned
+    /// entries are still removed, and so are the elements of this map under the primary keys
+    /// they point to.
+    pub fn remove_by_index_prefix<'b, IK, PK>(
+        &self,
+        store: &mut dyn Storage,
+        index: &MultiIndex<'b, IK, T, PK>,
+        idx: IK,
+        limit: Option<usize>,
+    ) -> StdResult<usize>
+    where
+        IK: PrimaryKey<'b> + Prefixer<'b>,
+        PK: PrimaryKey<'b> + KeyDeserialize,
+    {
+        let pks = index.remove_prefix_raw(store, idx, limit.unwrap_or(usize::MAX));
+
+        for pk in &pks {
+            let path = Path::<T>::new(self.pk_namespace.as_slice(), &[pk.as_slice()]);
+            if let Some(old) = path.may_load(store)? {
+                for index in self.idx.get_indexes() {
+                    index.remove(store, pk, &old)?;
+                }
+            }
+            store.remove(&path);
+        }
+        Ok(pks.len())
+    }
+
     // use no_prefix to scan -> range
     fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K> {
         Prefix::new(self.pk_namespace.as_slice(), &[])
     }
 
-    /// Clears the map, removing all elements.
-    pub fn clear(&self, store: &mut dyn Storage) {
+    /// Clears the map, removing all elements. Returns how many elements were removed.
+    pub fn clear(&self, store: &mut dyn Storage) -> usize {
         const TAKE: usize = 10;
         let mut cleared = false;
+        let mut removed = 0;
 
         while !cleared {
             let paths = self
@@ -163,9 +268,11 @@ where
                 .collect::<Vec<_>>();
 
             paths.iter().for_each(|path| store.remove(path));
+            removed += paths.len();
 
             cleared = paths.len() < TAKE;
         }
+        removed
     }
 
     /// Returns `true` if the map is empty.
@@ -304,6 +411,59 @@ where
         self.no_prefix().keys(store, min, max, order)
     }
 
+    /// Like `range`, but also returns the key each index stores the row under, in the order
+    /// given by `IndexList::get_indexes`, or `None` for indexes that do not expose their keys.
+    /// This is meant for debugging and auditing indexes against the primary data.
+    ///
+    /// Note that the index keys are recomputed from the data for every row, which is as
+    /// expensive as a `save` without the writes. Avoid it in regular contract execution.
+    pub fn range_with_index_keys<'c>(
+        &'c self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = StdResult<IndexedRecord<K::Output, T>>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        let mapped = self.range_raw(store, min, max, order).map(move |item| {
+            let (pk, data) = item?;
+            let index_keys = self
+                .idx
+                .get_indexes()
+                .map(|index| index.index_key_raw(&pk, &data))
+                .collect();
+            Ok((K::from_vec(pk)?, data, index_keys))
+        });
+        Box::new(mapped)
+    }
+
+    /// Checks that every index holds the entry it should have for every row of the primary map,
+    /// and returns an error describing the first missing one. This is meant for debugging
+    /// index corruption and only available with the `verify-indexes` feature.
+    ///
+    /// Note that this reads the whole map, plus one read per index for every entry,
+    /// so never use it in regular contract execution.
+    /// Stale index entries without a matching row are not detected.
+    #[cfg(feature = "verify-indexes")]
+    pub fn verify_indexes(&self, store: &dyn Storage) -> StdResult<()> {
+        for item in self.range_raw(store, None, None, cosmwasm_std::Order::Ascending) {
+            let (pk, data) = item?;
+            for index in self.idx.get_indexes() {
+                if !index.contains(store, &pk, &data)? {
+                    return Err(StdError::generic_err(format!(
+                        "Index \"{}\" is missing the entry for primary key {:?}",
+                        String::from_utf8_lossy(index.namespace().unwrap_or_default()),
+                        pk
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
     fn no_prefix(&self) -> Prefix<K, T, K> {
         Prefix::new(self.pk_namespace.as_slice(), &[])
     }
@@ -315,6 +475,7 @@ mod test {
 
     use crate::indexes::test::{index_string_tuple, index_tuple};
     use crate::{MultiIndex, UniqueIndex};
+    use cosmwasm_std::storage_keys::namespace_with_key;
     use cosmwasm_std::testing::MockStorage;
     use cosmwasm_std::{MemoryStorage, Order};
     use serde::{Deserialize, Serialize};
@@ -555,6 +716,132 @@ mod test {
         assert_eq!(None, aged);
     }
 
+    #[test]
+    fn load_by_unique_index() {
+        let mut store = MockStorage::new();
+        let (_, datas) = save_data(&mut store);
+
+        assert_eq!(DATA.idx.age.load(&store, 42).unwrap(), datas[0]);
+        assert_eq!(
+            DATA.idx.age.may_load(&store, 23).unwrap(),
+            Some(datas[1].clone())
+        );
+
+        assert_eq!(DATA.idx.age.may_load(&store, 43).unwrap(), None);
+        let err = DATA.idx.age.load(&store, 43).unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }));
+        // reports the key in the index
+        let key = Map::<u32, Data>::new("data__age").key(43);
+        assert_eq!(
+            err.to_string(),
+            crate::helpers::not_found_error::<Data>(&key).to_string()
+        );
+    }
+
+    #[test]
+    fn load_pk_by_unique_index() {
+        let mut store = MockStorage::new();
+        let (pks, datas) = save_data(&mut store);
+
+        let pk = DATA.idx.age.may_load_pk(&store, datas[1].age).unwrap();
+        assert_eq!(pk, Some(pks[1].to_string()));
+        assert_eq!(DATA.load(&store, &pk.unwrap()).unwrap(), datas[1]);
+
+        assert_eq!(DATA.idx.age.may_load_pk(&store, 43).unwrap(), None);
+
+        // composite index keys work the same way
+        let idx = index_string_tuple(&datas[0].name, &datas[0].last_name);
+        assert_eq!(
+            DATA.idx.name_lastname.may_load_pk(&store, idx).unwrap(),
+            Some(pks[0].to_string())
+        );
+    }
+
+    #[test]
+    fn count_by_multi_index_prefix() {
+        let mut store = MockStorage::new();
+        save_data(&mut store);
+
+        assert_eq!(
+            DATA.idx
+                .name
+                .prefix("Maria".to_string())
+                .count(&store, None, None),
+            2
+        );
+        assert_eq!(
+            DATA.idx
+                .name
+                .prefix("Maria Luisa".to_string())
+                .count(&store, None, None),
+            1
+        );
+        assert_eq!(
+            DATA.idx
+                .name
+                .prefix("Mari".to_string())
+                .count(&store, None, None),
+            0
+        );
+
+        // bounds are over the primary keys
+        assert_eq!(
+            DATA.idx.name.prefix("Maria".to_string()).count(
+                &store,
+                Some(Bound::exclusive("1")),
+                None
+            ),
+            1
+        );
+    }
+
+    #[test]
+    fn range_limited_by_multi_index_prefix() {
+        let mut store = MockStorage::new();
+        for pk in ["5", "1", "3", "2", "4"] {
+            let data = Data {
+                name: "Maria".to_string(),
+                last_name: pk.to_string(),
+                age: pk.parse().unwrap(),
+            };
+            DATA.save(&mut store, pk, &data).unwrap();
+        }
+        let other = Data {
+            name: "Maria Luisa".to_string(),
+            last_name: "".to_string(),
+            age: 100,
+        };
+        DATA.save(&mut store, "0", &other).unwrap();
+
+        for order in [Order::Ascending, Order::Descending] {
+            let prefix = DATA.idx.name.prefix("Maria".to_string());
+            let mut pks = vec![];
+            let mut next = None;
+            loop {
+                let (min, max) = match order {
+                    Order::Ascending => (next, None),
+                    Order::Descending => (None, next),
+                };
+                let (items, bound) = prefix.range_limited(&store, min, max, order, 2).unwrap();
+                assert!(items.len() <= 2);
+                for (pk, data) in items {
+                    assert_eq!(data.last_name, pk);
+                    pks.push(pk);
+                }
+                if bound.is_none() {
+                    break;
+                }
+                next = bound;
+            }
+
+            let mut expected = vec!["1", "2", "3", "4", "5"];
+            if order == Order::Descending {
+                expected.reverse();
+            }
+            assert_eq!(pks, expected);
+        }
+    }
+
     #[test]
     fn existence() {
         let mut store = MockStorage::new();
@@ -564,6 +851,69 @@ mod test {
         assert!(!DATA.has(&store, "6"));
     }
 
+    #[test]
+    fn range_with_index_keys_works() {
+        let mut store = MockStorage::new();
+        let (pks, datas) = save_data(&mut store);
+
+        let rows = DATA
+            .range_with_index_keys(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(rows.len(), pks.len());
+
+        let (pk, data, index_keys) = &rows[1];
+        assert_eq!(pk, pks[1]);
+        assert_eq!(data, &datas[1]);
+        assert_eq!(
+            index_keys,
+            &vec![
+                Some("Maria".to_string().joined_extra_key(b"2")),
+                Some(23u32.joined_key()),
+                Some(index_string_tuple("Maria", "Williams").joined_key()),
+            ]
+        );
+
+        // every computed key points to an existing index entry
+        let namespaces: [&[u8]; 3] = [b"data__name", b"data__age", b"data__name_lastname"];
+        for (_, _, index_keys) in rows {
+            for (ns, key) in namespaces.iter().zip(index_keys) {
+                assert!(store
+                    .get(&namespace_with_key(&[ns], &key.unwrap()))
+                    .is_some());
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "verify-indexes")]
+    fn verify_indexes_detects_corruption() {
+        let mut store = MockStorage::new();
+        save_data(&mut store);
+        DATA.verify_indexes(&store).unwrap();
+
+        // drop a multi index entry
+        let name_key = namespace_with_key(
+            &[b"data__name"],
+            &"Maria".to_string().joined_extra_key(b"2"),
+        );
+        let name_entry = store.get(&name_key).unwrap();
+        store.remove(&name_key);
+        let err = DATA.verify_indexes(&store).unwrap_err();
+        assert!(err.to_string().contains("data__name"), "{}", err);
+        store.set(&name_key, &name_entry);
+        DATA.verify_indexes(&store).unwrap();
+
+        // make a unique index entry point to another record
+        let age_key = |age: u32| namespace_with_key(&[b"data__age"], &age.joined_key());
+        let age_entry = store.get(&age_key(23)).unwrap();
+        store.set(&age_key(23), &store.get(&age_key(42)).unwrap());
+        let err = DATA.verify_indexes(&store).unwrap_err();
+        assert!(err.to_string().contains("data__age"), "{}", err);
+        store.set(&age_key(23), &age_entry);
+        DATA.verify_indexes(&store).unwrap();
+    }
+
     #[test]
     fn range_raw_simple_key_by_multi_index() {
         let mut store = MockStorage::new();
@@ -847,6 +1197,131 @@ mod test {
         assert_eq!(v.age, data5.age);
     }
 
+    #[test]
+    fn multi_index_keys_skip_values() {
+        #[derive(Serialize, Clone)]
+        struct Opaque {
+            owner: String,
+        }
+
+        impl<'de> Deserialize<'de> for Opaque {
+            fn deserialize<D: serde::Deserializer<'de>>(_: D) -> Result<Self, D::Error> {
+                panic!("values must not be deserialized")
+            }
+        }
+
+        struct OpaqueIndexes<'a> {
+            owner: MultiIndex<'a, String, Opaque, u64>,
+        }
+
+        impl<'a> IndexList<Opaque> for OpaqueIndexes<'a> {
+            fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Opaque>> + '_> {
+                let v: Vec<&dyn Index<Opaque>> = vec![&self.owner];
+                Box::new(v.into_iter())
+            }
+        }
+
+        let map = IndexedMap::new(
+            "opaque",
+            OpaqueIndexes {
+                owner: MultiIndex::new(|_pk, d| d.owner.clone(), "opaque", "opaque__owner"),
+            },
+        );
+
+        let mut store = MockStorage::new();
+        for (id, owner) in [(3u64, "alice"), (1, "bob"), (7, "alice"), (2, "alice")] {
+            let data = Opaque {
+                owner: owner.to_string(),
+            };
+            map.replace(&mut store, id, Some(&data), None).unwrap();
+        }
+
+        let pks = map
+            .idx
+            .owner
+            .prefix("alice".to_string())
+            .keys(&store, None, None, Order::Descending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(pks, vec![7, 3, 2]);
+
+        let all = map
+            .idx
+            .owner
+            .keys(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        // the shorter "bob" comes first
+        assert_eq!(all, vec![1, 2, 3, 7]);
+    }
+
+    #[test]
+    fn take_removes_index_entries() {
+        let mut store = MockStorage::new();
+        let (pks, datas) = save_data(&mut store);
+
+        assert_eq!(
+            DATA.take(&mut store, pks[0]).unwrap(),
+            Some(datas[0].clone())
+        );
+        assert!(!DATA.has(&store, pks[0]));
+        assert_eq!(DATA.idx.age.may_load(&store, datas[0].age).unwrap(), None);
+        assert_eq!(
+            DATA.idx
+                .name
+                .prefix(datas[0].name.clone())
+                .count(&store, None, None),
+            1
+        );
+
+        assert_eq!(DATA.take(&mut store, pks[0]).unwrap(), None);
+        // the age is free again
+        DATA.try_save(&mut store, "6", &datas[0]).unwrap();
+    }
+
+    #[test]
+    fn try_save_returns_unique_violation() {
+        use crate::IndexError;
+
+        let mut store = MockStorage::new();
+        let (pks, datas) = save_data(&mut store);
+
+        let dup = Data {
+            name: "Marcel".to_string(),
+            last_name: "Laurens".to_string(),
+            age: 42,
+        };
+        let err = DATA.try_save(&mut store, "6", &dup).unwrap_err();
+        assert_eq!(
+            err,
+            IndexError::UniqueViolation {
+                index: "data__age".to_string()
+            }
+        );
+        assert_eq!(
+            err.to_string(),
+            "Violates unique constraint on index data__age"
+        );
+        // nothing was written, not even to the other indexes
+        assert!(!DATA.has(&store, "6"));
+        assert_eq!(
+            DATA.idx
+                .name
+                .prefix("Marcel".to_string())
+                .count(&store, None, None),
+            0
+        );
+
+        // a record doesn't conflict with itself
+        let mut older = datas[0].clone();
+        older.name = "Mary".to_string();
+        DATA.try_save(&mut store, pks[0], &older).unwrap();
+        assert_eq!(DATA.load(&store, pks[0]).unwrap(), older);
+
+        DATA.try_save(&mut store, "6", &Data { age: 43, ..dup })
+            .unwrap();
+    }
+
     #[test]
     fn unique_index_enforced_composite_key() {
         let mut store = MockStorage::new();
@@ -865,6 +1340,114 @@ mod test {
         DATA.save(&mut store, pk5, &data5).unwrap_err();
     }
 
+    #[test]
+    fn normalized_indexes_work() {
+        struct NormalizedIndexes<'a> {
+            name: MultiIndex<'a, String, Data, String>,
+            last_name: UniqueIndex<'a, String, Data, String>,
+        }
+
+        impl<'a> IndexList<Data> for NormalizedIndexes<'a> {
+            fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Data>> + '_> {
+                let v: Vec<&dyn Index<Data>> = vec![&self.name, &self.last_name];
+                Box::new(v.into_iter())
+            }
+        }
+
+        let map: IndexedMap<&str, Data, NormalizedIndexes> = IndexedMap::new(
+            "data",
+            NormalizedIndexes {
+                name: MultiIndex::new_normalized(
+                    |_pk, d| d.name.clone(),
+                    |name| name.to_lowercase(),
+                    "data",
+                    "data__name",
+                ),
+                last_name: UniqueIndex::new_normalized(
+                    |d| d.last_name.clone(),
+                    |name| name.to_lowercase(),
+                    "data__last_name",
+                ),
+            },
+        );
+        let mut store = MockStorage::new();
+
+        let data1 = Data {
+            name: "Maria".to_string(),
+            last_name: "Doe".to_string(),
+            age: 42,
+        };
+        map.save(&mut store, "1", &data1).unwrap();
+
+        // same last name, different case => error
+        let data2 = Data {
+            name: "maria".to_string(),
+            last_name: "DOE".to_string(),
+            age: 24,
+        };
+        map.save(&mut store, "2", &data2).unwrap_err();
+
+        // lookups are normalized as well
+        let (pk, v) = map
+            .idx
+            .last_name
+            .item(&store, "dOe".to_string())
+            .unwrap()
+            .unwrap();
+        assert_eq!(pk, b"1");
+        assert_eq!(v, data1);
+        let found = map
+            .idx
+            .last_name
+            .range(
+                &store,
+                Some(Bound::inclusive("DOE")),
+                Some(Bound::inclusive("DOE")),
+                Order::Ascending,
+            )
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(found, vec![("1".to_string(), data1.clone())]);
+
+        let data2 = Data {
+            last_name: "Williams".to_string(),
+            ..data2
+        };
+        map.save(&mut store, "2", &data2).unwrap();
+
+        // both names end up under the same multi index key
+        let marias = map
+            .idx
+            .name
+            .prefix("MARIA".to_string())
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            marias,
+            vec![("1".to_string(), data1), ("2".to_string(), data2)]
+        );
+        let marias = map
+            .idx
+            .name
+            .prefix_range(
+                &store,
+                Some(PrefixBound::inclusive("MARIA")),
+                Some(PrefixBound::inclusive("Maria")),
+                Order::Ascending,
+            )
+            .count();
+        assert_eq!(marias, 2);
+
+        // removal uses the normalized key too
+        map.remove(&mut store, "1").unwrap();
+        assert_eq!(map.idx.name.count(&store, "maria".to_string()), 1);
+        assert_eq!(
+            map.idx.last_name.item(&store, "Doe".to_string()).unwrap(),
+            None
+        );
+    }
+
     #[test]
     fn remove_and_update_reflected_on_indexes() {
         let mut store = MockStorage::new();
@@ -905,6 +1488,39 @@ mod test {
         assert_eq!(name_count(&store, "Mary"), 1);
     }
 
+    #[test]
+    fn update_returning_old_works() {
+        let mut store = MockStorage::new();
+        let (pks, datas) = save_data(&mut store);
+
+        let (old, new) = DATA
+            .update_returning_old(&mut store, pks[2], |d| -> StdResult<_> {
+                let mut x = d.unwrap();
+                x.age += 1;
+                Ok(x)
+            })
+            .unwrap();
+        assert_eq!(old, Some(datas[2].clone()));
+        assert_eq!(new.age, datas[2].age + 1);
+        assert_eq!(DATA.load(&store, pks[2]).unwrap(), new);
+        // indexes are updated like with `update`
+        assert_eq!(DATA.idx.age.item(&store, datas[2].age).unwrap(), None);
+        assert!(DATA.idx.age.item(&store, new.age).unwrap().is_some());
+
+        let (old, new) = DATA
+            .update_returning_old(&mut store, "6", |d| -> StdResult<_> {
+                assert_eq!(d, None);
+                Ok(Data {
+                    name: "Mary".to_string(),
+                    last_name: "New".to_string(),
+                    age: 1,
+                })
+            })
+            .unwrap();
+        assert_eq!(old, None);
+        assert_eq!(DATA.load(&store, "6").unwrap(), new);
+    }
+
     #[test]
     fn range_raw_simple_key_by_unique_index() {
         let mut store = MockStorage::new();
@@ -1147,6 +1763,70 @@ mod test {
         );
     }
 
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn unit_sub_prefix_on_pair() {
+        let mut store = MockStorage::new();
+
+        let indexes = DataCompositeMultiIndex {
+            name_age: MultiIndex::new(
+                |_pk, d| index_tuple(&d.name, d.age),
+                "data",
+                "data__name_age",
+            ),
+        };
+        let map = IndexedMap::new("data", indexes);
+
+        // save data
+        let data1 = Data {
+            name: "Maria".to_string(),
+            last_name: "".to_string(),
+            age: 42,
+        };
+        let pk1 = ("1", "5627");
+        map.save(&mut store, pk1, &data1).unwrap();
+
+        let data2 = Data {
+            name: "Juan".to_string(),
+            last_name: "Perez".to_string(),
+            age: 13,
+        };
+        let pk2 = ("2", "5628");
+        map.save(&mut store, pk2, &data2).unwrap();
+
+        let data3 = Data {
+            name: "Maria".to_string(),
+            last_name: "Young".to_string(),
+            age: 24,
+        };
+        let pk3 = ("2", "5629");
+        map.save(&mut store, pk3, &data3).unwrap();
+
+        let data4 = Data {
+            name: "Maria Luisa".to_string(),
+            last_name: "Bemberg".to_string(),
+            age: 43,
+        };
+        let pk4 = ("3", "5630");
+        map.save(&mut store, pk4, &data4).unwrap();
+
+        // the unit sub-prefix of a pair ranges over all the full keys
+        let result: StdResult<Vec<_>> = map
+            .sub_prefix(())
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        let result = result.unwrap();
+        assert_eq!(
+            result,
+            [
+                (("1".to_string(), "5627".to_string()), data1),
+                (("2".to_string(), "5628".to_string()), data2),
+                (("2".to_string(), "5629".to_string()), data3),
+                (("3".to_string(), "5630".to_string()), data4),
+            ]
+        );
+    }
+
     #[test]
     #[cfg(feature = "iterator")]
     fn prefix_triple_key() {
@@ -1574,6 +2254,112 @@ mod test {
         }
     }
 
+    mod tuple_multi_index {
+        use super::*;
+
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct Product {
+            category: String,
+            subcategory: u32,
+        }
+
+        struct Indexes<'a> {
+            category: MultiIndex<'a, (String, u32), Product, &'a str>,
+        }
+
+        impl<'a> IndexList<Product> for Indexes<'a> {
+            fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Product>> + '_> {
+                let v: Vec<&dyn Index<Product>> = vec![&self.category];
+                Box::new(v.into_iter())
+            }
+        }
+
+        #[test]
+        #[cfg(feature = "iterator")]
+        fn prefix_by_leading_element() {
+            let indexes = Indexes {
+                category: MultiIndex::new(
+                    |_pk, p| (p.category.clone(), p.subcategory),
+                    "products",
+                    "products__category",
+                ),
+            };
+            let map = IndexedMap::<&str, Product, Indexes>::new("products", indexes);
+            let mut store = MockStorage::new();
+
+            let products = [
+                ("saw", "tools", 2),
+                ("hammer", "tools", 1),
+                ("apple", "food", 1),
+                ("drill", "tools", 2),
+                ("wrench", "tools", 1),
+                ("tool", "toolsx", 1),
+            ];
+            for (pk, category, subcategory) in products {
+                let product = Product {
+                    category: category.to_string(),
+                    subcategory,
+                };
+                map.save(&mut store, pk, &product).unwrap();
+            }
+
+            // all products of the category, by subcategory and then primary key
+            let pks = map
+                .idx
+                .category
+                .sub_prefix("tools".to_string())
+                .keys(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(pks, vec!["hammer", "wrench", "drill", "saw"]);
+
+            // bounds on the rest of the index key
+            let items = map
+                .idx
+                .category
+                .sub_prefix("tools".to_string())
+                .range(
+                    &store,
+                    Some(Bound::exclusive((1u32, "hammer"))),
+                    None,
+                    Order::Descending,
+                )
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            let pks = items.iter().map(|(pk, _)| pk.as_str()).collect::<Vec<_>>();
+            assert_eq!(pks, vec!["saw", "drill", "wrench"]);
+            assert_eq!(items[0].1.subcategory, 2);
+
+            // the full index key
+            let items = map
+                .idx
+                .category
+                .prefix(("tools".to_string(), 2))
+                .range(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(
+                items,
+                vec![
+                    (
+                        "drill".to_string(),
+                        Product {
+                            category: "tools".to_string(),
+                            subcategory: 2
+                        }
+                    ),
+                    (
+                        "saw".to_string(),
+                        Product {
+                            category: "tools".to_string(),
+                            subcategory: 2
+                        }
+                    ),
+                ]
+            );
+        }
+    }
+
     mod pk_multi_index {
         use super::*;
         use cosmwasm_std::{Addr, Uint128};
@@ -1701,13 +2487,228 @@ mod test {
         let mut storage = MockStorage::new();
         let (pks, _) = save_data(&mut storage);
 
-        DATA.clear(&mut storage);
+        assert_eq!(DATA.clear(&mut storage), pks.len());
 
         for key in pks {
             assert!(!DATA.has(&storage, key));
         }
     }
 
+    #[test]
+    fn save_with_old_works() {
+        let mut store = MockStorage::new();
+        save_data(&mut store);
+
+        let mut data = DATA.load(&store, "1").unwrap();
+        let old = data.clone();
+        data.name = "Mary".to_string();
+        data.age = 43;
+        DATA.save_with_old(&mut store, "1", &data, Some(&old))
+            .unwrap();
+
+        // same indexes as after a plain save
+        let mut expected = MockStorage::new();
+        save_data(&mut expected);
+        DATA.save(&mut expected, "1", &data).unwrap();
+        assert_eq!(
+            store
+                .range(None, None, Order::Ascending)
+                .collect::<Vec<_>>(),
+            expected
+                .range(None, None, Order::Ascending)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(DATA.idx.name.count(&store, "Maria".to_string()), 1);
+        assert_eq!(DATA.idx.name.count(&store, "Mary".to_string()), 1);
+        assert_eq!(DATA.idx.age.item(&store, 42).unwrap(), None);
+
+        // new entries have no old value
+        let new = Data {
+            name: "Jane".to_string(),
+            last_name: "Doe".to_string(),
+            age: 50,
+        };
+        DATA.save_with_old(&mut store, "6", &new, None).unwrap();
+        assert_eq!(DATA.idx.name.count(&store, "Jane".to_string()), 1);
+
+        // a wrong old value leaves the old index entries in place,
+        // here tripping the unique index on the unchanged age
+        let mut changed = new.clone();
+        changed.name = "Joan".to_string();
+        DATA.save_with_old(&mut store, "6", &changed, None)
+            .unwrap_err();
+    }
+
+    #[test]
+    fn remove_by_index_prefix_works() {
+        let mut store = MockStorage::new();
+        save_data(&mut store);
+
+        // two elements named "Maria", removed in batches
+        let removed = DATA
+            .remove_by_index_prefix(&mut store, &DATA.idx.name, "Maria".to_string(), Some(1))
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(DATA.idx.name.count(&store, "Maria".to_string()), 1);
+        let removed = DATA
+            .remove_by_index_prefix(&mut store, &DATA.idx.name, "Maria".to_string(), Some(1))
+            .unwrap();
+        assert_eq!(removed, 1);
+        let removed = DATA
+            .remove_by_index_prefix(&mut store, &DATA.idx.name, "Maria".to_string(), None)
+            .unwrap();
+        assert_eq!(removed, 0);
+
+        // primary rows and all their index entries are gone
+        assert!(!DATA.has(&store, "1"));
+        assert!(!DATA.has(&store, "2"));
+        assert_eq!(DATA.idx.name.count(&store, "Maria".to_string()), 0);
+        assert_eq!(DATA.idx.age.item(&store, 42).unwrap(), None);
+        assert_eq!(DATA.idx.age.item(&store, 23).unwrap(), None);
+        assert_eq!(
+            DATA.idx
+                .name_lastname
+                .item(&store, index_string_tuple("Maria", "Doe"))
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            DATA.idx
+                .name_lastname
+                .item(&store, index_string_tuple("Maria", "Williams"))
+                .unwrap(),
+            None
+        );
+
+        // the others are untouched, including the one sharing a name prefix
+        assert_eq!(DATA.idx.name.count(&store, "Maria Luisa".to_string()), 1);
+        assert_eq!(DATA.idx.age.item(&store, 12).unwrap().unwrap().1.age, 12);
+        let remaining = DATA
+            .keys(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(remaining, vec!["3", "4", "5"]);
+    }
+
+    #[test]
+    fn remove_by_index_prefix_skips_stale_entries() {
+        let mut store = MockStorage::new();
+        save_data(&mut store);
+
+        // "1" is gone but still indexed, "2" is indexed under a name it no longer has
+        DATA.primary.remove(&mut store, "1");
+        let mut data = DATA.load(&store, "2").unwrap();
+        data.name = "Mary".to_string();
+        DATA.primary.save(&mut store, "2", &data).unwrap();
+        DATA.idx.name.save(&mut store, b"2", &data).unwrap();
+
+        // every call makes progress, so the batch loop ends
+        for expected in [1, 1, 0] {
+            let removed = DATA
+                .remove_by_index_prefix(&mut store, &DATA.idx.name, "Maria".to_string(), Some(1))
+                .unwrap();
+            assert_eq!(removed, expected);
+        }
+
+        assert!(!DATA.has(&store, "2"));
+        assert_eq!(DATA.idx.name.count(&store, "Maria".to_string()), 0);
+        assert_eq!(DATA.idx.name.count(&store, "Mary".to_string()), 0);
+        assert_eq!(DATA.idx.age.item(&store, 23).unwrap(), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "Index namespace \"data\" collides with the primary map namespace")]
+    fn index_namespace_colliding_with_pk_namespace_panics() {
+        const BROKEN: IndexedMap<&str, Data, DataIndexes> = IndexedMap::new(
+            "data",
+            DataIndexes {
+                name: MultiIndex::new(|_pk, d| d.name.clone(), "data", "data"),
+                age: UniqueIndex::new(|d| d.age, "data__age"),
+                name_lastname: UniqueIndex::new(
+                    |d| index_string_tuple(&d.name, &d.last_name),
+                    "data__name_lastname",
+                ),
+            },
+        );
+
+        let mut store = MockStorage::new();
+        let data = Data {
+            name: "Maria".to_string(),
+            last_name: "Doe".to_string(),
+            age: 42,
+        };
+        BROKEN.save(&mut store, "1", &data).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Index namespace \"data__age\" is used by more than one index")]
+    fn index_namespaces_colliding_with_each_other_panic() {
+        IndexedMap::<&str, Data, _>::new_dyn(
+            "data",
+            DataIndexes {
+                name: MultiIndex::new(|_pk, d| d.name.clone(), "data", "data__name"),
+                age: UniqueIndex::new(|d| d.age, "data__age"),
+                name_lastname: UniqueIndex::new(
+                    |d| index_string_tuple(&d.name, &d.last_name),
+                    "data__age",
+                ),
+            },
+        );
+    }
+
+    #[test]
+    fn custom_index_with_required_methods_only() {
+        // counts the saved entries in a single item
+        struct CountIndex;
+
+        impl Index<Data> for CountIndex {
+            fn save(&self, store: &mut dyn Storage, _pk: &[u8], _data: &Data) -> StdResult<()> {
+                let count = store.get(b"count").map_or(0, |v| v[0]);
+                store.set(b"count", &[count + 1]);
+                Ok(())
+            }
+
+            fn remove(&self, store: &mut dyn Storage, _pk: &[u8], _old: &Data) -> StdResult<()> {
+                let count = store.get(b"count").map_or(0, |v| v[0]);
+                store.set(b"count", &[count - 1]);
+                Ok(())
+            }
+        }
+
+        struct CountIndexes {
+            count: CountIndex,
+        }
+
+        impl IndexList<Data> for CountIndexes {
+            fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Data>> + '_> {
+                let v: Vec<&dyn Index<Data>> = vec![&self.count];
+                Box::new(v.into_iter())
+            }
+        }
+
+        let map = IndexedMap::<&str, Data, _>::new_dyn("data", CountIndexes { count: CountIndex });
+        let mut store = MockStorage::new();
+        let data = Data {
+            name: "Maria".to_string(),
+            last_name: "Doe".to_string(),
+            age: 42,
+        };
+        map.save(&mut store, "1", &data).unwrap();
+        map.try_save(&mut store, "2", &data).unwrap();
+        assert_eq!(store.get(b"count"), Some(vec![2]));
+        #[cfg(feature = "verify-indexes")]
+        map.verify_indexes(&store).unwrap();
+
+        let rows = map
+            .range_with_index_keys(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(rows[0].2, vec![None]);
+
+        map.remove(&mut store, "1").unwrap();
+        assert_eq!(store.get(b"count"), Some(vec![1]));
+    }
+
     #[test]
     fn is_empty_works() {
         let mut storage = MockStorage::new();
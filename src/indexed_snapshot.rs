@@ -1,11 +1,13 @@
 // this module requires iterator to be useful at all
 #![cfg(feature = "iterator")]
 
-use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_std::storage_keys::namespace_with_key;
+use cosmwasm_std::{to_json_vec, MemoryStorage, StdError, StdResult, Storage};
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
 use crate::de::KeyDeserialize;
+use crate::indexes::debug_assert_distinct_namespaces;
 use crate::iter_helpers::deserialize_kv;
 use crate::keys::{Prefixer, PrimaryKey};
 use crate::namespace::Namespace;
@@ -92,6 +94,27 @@ where
         self.primary.assert_checkpointed(store, height)
     }
 
+    /// Only the primary values are snapshotted, the indexes always reflect the current state.
+    /// This rebuilds the primary map and all of its indexes as they were at the beginning of
+    /// the given height, into a separate in-memory store. The result can be queried with the
+    /// usual methods of this map and its indexes, e.g. `map.idx.owner.prefix(..).range(&historical, ..)`.
+    ///
+    /// Note that this reads the whole changelog and the whole primary map, and writes all the
+    /// historical entries again, so it is only suitable for small maps or off-chain queries.
+    pub fn index_at_height(&self, store: &dyn Storage, height: u64) -> StdResult<MemoryStorage> {
+        let mut historical = MemoryStorage::new();
+        for (pk, value) in self.primary.range_raw_at_height(store, height)? {
+            historical.set(
+                &namespace_with_key(&[self.pk_namespace.as_slice()], &pk),
+                &to_json_vec(&value)?,
+            );
+            for index in self.idx.get_indexes() {
+                index.save(&mut historical, &pk, &value)?;
+            }
+        }
+        Ok(historical)
+    }
+
     pub fn key(&self, k: K) -> Path<T> {
         self.primary.key(k)
     }
@@ -127,6 +150,7 @@ where
         old_data: Option<&T>,
         height: u64,
     ) -> StdResult<()> {
+        debug_assert_distinct_namespaces(self.pk_namespace.as_slice(), self.idx.get_indexes());
         // this is the key *relative* to the primary map namespace
         let pk = key.joined_key();
         if let Some(old) = old_data {
@@ -420,6 +444,55 @@ mod test {
         (pks, datas)
     }
 
+    #[test]
+    fn index_at_height_rebuilds_past_indexes() {
+        let mut store = MockStorage::new();
+        let map = build_snapshot_map();
+        let (_, datas) = save_data(&mut store, &map);
+
+        // rename one Maria and remove the other one
+        let mut renamed = datas[0].clone();
+        renamed.name = "Mary".to_string();
+        renamed.age = 43;
+        map.save(&mut store, "1", &renamed, 5).unwrap();
+        map.remove(&mut store, "2", 5).unwrap();
+
+        let marias = |store: &dyn Storage| -> Vec<_> {
+            map.idx
+                .name
+                .prefix(b"Maria".to_vec())
+                .range(store, None, None, Order::Ascending)
+                .collect::<StdResult<_>>()
+                .unwrap()
+        };
+        assert_eq!(marias(&store), vec![]);
+
+        // the indexes as they were at the beginning of height 5
+        let historical = map.index_at_height(&store, 5).unwrap();
+        assert_eq!(
+            marias(&historical),
+            vec![
+                ("1".to_string(), datas[0].clone()),
+                ("2".to_string(), datas[1].clone()),
+            ]
+        );
+        assert_eq!(
+            map.idx.age.item(&historical, 42).unwrap(),
+            Some((b"1".to_vec(), datas[0].clone()))
+        );
+        assert_eq!(map.idx.age.item(&historical, 43).unwrap(), None);
+        assert_eq!(map.load(&historical, "2").unwrap(), datas[1]);
+
+        // and after the changes, they match the current ones
+        let current = map.index_at_height(&store, 6).unwrap();
+        assert_eq!(marias(&current), vec![]);
+        assert_eq!(
+            map.idx.age.item(&current, 43).unwrap(),
+            Some((b"1".to_vec(), renamed))
+        );
+        assert!(!map.key("2").has(&current));
+    }
+
     #[test]
     fn store_and_load_by_index() {
         let mut store = MockStorage::new();
@@ -665,10 +738,34 @@ mod test {
         assert_eq!(
             changes,
             vec![
-                (("A".into(), 1), ChangeSet { old: None }),
-                (("A".into(), 3), ChangeSet { old: Some(data1) }),
-                (("B".into(), 2), ChangeSet { old: None }),
-                (("B".into(), 4), ChangeSet { old: Some(data2) })
+                (
+                    ("A".into(), 1),
+                    ChangeSet {
+                        old: None,
+                        new: None
+                    }
+                ),
+                (
+                    ("A".into(), 3),
+                    ChangeSet {
+                        old: Some(data1),
+                        new: None
+                    }
+                ),
+                (
+                    ("B".into(), 2),
+                    ChangeSet {
+                        old: None,
+                        new: None
+                    }
+                ),
+                (
+                    ("B".into(), 4),
+                    ChangeSet {
+                        old: Some(data2),
+                        new: None
+                    }
+                )
             ]
         );
     }
@@ -11,7 +11,10 @@ pub use unique::UniqueIndex;
 use serde::de::DeserializeOwned;
 use serde::Serialize;
 
-use cosmwasm_std::{StdResult, Storage};
+use std::fmt;
+
+use cosmwasm_std::storage_keys::namespace_with_key;
+use cosmwasm_std::{StdError, StdResult, Storage};
 
 // Note: we cannot store traits with generic functions inside `Box<dyn Index>`,
 // so I pull S: Storage to a top-level
@@ -21,6 +24,100 @@ where
 {
     fn save(&self, store: &mut dyn Storage, pk: &[u8], data: &T) -> StdResult<()>;
     fn remove(&self, store: &mut dyn Storage, pk: &[u8], old_data: &T) -> StdResult<()>;
+    /// Returns the key (relative to the index namespace) under which `save` stores an entry
+    /// for the given primary key and data, or `None` if the index does not expose it.
+    fn index_key_raw(&self, _pk: &[u8], _data: &T) -> Option<Vec<u8>> {
+        None
+    }
+    /// Returns the namespace the index entries are stored under, or `None` if the index
+    /// does not expose it.
+    fn namespace(&self) -> Option<&[u8]> {
+        None
+    }
+    /// Returns `true` if saving `data` under `pk` would violate a uniqueness constraint
+    /// of the index, because another primary key is already stored under the same index key.
+    /// Indexes without such a constraint never conflict.
+    fn conflicts(&self, _store: &dyn Storage, _pk: &[u8], _data: &T) -> StdResult<bool> {
+        Ok(false)
+    }
+    /// Returns `true` if the index holds the entry `save` would have written for the given
+    /// primary key and data. By default, this only checks that the index key is set,
+    /// and assumes the entry is there if the index does not expose its namespace and keys.
+    fn contains(&self, store: &dyn Storage, pk: &[u8], data: &T) -> StdResult<bool> {
+        match (self.namespace(), self.index_key_raw(pk, data)) {
+            (Some(namespace), Some(key)) => {
+                let key = namespace_with_key(&[namespace], &key);
+                Ok(store.get(&key).is_some())
+            }
+            _ => Ok(true),
+        }
+    }
+}
+
+/// Error returned by `IndexedMap::try_save`, which tells uniqueness violations apart
+/// from other failures, so contracts can map them to their own errors.
+#[derive(Debug, PartialEq)]
+pub enum IndexError {
+    /// The data would be stored under an index key that is already taken by another record.
+    /// `index` is the namespace of the violated index, or empty if the index does not expose it.
+    UniqueViolation {
+        index: String,
+    },
+    Std(StdError),
+}
+
+impl fmt::Display for IndexError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IndexError::UniqueViolation { index } => {
+                write!(f, "Violates unique constraint on index {}", index)
+            }
+            IndexError::Std(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for IndexError {}
+
+impl From<StdError> for IndexError {
+    fn from(err: StdError) -> Self {
+        IndexError::Std(err)
+    }
+}
+
+/// Panics if an index shares its namespace with the primary map or with another index,
+/// which would silently corrupt data. Indexes that do not expose their namespace are skipped.
+/// This is only checked in debug builds.
+pub(crate) fn debug_assert_distinct_namespaces<'i, T>(
+    pk_namespace: &[u8],
+    indexes: impl Iterator<Item = &'i dyn Index<T>>,
+) where
+    T: Serialize + DeserializeOwned + Clone + 'i,
+{
+    if !cfg!(debug_assertions) {
+        return;
+    }
+    let mut seen = vec![];
+    for namespace in indexes.filter_map(|index| index.namespace()) {
+        if namespace == pk_namespace {
+            panic!(
+                "Index namespace \"{}\" collides with the primary map namespace",
+                String::from_utf8_lossy(namespace)
+            );
+        }
+        if seen.contains(&namespace) {
+            panic!(
+                "Index namespace \"{}\" is used by more than one index",
+                String::from_utf8_lossy(namespace)
+            );
+        }
+        seen.push(namespace);
+    }
+}
+
+/// Default index key normalization, leaving the key untouched
+pub(crate) fn no_normalization<K>(k: K) -> K {
+    k
 }
 
 #[cfg(test)]
@@ -9,7 +9,7 @@ use cosmwasm_std::{from_json, Order, Record, StdError, StdResult, Storage};
 
 use crate::bound::PrefixBound;
 use crate::de::KeyDeserialize;
-use crate::indexes::IndexPrefix;
+use crate::indexes::{no_normalization, IndexPrefix};
 use crate::iter_helpers::deserialize_kv;
 use crate::map::Map;
 use crate::prefix::namespaced_prefix_range;
@@ -30,6 +30,7 @@ use std::marker::PhantomData;
 /// or its owned variant.
 pub struct MultiIndex<'a, IK, T, PK> {
     index: fn(&[u8], &T) -> IK,
+    normalize: fn(IK) -> IK,
     idx_namespace: &'a [u8],
     // note, we collapse the ik - combining everything under the namespace - and concatenating the pk
     idx_map: Map<Vec<u8>, u32>,
@@ -72,12 +73,82 @@ where
     ) -> Self {
         MultiIndex {
             index: idx_fn,
+            normalize: no_normalization,
             idx_namespace: idx_namespace.as_bytes(),
             idx_map: Map::new(idx_namespace),
             pk_namespace: pk_namespace.as_bytes(),
             phantom: PhantomData,
         }
     }
+
+    /// Create a new MultiIndex over a normalized index key
+    ///
+    /// idx_fn - lambda creating index key from value
+    /// normalize_fn - applied to the index key on save and remove, as well as to the keys
+    /// passed to `prefix`, `index_key` and the range and `prefix_range` bounds, so reads and
+    /// writes always agree. It works on whole index keys, so the partial keys passed to
+    /// `sub_prefix` cannot be normalized, and must already be in normalized form
+    /// pk_namespace - prefix for the primary key
+    /// idx_namespace - prefix for the index value
+    ///
+    /// ## Example:
+    ///
+    /// ```rust
+    /// use cw_storage_plus::MultiIndex;
+    /// use serde::{Deserialize, Serialize};
+    ///
+    /// #[derive(Deserialize, Serialize, Clone)]
+    /// struct Data {
+    ///     pub name: String,
+    ///     pub age: u32,
+    /// }
+    ///
+    /// let index: MultiIndex<_, _, String> = MultiIndex::new_normalized(
+    ///     |_pk: &[u8], d: &Data| d.name.clone(),
+    ///     |name| name.to_lowercase(),
+    ///     "data",
+    ///     "data__name",
+    /// );
+    /// ```
+    pub const fn new_normalized(
+        idx_fn: fn(&[u8], &T) -> IK,
+        normalize_fn: fn(IK) -> IK,
+        pk_namespace: &'a str,
+        idx_namespace: &'static str,
+    ) -> Self {
+        MultiIndex {
+            index: idx_fn,
+            normalize: normalize_fn,
+            idx_namespace: idx_namespace.as_bytes(),
+            idx_map: Map::new(idx_namespace),
+            pk_namespace: pk_namespace.as_bytes(),
+            phantom: PhantomData,
+        }
+    }
+
+    fn normalize_bound<PB>(&self, bound: Option<Bound<'a, (IK, PB)>>) -> Option<Bound<'a, (IK, PB)>>
+    where
+        (IK, PB): PrimaryKey<'a>,
+    {
+        bound.map(|b| match b {
+            Bound::Inclusive(((ik, pk), p)) => Bound::Inclusive((((self.normalize)(ik), pk), p)),
+            Bound::Exclusive(((ik, pk), p)) => Bound::Exclusive((((self.normalize)(ik), pk), p)),
+            raw => raw,
+        })
+    }
+
+    fn normalize_prefix_bound(
+        &self,
+        bound: Option<PrefixBound<'a, IK>>,
+    ) -> Option<PrefixBound<'a, IK>>
+    where
+        IK: Prefixer<'a>,
+    {
+        bound.map(|b| match b {
+            PrefixBound::Inclusive((k, p)) => PrefixBound::Inclusive(((self.normalize)(k), p)),
+            PrefixBound::Exclusive((k, p)) => PrefixBound::Exclusive(((self.normalize)(k), p)),
+        })
+    }
 }
 
 fn deserialize_multi_v<T: DeserializeOwned>(
@@ -129,21 +200,40 @@ fn deserialize_multi_kv<K: KeyDeserialize, T: DeserializeOwned>(
     Ok((K::from_slice(pk)?, v))
 }
 
+fn deserialize_multi_k<K: KeyDeserialize>(kv: Record) -> StdResult<K::Output> {
+    let (key, pk_len) = kv;
+
+    // Deserialize pk_len
+    let pk_len = from_json::<u32>(pk_len.as_slice())?;
+
+    // Recover pk from last part of k, without loading the value
+    let offset = key.len() - pk_len as usize;
+    K::from_slice(&key[offset..])
+}
+
 impl<'a, IK, T, PK> Index<T> for MultiIndex<'a, IK, T, PK>
 where
     T: Serialize + DeserializeOwned + Clone,
     IK: PrimaryKey<'a>,
 {
     fn save(&self, store: &mut dyn Storage, pk: &[u8], data: &T) -> StdResult<()> {
-        let idx = (self.index)(pk, data).joined_extra_key(pk);
+        let idx = (self.normalize)((self.index)(pk, data)).joined_extra_key(pk);
         self.idx_map.save(store, idx, &(pk.len() as u32))
     }
 
     fn remove(&self, store: &mut dyn Storage, pk: &[u8], old_data: &T) -> StdResult<()> {
-        let idx = (self.index)(pk, old_data).joined_extra_key(pk);
+        let idx = (self.normalize)((self.index)(pk, old_data)).joined_extra_key(pk);
         self.idx_map.remove(store, idx);
         Ok(())
     }
+
+    fn index_key_raw(&self, pk: &[u8], data: &T) -> Option<Vec<u8>> {
+        Some((self.normalize)((self.index)(pk, data)).joined_extra_key(pk))
+    }
+
+    fn namespace(&self) -> Option<&[u8]> {
+        Some(self.idx_map.namespace_bytes())
+    }
 }
 
 impl<'a, IK, T, PK> MultiIndex<'a, IK, T, PK>
@@ -160,6 +250,35 @@ where
             deserialize_multi_v,
         )
     }
+
+    /// Removes up to `limit` entries stored under the given index value and returns the raw
+    /// primary keys they pointed to. The entries are removed directly, without loading the
+    /// values they reference.
+    pub(crate) fn remove_prefix_raw(
+        &self,
+        store: &mut dyn Storage,
+        idx: IK,
+        limit: usize,
+    ) -> Vec<Vec<u8>> {
+        let idx = (self.normalize)(idx);
+        let idx_key = idx.joined_extra_key(b"");
+        let pks = IndexPrefix::<Vec<u8>, T>::with_deserialization_functions(
+            self.idx_namespace,
+            &idx.prefix(),
+            self.pk_namespace,
+            deserialize_multi_v,
+            deserialize_multi_v,
+        )
+        .keys_raw(store, None, None, Order::Ascending)
+        .take(limit)
+        .collect::<Vec<_>>();
+
+        for pk in &pks {
+            self.idx_map
+                .remove(store, [idx_key.as_slice(), pk].concat());
+        }
+        pks
+    }
 }
 
 impl<'a, IK, T, PK> MultiIndex<'a, IK, T, PK>
@@ -169,7 +288,7 @@ where
     IK: PrimaryKey<'a> + Prefixer<'a>,
 {
     pub fn index_key(&self, k: IK) -> Vec<u8> {
-        k.joined_extra_key(b"")
+        (self.normalize)(k).joined_extra_key(b"")
     }
 
     #[cfg(test)]
@@ -214,6 +333,7 @@ where
     where
         T: 'c,
     {
+        let (min, max) = (self.normalize_bound(min), self.normalize_bound(max));
         self.no_prefix_raw().range_raw(store, min, max, order)
     }
 
@@ -224,6 +344,7 @@ where
         max: Option<Bound<'a, (IK, PK)>>,
         order: Order,
     ) -> Box<dyn Iterator<Item = Vec<u8>> + 'c> {
+        let (min, max) = (self.normalize_bound(min), self.normalize_bound(max));
         self.no_prefix_raw().keys_raw(store, min, max, order)
     }
 
@@ -260,13 +381,15 @@ where
     pub fn prefix(&self, p: IK) -> IndexPrefix<PK, T, PK> {
         IndexPrefix::with_deserialization_functions(
             self.idx_namespace,
-            &p.prefix(),
+            &(self.normalize)(p).prefix(),
             self.pk_namespace,
             deserialize_multi_kv::<PK, T>,
             deserialize_multi_v,
         )
+        .with_key_deserialization_function(deserialize_multi_k::<PK>)
     }
 
+    /// Note that the partial key is not normalized, see [`MultiIndex::new_normalized`].
     pub fn sub_prefix(&self, p: IK::Prefix) -> IndexPrefix<PK, T, (IK::Suffix, PK)> {
         IndexPrefix::with_deserialization_functions(
             self.idx_namespace,
@@ -275,6 +398,7 @@ where
             deserialize_multi_kv::<PK, T>,
             deserialize_multi_v,
         )
+        .with_key_deserialization_function(deserialize_multi_k::<PK>)
     }
 }
 
@@ -305,6 +429,10 @@ where
         PK: 'c,
         PK::Output: 'static,
     {
+        let (min, max) = (
+            self.normalize_prefix_bound(min),
+            self.normalize_prefix_bound(max),
+        );
         let mapped = namespaced_prefix_range(store, self.idx_namespace, min, max, order)
             .map(deserialize_kv::<PK, T>);
         Box::new(mapped)
@@ -321,6 +449,7 @@ where
         T: 'c,
         PK::Output: 'static,
     {
+        let (min, max) = (self.normalize_bound(min), self.normalize_bound(max));
         self.no_prefix().range(store, min, max, order)
     }
 
@@ -335,6 +464,7 @@ where
         T: 'c,
         PK::Output: 'static,
     {
+        let (min, max) = (self.normalize_bound(min), self.normalize_bound(max));
         self.no_prefix().keys(store, min, max, order)
     }
 
@@ -346,5 +476,6 @@ where
             deserialize_multi_kv::<PK, T>,
             deserialize_multi_v,
         )
+        .with_key_deserialization_function(deserialize_multi_k::<PK>)
     }
 }
@@ -17,6 +17,14 @@ type DeserializeVFn<T> = fn(&dyn Storage, &[u8], Record) -> StdResult<Record<T>>
 type DeserializeKvFn<K, T> =
     fn(&dyn Storage, &[u8], Record) -> StdResult<(<K as KeyDeserialize>::Output, T)>;
 
+type DeserializeKFn<K> = fn(Record) -> StdResult<<K as KeyDeserialize>::Output>;
+
+/// A page of entries together with the bound to continue after them
+type IndexRangePage<'b, K, T, B> = (
+    Vec<(<K as KeyDeserialize>::Output, T)>,
+    Option<Bound<'b, B>>,
+);
+
 pub fn default_deserializer_v<T: DeserializeOwned>(
     _: &dyn Storage,
     _: &[u8],
@@ -43,6 +51,8 @@ where
     pk_name: Vec<u8>,
     de_fn_kv: DeserializeKvFn<K, T>,
     de_fn_v: DeserializeVFn<T>,
+    /// Deserializes the key only, if this can be done without loading the value
+    de_fn_k: Option<DeserializeKFn<K>>,
 }
 
 impl<K, T> Debug for IndexPrefix<K, T>
@@ -97,8 +107,16 @@ where
             pk_name: pk_name.to_vec(),
             de_fn_kv,
             de_fn_v,
+            de_fn_k: None,
         }
     }
+
+    /// Lets `keys` deserialize the keys with the given function, instead of deserializing
+    /// whole records and dropping the values.
+    pub(crate) fn with_key_deserialization_function(mut self, de_fn_k: DeserializeKFn<K>) -> Self {
+        self.de_fn_k = Some(de_fn_k);
+        self
+    }
 }
 
 impl<'b, K, T, B> IndexPrefix<K, T, B>
@@ -146,9 +164,22 @@ where
         )
     }
 
+    /// Counts the index entries under the prefix within the given bounds, without trimming
+    /// or deserializing the keys. Every entry is counted, so for a `MultiIndex` this is
+    /// the number of matching records.
+    pub fn count(
+        &self,
+        store: &dyn Storage,
+        min: Option<Bound<'b, B>>,
+        max: Option<Bound<'b, B>>,
+    ) -> usize {
+        self.inner.count(store, min, max)
+    }
+
     /// Clears the prefix, removing the first `limit` elements (or all if `limit == None`).
-    pub fn clear(&self, store: &mut dyn Storage, limit: Option<usize>) {
-        self.inner.clear(store, limit);
+    /// Returns how many elements were removed.
+    pub fn clear(&self, store: &mut dyn Storage, limit: Option<usize>) -> usize {
+        self.inner.clear(store, limit)
     }
 
     /// Returns `true` if the prefix is empty.
@@ -199,17 +230,59 @@ where
         T: 'a,
         K::Output: 'static,
     {
+        let records = crate::prefix::range_with_prefix(
+            store,
+            &self.inner.storage_prefix,
+            min.map(|b| b.to_raw_bound()),
+            max.map(|b| b.to_raw_bound()),
+            order,
+        );
+        if let Some(de_fn_k) = self.de_fn_k {
+            return Box::new(records.map(de_fn_k));
+        }
         let de_fn = self.de_fn_kv;
         let pk_name = self.pk_name.clone();
-        let mapped = crate::prefix::range_with_prefix(
+        let mapped = records.map(move |kv| (de_fn)(store, &pk_name, kv).map(|(k, _)| k));
+        Box::new(mapped)
+    }
+
+    /// Returns up to `limit` entries in the given range, together with an exclusive bound just past
+    /// the last of them, like `Map::range_limited`. The bound can be passed back as `min`
+    /// (or `max` in descending order) to get the next page, and is `None` if fewer than `limit`
+    /// entries were found.
+    ///
+    /// The bound is built from the raw index key, so it is exact even though `MultiIndex` keys
+    /// embed the primary key.
+    pub fn range_limited(
+        &self,
+        store: &dyn Storage,
+        min: Option<Bound<'b, B>>,
+        max: Option<Bound<'b, B>>,
+        order: Order,
+        limit: usize,
+    ) -> StdResult<IndexRangePage<'b, K, T, B>> {
+        let mut items = Vec::with_capacity(limit);
+        let mut last_key = None;
+        let records = crate::prefix::range_with_prefix(
             store,
             &self.inner.storage_prefix,
             min.map(|b| b.to_raw_bound()),
             max.map(|b| b.to_raw_bound()),
             order,
-        )
-        .map(move |kv| (de_fn)(store, &pk_name, kv).map(|(k, _)| k));
-        Box::new(mapped)
+        );
+        for (raw_key, value) in records.take(limit) {
+            items.push((self.de_fn_kv)(
+                store,
+                &self.pk_name,
+                (raw_key.clone(), value),
+            )?);
+            last_key = Some(raw_key);
+        }
+        let next = match items.len() < limit {
+            true => None,
+            false => last_key.map(Bound::ExclusiveRaw),
+        };
+        Ok((items, next))
     }
 }
 
@@ -233,6 +306,7 @@ mod test {
             pk_name: vec![],
             de_fn_kv: |_, _, kv| deserialize_kv::<Vec<u8>, u64>(kv),
             de_fn_v: |_, _, kv| deserialize_v(kv),
+            de_fn_k: None,
         };
 
         // set some data, we care about "foo" prefix
@@ -384,6 +458,7 @@ mod test {
             pk_name: vec![],
             de_fn_kv: |_, _, kv| deserialize_kv::<Vec<u8>, u64>(kv),
             de_fn_v: |_, _, kv| deserialize_v(kv),
+            de_fn_k: None,
         };
 
         let mut storage = MockStorage::new();
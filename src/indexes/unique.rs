@@ -10,7 +10,8 @@ use cosmwasm_std::{from_json, Binary, Order, Record, StdError, StdResult, Storag
 
 use crate::bound::PrefixBound;
 use crate::de::KeyDeserialize;
-use crate::indexes::IndexPrefix;
+use crate::helpers::not_found_error;
+use crate::indexes::{no_normalization, IndexPrefix};
 use crate::iter_helpers::deserialize_kv;
 use crate::map::Map;
 use crate::prefix::namespaced_prefix_range;
@@ -29,6 +30,7 @@ pub(crate) struct UniqueRef<T> {
 /// The PK type defines the type of Primary Key deserialization.
 pub struct UniqueIndex<'a, IK, T, PK> {
     index: fn(&T) -> IK,
+    normalize: fn(IK) -> IK,
     idx_map: Map<IK, UniqueRef<T>>,
     idx_namespace: &'a [u8],
     phantom: PhantomData<PK>,
@@ -55,6 +57,46 @@ impl<'a, IK, T, PK> UniqueIndex<'a, IK, T, PK> {
     pub const fn new(idx_fn: fn(&T) -> IK, idx_namespace: &'static str) -> Self {
         UniqueIndex {
             index: idx_fn,
+            normalize: no_normalization,
+            idx_map: Map::new(idx_namespace),
+            idx_namespace: idx_namespace.as_bytes(),
+            phantom: PhantomData,
+        }
+    }
+
+    /// Create a new UniqueIndex over a normalized index key
+    ///
+    /// idx_fn - lambda creating index key from index value
+    /// normalize_fn - applied to the index key on save and remove, as well as to the keys
+    /// passed to `item`, `index_key` and the range bounds, so reads and writes always agree.
+    /// It works on whole index keys, so the partial keys passed to `prefix`, `sub_prefix` and
+    /// `prefix_range` cannot be normalized, and must already be in normalized form
+    /// idx_namespace - prefix for the index value
+    ///
+    /// ## Example:
+    ///
+    /// ```rust
+    /// use cw_storage_plus::UniqueIndex;
+    ///
+    /// struct User {
+    ///     pub name: String,
+    /// }
+    ///
+    /// // "Alice" and "alice" collide
+    /// UniqueIndex::<_, _, ()>::new_normalized(
+    ///     |u: &User| u.name.clone(),
+    ///     |name| name.to_lowercase(),
+    ///     "users__name",
+    /// );
+    /// ```
+    pub const fn new_normalized(
+        idx_fn: fn(&T) -> IK,
+        normalize_fn: fn(IK) -> IK,
+        idx_namespace: &'static str,
+    ) -> Self {
+        UniqueIndex {
+            index: idx_fn,
+            normalize: normalize_fn,
             idx_map: Map::new(idx_namespace),
             idx_namespace: idx_namespace.as_bytes(),
             phantom: PhantomData,
@@ -68,7 +110,7 @@ where
     IK: PrimaryKey<'a>,
 {
     fn save(&self, store: &mut dyn Storage, pk: &[u8], data: &T) -> StdResult<()> {
-        let idx = (self.index)(data);
+        let idx = (self.normalize)((self.index)(data));
         // error if this is already set
         self.idx_map
             .update(store, idx, |existing| -> StdResult<_> {
@@ -84,10 +126,36 @@ where
     }
 
     fn remove(&self, store: &mut dyn Storage, _pk: &[u8], old_data: &T) -> StdResult<()> {
-        let idx = (self.index)(old_data);
+        let idx = (self.normalize)((self.index)(old_data));
         self.idx_map.remove(store, idx);
         Ok(())
     }
+
+    fn index_key_raw(&self, _pk: &[u8], data: &T) -> Option<Vec<u8>> {
+        Some((self.normalize)((self.index)(data)).joined_key())
+    }
+
+    fn namespace(&self) -> Option<&[u8]> {
+        Some(self.idx_map.namespace_bytes())
+    }
+
+    fn conflicts(&self, store: &dyn Storage, pk: &[u8], data: &T) -> StdResult<bool> {
+        let idx = (self.normalize)((self.index)(data));
+        // the entry of the record itself is replaced on save
+        Ok(self
+            .idx_map
+            .may_load(store, idx)?
+            .map_or(false, |existing| existing.pk.as_slice() != pk))
+    }
+
+    fn contains(&self, store: &dyn Storage, pk: &[u8], data: &T) -> StdResult<bool> {
+        let idx = (self.normalize)((self.index)(data));
+        // the entry must also point back to the record
+        Ok(self
+            .idx_map
+            .may_load(store, idx)?
+            .map_or(false, |existing| existing.pk.as_slice() == pk))
+    }
 }
 
 fn deserialize_unique_v<T: DeserializeOwned>(kv: Record) -> StdResult<Record<T>> {
@@ -110,7 +178,15 @@ where
     IK: PrimaryKey<'a>,
 {
     pub fn index_key(&self, k: IK) -> Vec<u8> {
-        k.joined_key()
+        (self.normalize)(k).joined_key()
+    }
+
+    fn normalize_bound(&self, bound: Option<Bound<'a, IK>>) -> Option<Bound<'a, IK>> {
+        bound.map(|b| match b {
+            Bound::Inclusive((k, p)) => Bound::Inclusive(((self.normalize)(k), p)),
+            Bound::Exclusive((k, p)) => Bound::Exclusive(((self.normalize)(k), p)),
+            raw => raw,
+        })
     }
 
     fn no_prefix_raw(&self) -> IndexPrefix<Vec<u8>, T, IK> {
@@ -127,10 +203,29 @@ where
     pub fn item(&self, store: &dyn Storage, idx: IK) -> StdResult<Option<Record<T>>> {
         let data = self
             .idx_map
-            .may_load(store, idx)?
+            .may_load(store, (self.normalize)(idx))?
             .map(|i| (i.pk.into(), i.value));
         Ok(data)
     }
+
+    /// Returns the data matching this secondary index, or an error if there is none.
+    /// Use `item` if the primary key is needed too.
+    pub fn load(&self, store: &dyn Storage, idx: IK) -> StdResult<T> {
+        let idx = (self.normalize)(idx);
+        self.idx_map
+            .may_load(store, idx.clone())?
+            .map(|i| i.value)
+            .ok_or_else(|| not_found_error::<T>(&self.idx_map.key(idx)))
+    }
+
+    /// Returns the data matching this secondary index, or `None` if there is none.
+    pub fn may_load(&self, store: &dyn Storage, idx: IK) -> StdResult<Option<T>> {
+        let data = self
+            .idx_map
+            .may_load(store, (self.normalize)(idx))?
+            .map(|i| i.value);
+        Ok(data)
+    }
 }
 
 // short-cut for simple keys, rather than .prefix(()).range_raw(...)
@@ -151,6 +246,7 @@ where
     where
         T: 'c,
     {
+        let (min, max) = (self.normalize_bound(min), self.normalize_bound(max));
         self.no_prefix_raw().range_raw(store, min, max, order)
     }
 
@@ -161,6 +257,7 @@ where
         max: Option<Bound<'a, IK>>,
         order: Order,
     ) -> Box<dyn Iterator<Item = Vec<u8>> + 'c> {
+        let (min, max) = (self.normalize_bound(min), self.normalize_bound(max));
         self.no_prefix_raw().keys_raw(store, min, max, order)
     }
 }
@@ -178,6 +275,8 @@ where
     /// `PrefixBound`).
     /// There are some issues that distinguish these two, and blindly casting to `Vec<u8>` doesn't
     /// solve them.
+    ///
+    /// Note that the bounds are not normalized, see [`UniqueIndex::new_normalized`].
     pub fn prefix_range<'c>(
         &self,
         store: &'c dyn Storage,
@@ -208,6 +307,7 @@ where
         T: 'c,
         PK::Output: 'static,
     {
+        let (min, max) = (self.normalize_bound(min), self.normalize_bound(max));
         self.no_prefix().range(store, min, max, order)
     }
 
@@ -222,9 +322,19 @@ where
         T: 'c,
         PK::Output: 'static,
     {
+        let (min, max) = (self.normalize_bound(min), self.normalize_bound(max));
         self.no_prefix().keys(store, min, max, order)
     }
 
+    /// Returns the deserialized primary key matching this secondary index, or `None` if there is none.
+    pub fn may_load_pk(&self, store: &dyn Storage, idx: IK) -> StdResult<Option<PK::Output>> {
+        self.idx_map
+            .may_load(store, (self.normalize)(idx))?
+            .map(|i| PK::from_slice(&i.pk))
+            .transpose()
+    }
+
+    /// Note that the partial key is not normalized, see [`UniqueIndex::new_normalized`].
     pub fn prefix(&self, p: IK::Prefix) -> IndexPrefix<PK, T, IK::Suffix> {
         IndexPrefix::with_deserialization_functions(
             self.idx_namespace,
@@ -235,6 +345,7 @@ where
         )
     }
 
+    /// Note that the partial key is not normalized, see [`UniqueIndex::new_normalized`].
     pub fn sub_prefix(&self, p: IK::SubPrefix) -> IndexPrefix<PK, T, IK::SuperSuffix> {
         IndexPrefix::with_deserialization_functions(
             self.idx_namespace,
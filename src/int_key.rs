@@ -1,6 +1,6 @@
 use std::mem;
 
-use cosmwasm_std::{Int128, Int64, Uint128, Uint64};
+use cosmwasm_std::{Int128, Int256, Int64, Uint128, Uint256, Uint64};
 
 /// Our int keys are simply the big-endian representation bytes for unsigned ints,
 /// but "sign-flipped" (xored msb) big-endian bytes for signed ints.
@@ -95,6 +95,38 @@ macro_rules! cw_int_std_keys {
 
 cw_int_std_keys!(for Int64, Int128);
 
+// there is no primitive 256-bit integer to delegate to, so these are implemented directly
+impl IntKey for Uint256 {
+    type Buf = [u8; 32];
+
+    #[inline]
+    fn to_cw_bytes(&self) -> Self::Buf {
+        self.to_be_bytes()
+    }
+
+    #[inline]
+    fn from_cw_bytes(bytes: Self::Buf) -> Self {
+        Self::from_be_bytes(bytes)
+    }
+}
+
+impl IntKey for Int256 {
+    type Buf = [u8; 32];
+
+    #[inline]
+    fn to_cw_bytes(&self) -> Self::Buf {
+        let mut bytes = self.to_be_bytes();
+        bytes[0] ^= 0x80;
+        bytes
+    }
+
+    #[inline]
+    fn from_cw_bytes(mut bytes: Self::Buf) -> Self {
+        bytes[0] ^= 0x80;
+        Self::from_be_bytes(bytes)
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -161,6 +193,27 @@ mod test {
         );
     }
 
+    #[test]
+    fn x256_int_key_works() {
+        let mut expected = [0u8; 32];
+        expected[27..].copy_from_slice(&[0x42, 0x43, 0x44, 0x45, 0x46]);
+        assert_eq!(Uint256::from(0x4243444546u128).to_cw_bytes(), expected);
+
+        expected[0] = 0x80;
+        assert_eq!(Int256::from(0x4243444546i128).to_cw_bytes(), expected);
+
+        assert_eq!(Int256::MIN.to_cw_bytes(), [0u8; 32]);
+        assert_eq!(Int256::MAX.to_cw_bytes(), [0xff; 32]);
+        assert_eq!(Uint256::MAX.to_cw_bytes(), [0xff; 32]);
+
+        for value in [Int256::MIN, Int256::from(-1), Int256::zero(), Int256::MAX] {
+            assert_eq!(Int256::from_cw_bytes(value.to_cw_bytes()), value);
+        }
+        for value in [Uint256::zero(), Uint256::from(42u8), Uint256::MAX] {
+            assert_eq!(Uint256::from_cw_bytes(value.to_cw_bytes()), value);
+        }
+    }
+
     #[test]
     fn unsigned_int_key_order() {
         assert!(0u32.to_cw_bytes() < 652u32.to_cw_bytes());
@@ -170,5 +223,16 @@ mod test {
     fn signed_int_key_order() {
         assert!((-321i32).to_cw_bytes() < 0i32.to_cw_bytes());
         assert!(0i32.to_cw_bytes() < 652i32.to_cw_bytes());
+
+        let ordered = [
+            Int256::MIN,
+            Int256::from(-1),
+            Int256::zero(),
+            Int256::one(),
+            Int256::MAX,
+        ];
+        for pair in ordered.windows(2) {
+            assert!(pair[0].to_cw_bytes() < pair[1].to_cw_bytes());
+        }
     }
 }
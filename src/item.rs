@@ -7,7 +7,12 @@ use cosmwasm_std::{
     WasmQuery,
 };
 
-use crate::{helpers::not_found_object_info, namespace::Namespace};
+#[cfg(feature = "load-into")]
+use crate::helpers::from_json_in_place;
+use crate::{
+    helpers::{not_found_error, query_raw},
+    namespace::Namespace,
+};
 
 /// Item stores one typed item at the given key.
 /// This is an analog of Singleton.
@@ -48,6 +53,13 @@ where
         self.storage_key.as_slice()
     }
 
+    /// Returns the storage key of the item, which can be used for raw queries.
+    /// Unlike for maps, this is just the storage key the item was created with,
+    /// without a length prefix.
+    pub fn raw_key(&self) -> Vec<u8> {
+        self.storage_key.as_slice().to_vec()
+    }
+
     /// save will serialize the model and store, returns an error on serialization issues
     pub fn save(&self, store: &mut dyn Storage, data: &T) -> StdResult<()> {
         store.set(self.storage_key.as_slice(), &to_json_vec(data)?);
@@ -58,13 +70,33 @@ where
         store.remove(self.storage_key.as_slice());
     }
 
+    /// Stores `data` and returns the value it replaced, or `None` if there was none.
+    /// Nothing is written if either value fails to (de)serialize.
+    pub fn swap(&self, store: &mut dyn Storage, data: &T) -> StdResult<Option<T>> {
+        let key = self.storage_key.as_slice();
+        let new = to_json_vec(data)?;
+        let old = store.get(key).map(from_json).transpose()?;
+        store.set(key, &new);
+        Ok(old)
+    }
+
     /// load will return an error if no data is set at the given key, or on parse error
     pub fn load(&self, store: &dyn Storage) -> StdResult<T> {
         if let Some(value) = store.get(self.storage_key.as_slice()) {
             from_json(value)
         } else {
-            let object_info = not_found_object_info::<T>(self.storage_key.as_slice());
-            Err(StdError::not_found(object_info))
+            Err(not_found_error::<T>(self.storage_key.as_slice()))
+        }
+    }
+
+    /// load_into works like `load`, but deserializes into an existing value, reusing its
+    /// allocations where possible. See [`Path::load_into`](crate::Path::load_into).
+    #[cfg(feature = "load-into")]
+    pub fn load_into(&self, store: &dyn Storage, out: &mut T) -> StdResult<()> {
+        if let Some(value) = store.get(self.storage_key.as_slice()) {
+            from_json_in_place(&value, out)
+        } else {
+            Err(not_found_error::<T>(self.storage_key.as_slice()))
         }
     }
 
@@ -75,11 +107,38 @@ where
         value.map(|v| from_json(v)).transpose()
     }
 
+    /// Returns the stored value, or the one produced by `default` if there is none.
+    /// Nothing is written in either case.
+    pub fn load_or(&self, store: &dyn Storage, default: impl FnOnce() -> T) -> StdResult<T> {
+        Ok(self.may_load(store)?.unwrap_or_else(default))
+    }
+
+    /// Returns the stored value, or `T::default()` if there is none.
+    /// Nothing is written in either case.
+    pub fn load_or_default(&self, store: &dyn Storage) -> StdResult<T>
+    where
+        T: Default,
+    {
+        Ok(self.may_load(store)?.unwrap_or_default())
+    }
+
     /// Returns `true` if data is stored at the key, `false` otherwise.
     pub fn exists(&self, store: &dyn Storage) -> bool {
         store.get(self.storage_key.as_slice()).is_some()
     }
 
+    /// Returns `true` if data is stored at the key, `false` otherwise.
+    /// This is an alias of [`Item::exists`], named like `Map::has` and `Path::has`.
+    pub fn has(&self, store: &dyn Storage) -> bool {
+        self.exists(store)
+    }
+
+    /// Returns the length in bytes of the raw value stored at the key, without parsing it.
+    /// Returns `None` if there is no data at the key.
+    pub fn value_len(&self, store: &dyn Storage) -> Option<usize> {
+        store.get(self.storage_key.as_slice()).map(|v| v.len())
+    }
+
     /// Loads the data, perform the specified action, and store the result
     /// in the database. This is shorthand for some common sequences, which may be useful.
     ///
@@ -96,6 +155,75 @@ where
         Ok(output)
     }
 
+    /// Like `update`, but also works if no data is stored yet. `action` is called with
+    /// `Some(value)` if the data exists and `None` otherwise, like the closure of `Map::update`.
+    pub fn may_update<A, E>(&self, store: &mut dyn Storage, action: A) -> Result<T, E>
+    where
+        A: FnOnce(Option<T>) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        let input = self.may_load(store)?;
+        let output = action(input)?;
+        self.save(store, &output)?;
+        Ok(output)
+    }
+
+    /// Like `update`, but if no data is stored yet, `action` is called with `default` instead
+    /// of returning an error. Useful for accumulators that start at zero.
+    pub fn update_or<A, E>(&self, store: &mut dyn Storage, default: T, action: A) -> Result<T, E>
+    where
+        A: FnOnce(T) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        let input = self.may_load(store)?.unwrap_or(default);
+        let output = action(input)?;
+        self.save(store, &output)?;
+        Ok(output)
+    }
+
+    /// Starts a scope in which this item can be changed speculatively. The returned guard captures
+    /// the current value and restores it when dropped, unless [`ItemGuard::commit`] was called.
+    ///
+    /// The guard holds on to the store for its whole lifetime. Other data can still be accessed
+    /// through [`ItemGuard::store`], but only the changes to this item are rolled back.
+    ///
+    /// Example:
+    ///
+    /// ```rust
+    /// use cosmwasm_std::testing::MockStorage;
+    /// use cw_storage_plus::Item;
+    ///
+    /// const COUNTER: Item<u32> = Item::new("counter");
+    ///
+    /// let mut store = MockStorage::new();
+    /// COUNTER.save(&mut store, &1).unwrap();
+    ///
+    /// {
+    ///     let mut guard = COUNTER.scoped(&mut store);
+    ///     guard.save(&5).unwrap();
+    ///     assert_eq!(guard.load().unwrap(), 5);
+    ///     // dropped without commit
+    /// }
+    /// assert_eq!(COUNTER.load(&store).unwrap(), 1);
+    ///
+    /// let mut guard = COUNTER.scoped(&mut store);
+    /// guard.save(&5).unwrap();
+    /// guard.commit();
+    /// assert_eq!(COUNTER.load(&store).unwrap(), 5);
+    /// ```
+    pub fn scoped<'s>(&self, store: &'s mut dyn Storage) -> ItemGuard<'s, T> {
+        let original = store.get(self.storage_key.as_slice());
+        ItemGuard {
+            item: Item {
+                storage_key: self.storage_key.clone(),
+                data_type: PhantomData,
+            },
+            store,
+            original,
+            committed: false,
+        }
+    }
+
     /// If you import the proper Item from the remote contract, this will let you read the data
     /// from a remote contract in a type-safe way using WasmQuery::RawQuery.
     ///
@@ -111,6 +239,89 @@ where
         };
         querier.query(&request.into())
     }
+
+    /// Like [`Item::query`], but returns `Ok(None)` if the item is not set on the remote contract,
+    /// rather than an error.
+    pub fn query_may<Q: CustomQuery>(
+        &self,
+        querier: &QuerierWrapper<Q>,
+        remote_contract: Addr,
+    ) -> StdResult<Option<T>> {
+        let result = query_raw(querier, remote_contract, self.storage_key.as_slice().into())?;
+        if result.is_empty() {
+            Ok(None)
+        } else {
+            from_json(&result).map(Some)
+        }
+    }
+}
+
+/// Savepoint for a single [`Item`], created by [`Item::scoped`].
+/// If it is dropped without being committed, the item is restored to the value it had when
+/// the guard was created (or removed, if it did not exist then).
+pub struct ItemGuard<'s, T> {
+    item: Item<T>,
+    store: &'s mut dyn Storage,
+    // raw value at creation time, restored on rollback
+    original: Option<Vec<u8>>,
+    committed: bool,
+}
+
+impl<'s, T> ItemGuard<'s, T>
+where
+    T: Serialize + DeserializeOwned,
+{
+    pub fn save(&mut self, data: &T) -> StdResult<()> {
+        self.item.save(self.store, data)
+    }
+
+    pub fn remove(&mut self) {
+        self.item.remove(self.store)
+    }
+
+    pub fn load(&self) -> StdResult<T> {
+        self.item.load(self.store)
+    }
+
+    pub fn may_load(&self) -> StdResult<Option<T>> {
+        self.item.may_load(self.store)
+    }
+
+    pub fn update<A, E>(&mut self, action: A) -> Result<T, E>
+    where
+        A: FnOnce(T) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        self.item.update(self.store, action)
+    }
+
+    /// Gives access to the underlying store while the guard is alive.
+    /// Changes to anything but the guarded item are not rolled back.
+    pub fn store(&mut self) -> &mut dyn Storage {
+        self.store
+    }
+
+    /// Keeps the changes made through this guard.
+    pub fn commit(mut self) {
+        self.committed = true;
+    }
+
+    /// Restores the original value right away. This is what happens when the guard is dropped
+    /// without being committed.
+    pub fn rollback(self) {}
+}
+
+impl<'s, T> Drop for ItemGuard<'s, T> {
+    fn drop(&mut self) {
+        if self.committed {
+            return;
+        }
+        let key = self.item.storage_key.as_slice();
+        match &self.original {
+            Some(value) => self.store.set(key, value),
+            None => self.store.remove(key),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -130,6 +341,22 @@ mod test {
     // note const constructor rather than 2 funcs with Singleton
     const CONFIG: Item<Config> = Item::new("config");
 
+    #[test]
+    fn raw_key_works() {
+        assert_eq!(CONFIG.raw_key(), b"config".to_vec());
+
+        let mut store = MockStorage::new();
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        CONFIG.save(&mut store, &cfg).unwrap();
+        assert_eq!(
+            store.get(&CONFIG.raw_key()),
+            Some(to_json_vec(&cfg).unwrap())
+        );
+    }
+
     #[test]
     fn save_and_load() {
         let mut store = MockStorage::new();
@@ -182,6 +409,119 @@ mod test {
         OPTIONAL.save(&mut store, &None).unwrap();
 
         assert!(OPTIONAL.exists(&store));
+        assert!(OPTIONAL.has(&store));
+    }
+
+    #[test]
+    fn value_len_works() {
+        let mut store = MockStorage::new();
+
+        assert_eq!(CONFIG.value_len(&store), None);
+
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        CONFIG.save(&mut store, &cfg).unwrap();
+
+        assert_eq!(
+            CONFIG.value_len(&store),
+            Some(to_json_vec(&cfg).unwrap().len())
+        );
+
+        CONFIG.remove(&mut store);
+        assert_eq!(CONFIG.value_len(&store), None);
+    }
+
+    #[test]
+    #[cfg(feature = "load-into")]
+    fn load_into_works() {
+        let mut store = MockStorage::new();
+
+        let mut out = Config {
+            owner: String::new(),
+            max_tokens: 0,
+        };
+        let err = CONFIG.load_into(&store, &mut out).unwrap_err();
+        assert!(matches!(err, StdError::NotFound { .. }));
+
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        CONFIG.save(&mut store, &cfg).unwrap();
+        CONFIG.load_into(&store, &mut out).unwrap();
+        assert_eq!(out, cfg);
+
+        // the buffer of a vec is reused
+        const AMOUNTS: Item<Vec<u64>> = Item::new("amounts");
+        AMOUNTS.save(&mut store, &vec![1, 2, 3]).unwrap();
+        let mut buf = Vec::with_capacity(100);
+        buf.push(7);
+        let ptr = buf.as_ptr();
+        AMOUNTS.load_into(&store, &mut buf).unwrap();
+        assert_eq!(buf, vec![1, 2, 3]);
+        assert_eq!(buf.as_ptr(), ptr);
+
+        // parse errors are reported like for load
+        store.set(b"amounts", b"[1, 2, \"x\"]");
+        let err = AMOUNTS.load_into(&store, &mut buf).unwrap_err();
+        assert!(matches!(err, StdError::ParseErr { .. }));
+        store.set(b"amounts", b"[1] trailing");
+        let err = AMOUNTS.load_into(&store, &mut buf).unwrap_err();
+        assert!(matches!(err, StdError::ParseErr { .. }));
+    }
+
+    #[test]
+    fn scoped_rolls_back_unless_committed() {
+        let mut store = MockStorage::new();
+        const OTHER: Item<u32> = Item::new("other");
+
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        CONFIG.save(&mut store, &cfg).unwrap();
+
+        // dropped without commit restores the original value, but not other data
+        {
+            let mut guard = CONFIG.scoped(&mut store);
+            guard
+                .update(|mut c| -> StdResult<_> {
+                    c.max_tokens = 1;
+                    Ok(c)
+                })
+                .unwrap();
+            assert_eq!(guard.load().unwrap().max_tokens, 1);
+            OTHER.save(guard.store(), &7).unwrap();
+        }
+        assert_eq!(CONFIG.load(&store).unwrap(), cfg);
+        assert_eq!(OTHER.load(&store).unwrap(), 7);
+
+        // removal is rolled back as well
+        let mut guard = CONFIG.scoped(&mut store);
+        guard.remove();
+        assert_eq!(guard.may_load().unwrap(), None);
+        guard.rollback();
+        assert_eq!(CONFIG.load(&store).unwrap(), cfg);
+
+        // committed changes are kept
+        let mut guard = CONFIG.scoped(&mut store);
+        guard
+            .save(&Config {
+                owner: "other".to_string(),
+                max_tokens: 5,
+            })
+            .unwrap();
+        guard.commit();
+        assert_eq!(CONFIG.load(&store).unwrap().max_tokens, 5);
+
+        // an item that did not exist is removed again
+        const MISSING: Item<u32> = Item::new("missing");
+        let mut guard = MISSING.scoped(&mut store);
+        guard.save(&1).unwrap();
+        drop(guard);
+        assert!(!MISSING.exists(&store));
     }
 
     #[test]
@@ -244,6 +584,107 @@ mod test {
         assert_eq!(CONFIG.load(&store).unwrap(), expected);
     }
 
+    #[test]
+    fn load_or_works() {
+        let mut store = MockStorage::new();
+        let default = || Config {
+            owner: "nobody".to_string(),
+            max_tokens: 0,
+        };
+
+        assert_eq!(CONFIG.load_or(&store, default).unwrap(), default());
+        // nothing was written
+        assert!(!CONFIG.exists(&store));
+
+        let cfg = Config {
+            owner: "admin".to_string(),
+            max_tokens: 1234,
+        };
+        CONFIG.save(&mut store, &cfg).unwrap();
+        assert_eq!(CONFIG.load_or(&store, default).unwrap(), cfg);
+    }
+
+    #[test]
+    fn load_or_default_works() {
+        const FEE: Item<u64> = Item::new("fee");
+        let mut store = MockStorage::new();
+
+        assert_eq!(FEE.load_or_default(&store).unwrap(), 0);
+        assert!(!FEE.exists(&store));
+
+        FEE.save(&mut store, &25).unwrap();
+        assert_eq!(FEE.load_or_default(&store).unwrap(), 25);
+    }
+
+    #[test]
+    fn swap_works() {
+        const FEE: Item<u64> = Item::new("fee");
+        let mut store = MockStorage::new();
+
+        assert_eq!(FEE.swap(&mut store, &25).unwrap(), None);
+        assert_eq!(FEE.load(&store).unwrap(), 25);
+
+        assert_eq!(FEE.swap(&mut store, &30).unwrap(), Some(25));
+        assert_eq!(FEE.load(&store).unwrap(), 30);
+
+        // a corrupt old value is an error and nothing is written
+        store.set(b"fee", b"not json");
+        assert!(FEE.swap(&mut store, &35).is_err());
+        assert_eq!(store.get(b"fee").unwrap(), b"not json");
+    }
+
+    #[test]
+    fn may_update_works() {
+        const COUNT: Item<u32> = Item::new("count");
+        let mut store = MockStorage::new();
+
+        let increment = |c: Option<u32>| -> StdResult<_> { Ok(c.map_or(1, |c| c + 1)) };
+        assert_eq!(COUNT.may_update(&mut store, increment).unwrap(), 1);
+        assert_eq!(COUNT.may_update(&mut store, increment).unwrap(), 2);
+        assert_eq!(COUNT.load(&store).unwrap(), 2);
+
+        // errors leave the data untouched
+        let err = COUNT
+            .may_update(&mut store, |_| -> StdResult<u32> {
+                Err(StdError::generic_err("nope"))
+            })
+            .unwrap_err();
+        assert_eq!(err, StdError::generic_err("nope"));
+        assert_eq!(COUNT.load(&store).unwrap(), 2);
+
+        COUNT.remove(&mut store);
+        let result = COUNT.may_update(&mut store, |c| -> StdResult<_> {
+            assert_eq!(c, None);
+            Ok(7)
+        });
+        assert_eq!(result.unwrap(), 7);
+    }
+
+    #[test]
+    fn update_or_works() {
+        const SUPPLY: Item<u128> = Item::new("supply");
+        let mut store = MockStorage::new();
+
+        // first write uses the default
+        let mint = |amount: u128| move |s: u128| -> StdResult<_> { Ok(s + amount) };
+        assert_eq!(SUPPLY.update_or(&mut store, 0, mint(100)).unwrap(), 100);
+        // later ones the stored value
+        assert_eq!(SUPPLY.update_or(&mut store, 0, mint(50)).unwrap(), 150);
+        assert_eq!(SUPPLY.update_or(&mut store, 1000, mint(1)).unwrap(), 151);
+        assert_eq!(SUPPLY.load(&store).unwrap(), 151);
+
+        // errors are propagated and nothing is written
+        SUPPLY.remove(&mut store);
+        let err = SUPPLY
+            .update_or(&mut store, 0, |s| {
+                s.checked_sub(1)
+                    .ok_or_else(|| StdError::overflow(OverflowError::new(OverflowOperation::Sub)))
+            })
+            .unwrap_err();
+        assert!(matches!(err, StdError::Overflow { .. }));
+        assert!(!SUPPLY.exists(&store));
+    }
+
     #[test]
     fn update_can_change_variable_from_outer_scope() {
         let mut store = MockStorage::new();
@@ -373,4 +814,45 @@ mod test {
 
         Ok(())
     }
+
+    #[test]
+    fn query_may_works() {
+        use cosmwasm_std::testing::MockQuerier;
+        use cosmwasm_std::{
+            Binary, ContractResult, Empty, QuerierWrapper, SystemResult, WasmQuery,
+        };
+
+        let mut querier = MockQuerier::<Empty>::new(&[]);
+        querier.update_wasm(|query| match query {
+            WasmQuery::Raw { contract_addr, key } => {
+                let value = match (contract_addr.as_str(), key.as_slice()) {
+                    ("set", b"config") => to_json_vec(&Config {
+                        owner: "admin".to_string(),
+                        max_tokens: 1234,
+                    })
+                    .unwrap(),
+                    _ => vec![],
+                };
+                SystemResult::Ok(ContractResult::Ok(Binary::from(value)))
+            }
+            _ => panic!("unexpected query"),
+        });
+        let querier = QuerierWrapper::<Empty>::new(&querier);
+
+        assert_eq!(
+            CONFIG.query_may(&querier, Addr::unchecked("set")).unwrap(),
+            Some(Config {
+                owner: "admin".to_string(),
+                max_tokens: 1234,
+            })
+        );
+        assert_eq!(
+            CONFIG
+                .query_may(&querier, Addr::unchecked("unset"))
+                .unwrap(),
+            None
+        );
+        // unlike query_may, query requires the item to be set
+        assert!(CONFIG.query(&querier, Addr::unchecked("unset")).is_err());
+    }
 }
@@ -1,5 +1,7 @@
-use cosmwasm_std::{storage_keys::namespace_with_key, Addr};
-use cosmwasm_std::{Int128, Int64, Uint128, Uint64};
+use std::borrow::Cow;
+
+use cosmwasm_std::{storage_keys::namespace_with_key, Addr, Binary};
+use cosmwasm_std::{Decimal, Int128, Int256, Int64, Uint128, Uint256, Uint64};
 
 use crate::de::KeyDeserialize;
 use crate::int_key::IntKey;
@@ -12,6 +14,8 @@ pub enum Key<'a> {
     Val32([u8; 4]),
     Val64([u8; 8]),
     Val128([u8; 16]),
+    Val256([u8; 32]),
+    Owned(Vec<u8>),
 }
 
 impl<'a> AsRef<[u8]> for Key<'a> {
@@ -23,6 +27,8 @@ impl<'a> AsRef<[u8]> for Key<'a> {
             Key::Val32(v) => v,
             Key::Val64(v) => v,
             Key::Val128(v) => v,
+            Key::Val256(v) => v,
+            Key::Owned(v) => v,
         }
     }
 }
@@ -50,8 +56,8 @@ impl<'a> PartialEq<&[u8]> for Key<'a> {
 /// `()`: Sub-prefix.
 /// `(T, U)`: Super-suffix.
 ///
-/// `SubPrefix` and `SuperSuffix` only make real sense in the case of triples. Still, they need to be
-/// consistently defined for all types.
+/// `SubPrefix` and `SuperSuffix` only make real sense in the case of triples and 4-tuples. Still,
+/// they need to be consistently defined for all types.
 pub trait PrimaryKey<'a>: Clone {
     /// These associated types need to implement `Prefixer`, so that they can be useful arguments
     /// for `prefix()`, `sub_prefix()`, and their key-deserializable variants.
@@ -66,9 +72,19 @@ pub trait PrimaryKey<'a>: Clone {
     /// returns a slice of key steps, which can be optionally combined
     fn key(&self) -> Vec<Key>;
 
+    /// Returns the storage representation of the key, with all elements but the last
+    /// length-prefixed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any element but the last is longer than `0xFFFF` bytes, as its length
+    /// does not fit the 2-byte prefix. Maps and indexes build their storage keys the same way.
     fn joined_key(&self) -> Vec<u8> {
         let keys = self.key();
         let l = keys.len();
+        if l == 0 {
+            return vec![];
+        }
         namespace_with_key(
             &keys[0..l - 1].iter().map(Key::as_ref).collect::<Vec<_>>(),
             keys[l - 1].as_ref(),
@@ -81,7 +97,9 @@ pub trait PrimaryKey<'a>: Clone {
     }
 }
 
-// Empty / no primary key
+// Empty / no primary key.
+// A `Map<(), T>` holds at most one entry, stored right at the (length-prefixed) map namespace.
+// This is mostly useful for code that is generic over the key type.
 impl<'a> PrimaryKey<'a> for () {
     type Prefix = Self;
     type SubPrefix = Self;
@@ -182,6 +200,29 @@ impl<
     }
 }
 
+// use generics for combining there - so we can use &[u8], Vec<u8>, or IntKey
+impl<
+        'a,
+        T: PrimaryKey<'a> + Prefixer<'a>,
+        U: PrimaryKey<'a> + Prefixer<'a>,
+        V: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
+        W: PrimaryKey<'a> + KeyDeserialize,
+    > PrimaryKey<'a> for (T, U, V, W)
+{
+    type Prefix = (T, U, V);
+    type SubPrefix = (T, U);
+    type Suffix = W;
+    type SuperSuffix = (V, W);
+
+    fn key(&self) -> Vec<Key> {
+        let mut keys = self.0.key();
+        keys.extend(self.1.key());
+        keys.extend(self.2.key());
+        keys.extend(self.3.key());
+        keys
+    }
+}
+
 pub trait Prefixer<'a> {
     /// returns 0 or more namespaces that should be length-prefixed and concatenated for range searches
     fn prefix(&self) -> Vec<Key>;
@@ -204,6 +245,12 @@ impl<'a> Prefixer<'a> for &'a [u8] {
     }
 }
 
+impl<'a, const N: usize> Prefixer<'a> for [u8; N] {
+    fn prefix(&self) -> Vec<Key> {
+        vec![Key::Ref(self.as_slice())]
+    }
+}
+
 impl<'a, T: Prefixer<'a>, U: Prefixer<'a>> Prefixer<'a> for (T, U) {
     fn prefix(&self) -> Vec<Key> {
         let mut res = self.0.prefix();
@@ -221,6 +268,18 @@ impl<'a, T: Prefixer<'a>, U: Prefixer<'a>, V: Prefixer<'a>> Prefixer<'a> for (T,
     }
 }
 
+impl<'a, T: Prefixer<'a>, U: Prefixer<'a>, V: Prefixer<'a>, W: Prefixer<'a>> Prefixer<'a>
+    for (T, U, V, W)
+{
+    fn prefix(&self) -> Vec<Key> {
+        let mut res = self.0.prefix();
+        res.extend(self.1.prefix());
+        res.extend(self.2.prefix());
+        res.extend(self.3.prefix());
+        res
+    }
+}
+
 impl<'a, T> Prefixer<'a> for &'a T
 where
     T: Prefixer<'a>,
@@ -254,6 +313,24 @@ impl<'a> Prefixer<'a> for Vec<u8> {
     }
 }
 
+/// `Binary` keys are stored as their raw bytes, just like `Vec<u8>`.
+impl<'a> PrimaryKey<'a> for Binary {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key> {
+        vec![Key::Ref(self.as_slice())]
+    }
+}
+
+impl<'a> Prefixer<'a> for Binary {
+    fn prefix(&self) -> Vec<Key> {
+        vec![Key::Ref(self.as_slice())]
+    }
+}
+
 impl<'a> PrimaryKey<'a> for String {
     type Prefix = ();
     type SubPrefix = ();
@@ -271,6 +348,40 @@ impl<'a> Prefixer<'a> for String {
     }
 }
 
+impl<'a> PrimaryKey<'a> for Cow<'a, str> {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key> {
+        vec![Key::Ref(self.as_bytes())]
+    }
+}
+
+impl<'a> Prefixer<'a> for Cow<'a, str> {
+    fn prefix(&self) -> Vec<Key> {
+        vec![Key::Ref(self.as_bytes())]
+    }
+}
+
+impl<'a> PrimaryKey<'a> for Cow<'a, [u8]> {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key> {
+        vec![Key::Ref(self)]
+    }
+}
+
+impl<'a> Prefixer<'a> for Cow<'a, [u8]> {
+    fn prefix(&self) -> Vec<Key> {
+        vec![Key::Ref(self)]
+    }
+}
+
 /// owned variant.
 impl<'a> PrimaryKey<'a> for Addr {
     type Prefix = ();
@@ -290,6 +401,59 @@ impl<'a> Prefixer<'a> for Addr {
     }
 }
 
+impl<'a> PrimaryKey<'a> for bool {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key> {
+        vec![Key::Val8([*self as u8])]
+    }
+}
+
+impl<'a> Prefixer<'a> for bool {
+    fn prefix(&self) -> Vec<Key> {
+        vec![Key::Val8([*self as u8])]
+    }
+}
+
+impl<'a> PrimaryKey<'a> for char {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key> {
+        vec![Key::Val32((*self as u32).to_be_bytes())]
+    }
+}
+
+impl<'a> Prefixer<'a> for char {
+    fn prefix(&self) -> Vec<Key> {
+        vec![Key::Val32((*self as u32).to_be_bytes())]
+    }
+}
+
+/// Decimals are stored as their atomics (a `u128` with 18 fractional digits), big-endian,
+/// so they sort numerically. As the scale is fixed, values like `0.9` and `10` compare properly.
+impl<'a> PrimaryKey<'a> for Decimal {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key> {
+        vec![Key::Val128(self.atomics().u128().to_be_bytes())]
+    }
+}
+
+impl<'a> Prefixer<'a> for Decimal {
+    fn prefix(&self) -> Vec<Key> {
+        vec![Key::Val128(self.atomics().u128().to_be_bytes())]
+    }
+}
+
 macro_rules! integer_key {
     (for $($t:ty, $v:tt),+) => {
         $(impl<'a> PrimaryKey<'a> for $t {
@@ -305,7 +469,7 @@ macro_rules! integer_key {
     }
 }
 
-integer_key!(for i8, Val8, u8, Val8, i16, Val16, u16, Val16, i32, Val32, u32, Val32, i64, Val64, u64, Val64, i128, Val128, u128, Val128, Uint64, Val64, Uint128, Val128, Int64, Val64, Int128, Val128);
+integer_key!(for i8, Val8, u8, Val8, i16, Val16, u16, Val16, i32, Val32, u32, Val32, i64, Val64, u64, Val64, i128, Val128, u128, Val128, Uint64, Val64, Uint128, Val128, Int64, Val64, Int128, Val128, Uint256, Val256, Int256, Val256);
 macro_rules! integer_prefix {
     (for $($t:ty, $v:tt),+) => {
         $(impl<'a> Prefixer<'a> for $t {
@@ -316,11 +480,40 @@ macro_rules! integer_prefix {
     }
 }
 
-integer_prefix!(for i8, Val8, u8, Val8, i16, Val16, u16, Val16, i32, Val32, u32, Val32, i64, Val64, u64, Val64, i128, Val128, u128, Val128, Uint64, Val64, Uint128, Val128, Int64, Val64, Int128, Val128);
+integer_prefix!(for i8, Val8, u8, Val8, i16, Val16, u16, Val16, i32, Val32, u32, Val32, i64, Val64, u64, Val64, i128, Val128, u128, Val128, Uint64, Val64, Uint128, Val128, Int64, Val64, Int128, Val128, Uint256, Val256, Int256, Val256);
+
+/// Optional keys are stored as a single element: a `0` byte for `None`, or a `1` byte followed
+/// by the joined inner key for `Some`. So `None` sorts before any `Some`, e.g. `(addr, None)`
+/// comes before `(addr, Some(0))`, and `Some` values keep the order of their inner keys.
+/// As with other variable length keys, an optional key that is not the last element of a
+/// composite key is length-prefixed, so there shorter keys sort first.
+impl<'a, K: PrimaryKey<'a> + KeyDeserialize> PrimaryKey<'a> for Option<K> {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key> {
+        match self {
+            Some(k) => {
+                let mut bytes = vec![1];
+                bytes.extend(k.joined_key());
+                vec![Key::Owned(bytes)]
+            }
+            None => vec![Key::Val8([0])],
+        }
+    }
+}
+
+impl<'a, K: PrimaryKey<'a> + KeyDeserialize> Prefixer<'a> for Option<K> {
+    fn prefix(&self) -> Vec<Key> {
+        self.key()
+    }
+}
 
 #[cfg(test)]
 mod test {
-    use cosmwasm_std::{Uint256, Uint512};
+    use cosmwasm_std::Uint512;
 
     use super::*;
 
@@ -421,6 +614,71 @@ mod test {
         assert_eq!((-4242i128).to_cw_bytes(), path[0].as_ref());
     }
 
+    #[test]
+    fn std_256_keys_work() {
+        let k: Uint256 = Uint256::from(4242u128);
+        let path = k.key();
+        assert_eq!(1, path.len());
+        assert_eq!(k.to_be_bytes(), path[0].as_ref());
+
+        let k: Int256 = Int256::from(-4242i128);
+        let path = k.key();
+        assert_eq!(1, path.len());
+        assert_eq!(k.to_cw_bytes(), path[0].as_ref());
+        assert_eq!(Int256::zero().joined_key()[0], 0x80);
+    }
+
+    #[test]
+    fn bool_key_works() {
+        let path = false.key();
+        assert_eq!(1, path.len());
+        assert_eq!([0u8], path[0].as_ref());
+        assert_eq!(true.joined_key(), vec![1u8]);
+
+        let k: (bool, u8) = (true, 7);
+        assert_eq!(k.joined_key(), vec![0, 1, 1, 7]);
+    }
+
+    #[test]
+    fn char_key_works() {
+        let path = 'a'.key();
+        assert_eq!(1, path.len());
+        assert_eq!(97u32.to_be_bytes(), path[0].as_ref());
+        assert_eq!('€'.joined_key(), 0x20ACu32.to_be_bytes().to_vec());
+    }
+
+    #[test]
+    fn decimal_key_works() {
+        let k = Decimal::percent(150);
+        let path = k.key();
+        assert_eq!(1, path.len());
+        assert_eq!(
+            1_500_000_000_000_000_000u128.to_be_bytes(),
+            path[0].as_ref()
+        );
+        assert!(Decimal::percent(90).joined_key() < Decimal::percent(100).joined_key());
+        assert!(Decimal::one().joined_key() < Decimal::percent(1000).joined_key());
+    }
+
+    #[test]
+    fn option_key_works() {
+        assert_eq!(None::<u64>.joined_key(), vec![0]);
+        assert_eq!(
+            Some(42u64).joined_key(),
+            [&[1], &42u64.to_be_bytes()[..]].concat()
+        );
+        assert_eq!(Some("abc").joined_key(), b"\x01abc".to_vec());
+        assert_eq!(Some(("a", 1u8)).joined_key(), vec![1, 0, 1, b'a', 1]);
+        assert_eq!(Some(42u64).key().len(), 1);
+
+        // None sorts before any value, also as the last element of a composite key
+        assert!(None::<u32>.joined_key() < Some(0u32).joined_key());
+        assert!(None::<&str>.joined_key() < Some("").joined_key());
+        assert!(("addr", None::<u32>).joined_key() < ("addr", Some(0u32)).joined_key());
+        assert!((None::<u32>, "addr").joined_key() < (Some(0u32), "addr").joined_key());
+        assert!(("addr", Some(1u32)).joined_key() < ("addr", Some(2u32)).joined_key());
+    }
+
     #[test]
     fn str_key_works() {
         type K<'a> = &'a str;
@@ -447,6 +705,23 @@ mod test {
         assert_eq!(joined, b"hello")
     }
 
+    #[test]
+    fn cow_key_works() {
+        let borrowed: Cow<str> = Cow::Borrowed("hello");
+        let owned: Cow<str> = Cow::Owned("hello".to_string());
+        assert_eq!(borrowed.joined_key(), b"hello");
+        assert_eq!(owned.joined_key(), "hello".joined_key());
+
+        let k: Cow<[u8]> = Cow::Borrowed(b"world");
+        let path = k.key();
+        assert_eq!(1, path.len());
+        assert_eq!(b"world", path[0].as_ref());
+
+        // same layout as the borrowed equivalents in composite keys
+        let k: (Cow<str>, Cow<[u8]>) = (borrowed, Cow::Owned(b"world".to_vec()));
+        assert_eq!(k.joined_key(), ("hello", b"world".as_slice()).joined_key());
+    }
+
     #[test]
     fn fixed_size_bytes_key_works() {
         type K = [u8; 32];
@@ -490,6 +765,30 @@ mod test {
         assert_eq!(path, vec!["foo".as_bytes(), b"bar"],);
     }
 
+    #[test]
+    #[should_panic(expected = "only supports namespace components up to length 0xFFFF")]
+    fn oversized_key_element_panics() {
+        let k: (Vec<u8>, u8) = (vec![1; 70000], 2);
+        k.joined_key();
+    }
+
+    #[test]
+    #[should_panic(expected = "only supports namespace components up to length 0xFFFF")]
+    fn oversized_key_element_panics_on_save() {
+        let map: crate::Map<(Vec<u8>, u8), u8> = crate::Map::new("map");
+        let mut store = cosmwasm_std::testing::MockStorage::new();
+        map.save(&mut store, (vec![1; 70000], 2), &3).unwrap();
+    }
+
+    #[test]
+    fn oversized_last_key_element_works() {
+        // the last element is not length-prefixed, so it has no size limit
+        let k: (u8, Vec<u8>) = (2, vec![1; 70000]);
+        let joined = k.joined_key();
+        assert_eq!(joined.len(), 2 + 1 + 70000);
+        assert_eq!(<(u8, Vec<u8>)>::from_vec(joined).unwrap(), k);
+    }
+
     #[test]
     fn naked_composite_int_key() {
         let k: (u32, u64) = (123, 87654);
@@ -501,6 +800,31 @@ mod test {
         assert_eq!(path[1].as_ref(), 87654u64.to_cw_bytes());
     }
 
+    #[test]
+    fn quadruple_key_works() {
+        let k: (&str, u64, u8, &str) = ("owner", 42, 7, "denom");
+        let path = k.key();
+        assert_eq!(4, path.len());
+        assert_eq!(
+            path,
+            vec![
+                b"owner".as_slice(),
+                42u64.to_cw_bytes().as_slice(),
+                &[7],
+                b"denom"
+            ]
+        );
+        assert_eq!(
+            k.joined_key(),
+            ("owner", 42u64, 7u8)
+                .joined_prefix()
+                .into_iter()
+                .chain(*b"denom")
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(k.prefix().len(), 4);
+    }
+
     #[test]
     fn nested_composite_keys() {
         // use this to ensure proper type-casts below
@@ -13,11 +13,15 @@ For more information on this package, please check out the
 [README](https://github.com/CosmWasm/cw-plus/blob/main/packages/storage-plus/README.md).
 */
 
+mod bimap;
 mod bound;
+mod counter;
 mod de;
 mod deque;
 mod endian;
+mod expiring_map;
 mod helpers;
+mod identified_map;
 mod indexed_map;
 mod indexed_snapshot;
 mod indexes;
@@ -26,28 +30,42 @@ mod item;
 mod iter_helpers;
 mod keys;
 mod map;
+#[cfg(feature = "metrics")]
+mod metrics;
 mod namespace;
+mod observed_map;
+mod overlay;
+mod padded_string;
 mod path;
 mod prefix;
 mod snapshot;
 
+pub use bimap::BiMap;
 #[cfg(feature = "iterator")]
 pub use bound::{Bound, Bounder, PrefixBound, RawBound};
+pub use counter::Counter;
 pub use de::KeyDeserialize;
 pub use deque::Deque;
 pub use deque::DequeIter;
 pub use endian::Endian;
+pub use expiring_map::{Expiring, ExpiringMap};
+pub use identified_map::{HasPrimaryKey, IdentifiedMap};
 #[cfg(feature = "iterator")]
 pub use indexed_map::{IndexList, IndexedMap};
 #[cfg(feature = "iterator")]
 pub use indexed_snapshot::IndexedSnapshotMap;
 #[cfg(feature = "iterator")]
-pub use indexes::{Index, IndexPrefix, MultiIndex, UniqueIndex};
+pub use indexes::{Index, IndexError, IndexPrefix, MultiIndex, UniqueIndex};
 pub use int_key::IntKey;
-pub use item::Item;
+pub use item::{Item, ItemGuard};
 pub use keys::{Key, Prefixer, PrimaryKey};
 pub use map::Map;
+#[cfg(feature = "metrics")]
+pub use metrics::{MeteredStorage, StorageMetrics};
 pub use namespace::Namespace;
+pub use observed_map::{ObservedMap, OnChange};
+pub use overlay::OverlayStorage;
+pub use padded_string::PaddedString;
 pub use path::Path;
 #[cfg(feature = "iterator")]
 pub use prefix::{range_with_prefix, Prefix};
@@ -61,6 +79,10 @@ extern crate cw_storage_macro;
 #[cfg(all(feature = "iterator", feature = "macro"))]
 /// Auto generate an `IndexList` impl for your indexes struct.
 ///
+/// The struct may have any lifetimes and type parameters, and the stored type given to the
+/// attribute may be one of them. In that case, state the bounds `Index` requires on it
+/// (`Serialize + DeserializeOwned + Clone`) in the struct's where clause.
+///
 /// # Example
 ///
 /// ```rust
@@ -83,3 +105,58 @@ extern crate cw_storage_macro;
 /// ```
 ///
 pub use cw_storage_macro::index_list;
+
+#[cfg(feature = "macro")]
+/// Derive `PrimaryKey`, `Prefixer` and `KeyDeserialize` for a struct, so it can be used as a key
+/// (or part of one) like a tuple of its fields.
+///
+/// The fields are stored in declaration order, each one as a length-prefixed element
+/// except the last, exactly as the corresponding tuple would be. All fields must be keys
+/// deserializing to their own type, so use `String` rather than `&str`, for example.
+/// The struct's `Prefix` is `()`, so a map keyed by it cannot be iterated by a prefix of its fields,
+/// but the struct itself can be a prefix when used in a tuple.
+/// References work as keys too, e.g. `Map<&Allowance, u128>`, like `&Addr` does.
+///
+/// # Example
+///
+/// ```rust
+/// use cosmwasm_std::Addr;
+/// use cw_storage_plus::{CompositeKey, Map};
+///
+/// #[derive(CompositeKey, Clone, Debug, PartialEq)]
+/// struct Allowance {
+///     owner: Addr,
+///     spender: Addr,
+///     denom: String,
+/// }
+///
+/// const ALLOWANCES: Map<Allowance, u128> = Map::new("allowances");
+/// ```
+pub use cw_storage_macro::CompositeKey;
+
+#[cfg(feature = "macro")]
+/// Derive `PrimaryKey`, `Prefixer` and `KeyDeserialize` for an enum whose variants each wrap
+/// a single key, so it is stored like the tuple `(u8, inner)`.
+///
+/// The `u8` discriminant is the position of the variant in the enum, starting at `0`, so
+/// reordering or removing variants changes the storage layout. All wrapped types must take
+/// the same number of key elements and deserialize to their own type.
+/// The enum's `Prefix` is the discriminant, so the keys of a single variant can be iterated
+/// with `prefix_range`, while `prefix` yields the raw keys of the wrapped values.
+/// References work as keys too, e.g. `Map<&Denom, u128>`.
+///
+/// # Example
+///
+/// ```rust
+/// use cosmwasm_std::Addr;
+/// use cw_storage_plus::{CwKey, Map};
+///
+/// #[derive(CwKey, Clone, Debug, PartialEq)]
+/// enum Denom {
+///     Native(String),
+///     Cw20(Addr),
+/// }
+///
+/// const BALANCES: Map<Denom, u128> = Map::new("balances");
+/// ```
+pub use cw_storage_macro::CwKey;
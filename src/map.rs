@@ -1,9 +1,11 @@
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+#[cfg(feature = "iterator")]
+use std::collections::BTreeMap;
 use std::marker::PhantomData;
 
 #[cfg(feature = "iterator")]
-use crate::bound::{Bound, PrefixBound};
+use crate::bound::{Bound, Bounder, PrefixBound};
 #[cfg(feature = "iterator")]
 use crate::de::KeyDeserialize;
 use crate::helpers::query_raw;
@@ -15,11 +17,21 @@ use crate::keys::{Key, PrimaryKey};
 use crate::namespace::Namespace;
 use crate::path::Path;
 #[cfg(feature = "iterator")]
-use crate::prefix::{namespaced_prefix_range, Prefix};
+use crate::prefix::{distinct_prefixes, namespaced_prefix_range, range_with_prefix, Prefix};
+use cosmwasm_std::storage_keys::namespace_with_key;
+#[cfg(feature = "iterator")]
+use cosmwasm_std::storage_keys::to_length_prefixed_nested;
 #[cfg(feature = "iterator")]
 use cosmwasm_std::Order;
 use cosmwasm_std::{from_json, Addr, CustomQuery, QuerierWrapper, StdError, StdResult, Storage};
 
+#[cfg(feature = "iterator")]
+type Chunk<K, T> = Vec<(<K as KeyDeserialize>::Output, T)>;
+
+/// A page of entries together with the bound to continue after them
+#[cfg(feature = "iterator")]
+type RangePage<'a, K, T> = (Chunk<K, T>, Option<Bound<'a, K>>);
+
 #[derive(Debug, Clone)]
 pub struct Map<K, T> {
     namespace: Namespace,
@@ -66,6 +78,15 @@ where
         )
     }
 
+    /// Returns the full storage key of the entry at `k`, which can be used for raw queries
+    /// of this map, e.g. from off-chain indexers.
+    ///
+    /// The key is the length-prefixed namespace, followed by the elements of `k`, where
+    /// all but the last are length-prefixed as well. This layout is stable.
+    pub fn raw_key(&self, k: K) -> Vec<u8> {
+        self.key(k).storage_key
+    }
+
     #[cfg(feature = "iterator")]
     pub(crate) fn no_prefix_raw(&self) -> Prefix<Vec<u8>, T, K> {
         Prefix::new(self.namespace.as_slice(), &[])
@@ -79,17 +100,58 @@ where
         self.key(k).remove(store)
     }
 
+    /// Removes the data at the key and returns it, or `None` if there was none.
+    /// Nothing is removed if the stored data fails to parse.
+    pub fn take(&self, store: &mut dyn Storage, k: K) -> StdResult<Option<T>> {
+        let path = self.key(k);
+        let data = path.may_load(store)?;
+        if data.is_some() {
+            path.remove(store);
+        }
+        Ok(data)
+    }
+
     /// load will return an error if no data is set at the given key, or on parse error
     pub fn load(&self, store: &dyn Storage, k: K) -> StdResult<T> {
         self.key(k).load(store)
     }
 
+    /// load_into works like `load`, but deserializes into an existing value, reusing its
+    /// allocations where possible. See [`Path::load_into`].
+    #[cfg(feature = "load-into")]
+    pub fn load_into(&self, store: &dyn Storage, k: K, out: &mut T) -> StdResult<()> {
+        self.key(k).load_into(store, out)
+    }
+
     /// may_load will parse the data stored at the key if present, returns Ok(None) if no data there.
     /// returns an error on issues parsing
     pub fn may_load(&self, store: &dyn Storage, k: K) -> StdResult<Option<T>> {
         self.key(k).may_load(store)
     }
 
+    /// Works like `may_load` for each of the given keys, returning the results in the same order.
+    /// Stops at the first parse error.
+    pub fn may_load_many<const N: usize>(
+        &self,
+        store: &dyn Storage,
+        keys: [K; N],
+    ) -> StdResult<[Option<T>; N]> {
+        let mut values = std::array::from_fn(|_| None);
+        for (value, k) in values.iter_mut().zip(keys) {
+            *value = self.may_load(store, k)?;
+        }
+        Ok(values)
+    }
+
+    /// Like [`Map::may_load_many`], for any number of keys.
+    pub fn may_load_many_vec(
+        &self,
+        store: &dyn Storage,
+        keys: impl IntoIterator<Item = K>,
+    ) -> StdResult<Vec<Option<T>>> {
+        keys.into_iter().map(|k| self.may_load(store, k)).collect()
+    }
+
     /// has returns true or false if any data is at this key, without parsing or interpreting the
     /// contents.
     pub fn has(&self, store: &dyn Storage, k: K) -> bool {
@@ -100,6 +162,7 @@ where
     /// in the database. This is shorthand for some common sequences, which may be useful.
     ///
     /// If the data exists, `action(Some(value))` is called. Otherwise `action(None)` is called.
+    /// See [`Map::update_returning_old`] to also get the previous value.
     pub fn update<A, E>(&self, store: &mut dyn Storage, k: K, action: A) -> Result<T, E>
     where
         A: FnOnce(Option<T>) -> Result<T, E>,
@@ -108,6 +171,26 @@ where
         self.key(k).update(store, action)
     }
 
+    /// Works like `update`, but returns the value before the update along with the new one,
+    /// e.g. to emit both in an event.
+    pub fn update_returning_old<A, E>(
+        &self,
+        store: &mut dyn Storage,
+        k: K,
+        action: A,
+    ) -> Result<(Option<T>, T), E>
+    where
+        A: FnOnce(Option<T>) -> Result<T, E>,
+        E: From<StdError>,
+        T: Clone,
+    {
+        let path = self.key(k);
+        let input = path.may_load(store)?;
+        let output = action(input.clone())?;
+        path.save(store, &output)?;
+        Ok((input, output))
+    }
+
     /// If you import the proper Map from the remote contract, this will let you read the data
     /// from a remote contract in a type-safe way using WasmQuery::RawQuery
     pub fn query<Q: CustomQuery>(
@@ -125,10 +208,10 @@ where
         }
     }
 
-    /// Clears the map, removing all elements.
+    /// Clears the map, removing all elements. Returns how many elements were removed.
     #[cfg(feature = "iterator")]
-    pub fn clear(&self, store: &mut dyn Storage) {
-        self.no_prefix_raw().clear(store, None);
+    pub fn clear(&self, store: &mut dyn Storage) -> usize {
+        self.no_prefix_raw().clear(store, None)
     }
 
     /// Returns `true` if the map is empty.
@@ -136,6 +219,48 @@ where
     pub fn is_empty(&self, store: &dyn Storage) -> bool {
         self.no_prefix_raw().is_empty(store)
     }
+
+    /// Counts the entries within the given bounds, without deserializing keys or values.
+    /// Unlike `keys_raw(...).count()`, this does not copy every key to strip the namespace.
+    /// Use `prefix(...).count(...)` to count the entries under a prefix.
+    #[cfg(feature = "iterator")]
+    pub fn count(
+        &self,
+        store: &dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+    ) -> usize {
+        self.no_prefix_raw().count(store, min, max)
+    }
+
+    /// Returns all the entries of the map as raw `(key, value)` pairs, in ascending key order,
+    /// without deserializing them. Keys are relative to the map namespace, so the entries can be
+    /// written back with [`Map::import`], into this map or another one with the same key type.
+    #[cfg(feature = "iterator")]
+    pub fn export<'c>(
+        &self,
+        store: &'c dyn Storage,
+    ) -> Box<dyn Iterator<Item = cosmwasm_std::Record> + 'c> {
+        let namespace = namespace_with_key(&[self.namespace.as_slice()], &[]);
+        range_with_prefix(store, &namespace, None, None, Order::Ascending)
+    }
+
+    /// Writes raw `(key, value)` pairs, as returned by [`Map::export`], verbatim under this
+    /// map's namespace. Existing entries with the same keys are overwritten.
+    ///
+    /// Nothing is validated nor deserialized. Note that for an `IndexedMap` this only restores
+    /// the primary values, so the indexes have to be rebuilt afterwards.
+    pub fn import<I>(&self, store: &mut dyn Storage, entries: I)
+    where
+        I: IntoIterator<Item = (Vec<u8>, Vec<u8>)>,
+    {
+        for (key, value) in entries {
+            store.set(
+                &namespace_with_key(&[self.namespace.as_slice()], &key),
+                &value,
+            );
+        }
+    }
 }
 
 #[cfg(feature = "iterator")]
@@ -144,6 +269,11 @@ where
     T: Serialize + DeserializeOwned,
     K: PrimaryKey<'a>,
 {
+    /// Iterates over the entries sharing the given sub-prefix, i.e. all but the last two
+    /// elements of the key. For triples this fixes the first element.
+    ///
+    /// For 2-tuples (and simple keys) the sub-prefix is `()`, so `sub_prefix(())` ranges over
+    /// the whole map, yielding full keys. This is the same as [`Map::range`] or [`Map::iter_all`].
     pub fn sub_prefix(&self, p: K::SubPrefix) -> Prefix<K::SuperSuffix, T, K::SuperSuffix> {
         Prefix::new(self.namespace.as_slice(), &p.prefix())
     }
@@ -151,6 +281,12 @@ where
     pub fn prefix(&self, p: K::Prefix) -> Prefix<K::Suffix, T, K::Suffix> {
         Prefix::new(self.namespace.as_slice(), &p.prefix())
     }
+
+    /// Removes all elements under the given prefix, leaving the rest of the map intact.
+    /// Returns how many elements were removed.
+    pub fn clear_prefix(&self, store: &mut dyn Storage, p: K::Prefix) -> usize {
+        Prefix::<Vec<u8>, T, K>::new(self.namespace.as_slice(), &p.prefix()).clear(store, None)
+    }
 }
 
 // short-cut for simple keys, rather than .prefix(()).range_raw(...)
@@ -219,6 +355,44 @@ where
     }
 }
 
+#[cfg(feature = "iterator")]
+impl<'a, K, T> Map<K, T>
+where
+    T: Serialize + DeserializeOwned,
+    K: PrimaryKey<'a>,
+    K::Prefix: KeyDeserialize,
+{
+    /// Iterates over the distinct values of the `Prefix` of the key, e.g. every `Addr` of a
+    /// `Map<(Addr, u64), T>` exactly once. Values are never read, and after each prefix
+    /// the remaining keys sharing it are skipped by starting a new range after them,
+    /// so this costs one storage read per distinct prefix.
+    pub fn sub_keys<'c>(
+        &self,
+        store: &'c dyn Storage,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = StdResult<<K::Prefix as KeyDeserialize>::Output>> + 'c>
+    where
+        <K::Prefix as KeyDeserialize>::Output: 'static,
+    {
+        distinct_prefixes::<K::Prefix>(
+            store,
+            to_length_prefixed_nested(&[self.namespace.as_slice()]),
+            order,
+        )
+    }
+
+    /// Counts the distinct values of the `Prefix` of the key, e.g. how many `Addr`s have
+    /// entries in a `Map<(Addr, u64), T>`. Like `sub_keys`, this costs one storage read
+    /// per distinct prefix and never reads the values.
+    pub fn prefix_count(&self, store: &dyn Storage) -> StdResult<usize>
+    where
+        <K::Prefix as KeyDeserialize>::Output: 'static,
+    {
+        self.sub_keys(store, Order::Ascending)
+            .try_fold(0, |count, prefix| prefix.map(|_| count + 1))
+    }
+}
+
 #[cfg(feature = "iterator")]
 impl<'a, K, T> Map<K, T>
 where
@@ -250,6 +424,24 @@ where
     {
         self.no_prefix_raw().keys_raw(store, min, max, order)
     }
+
+    /// Iterates over the values in the given range, without deserializing the keys.
+    /// This works for any key type, even ones that don't implement `KeyDeserialize`.
+    pub fn values<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = StdResult<T>> + 'c>
+    where
+        T: 'c,
+    {
+        Box::new(
+            self.range_raw(store, min, max, order)
+                .map(|item| item.map(|(_, v)| v)),
+        )
+    }
 }
 
 #[cfg(feature = "iterator")]
@@ -272,6 +464,9 @@ where
         self.no_prefix().range(store, min, max, order)
     }
 
+    /// Iterates over the deserialized keys of the map, without the need to call `prefix` first.
+    /// Only the keys are deserialized, and no values are read: this goes through
+    /// `Storage::range_keys`, which doesn't fetch them from the chain.
     pub fn keys<'c>(
         &self,
         store: &'c dyn Storage,
@@ -286,6 +481,232 @@ where
         self.no_prefix().keys(store, min, max, order)
     }
 
+    /// Collects the keys in the given range into a `Vec`, stopping after `limit` keys if given.
+    /// This is shorthand for `keys(...).take(limit).collect()`, and never reads past the limit.
+    pub fn keys_vec(
+        &self,
+        store: &dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+        limit: Option<usize>,
+    ) -> StdResult<Vec<K::Output>>
+    where
+        K::Output: 'static,
+    {
+        let keys = self.keys(store, min, max, order);
+        match limit {
+            Some(limit) => keys.take(limit).collect(),
+            None => keys.collect(),
+        }
+    }
+
+    /// Collects the entries in the given range into `out`, stopping after `limit` entries if given.
+    /// `out` is cleared first, so its allocation can be reused across calls. On error, `out` holds
+    /// the entries read up to that point.
+    pub fn collect_into(
+        &self,
+        store: &dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+        limit: Option<usize>,
+        out: &mut Vec<(K::Output, T)>,
+    ) -> StdResult<()>
+    where
+        K::Output: 'static,
+    {
+        out.clear();
+        let items = self
+            .range(store, min, max, order)
+            .take(limit.unwrap_or(usize::MAX));
+        for item in items {
+            out.push(item?);
+        }
+        Ok(())
+    }
+
+    /// Returns all entries in the given range, or an error if there are more than `max_items`.
+    ///
+    /// Unlike taking a limited number of entries from `range`, this never returns partial data,
+    /// which is useful for queries where a truncated result would be misleading. It reads at most
+    /// `max_items + 1` entries before erroring.
+    pub fn range_capped(
+        &self,
+        store: &dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+        max_items: usize,
+    ) -> StdResult<Vec<(K::Output, T)>>
+    where
+        K::Output: 'static,
+    {
+        let items = self
+            .range(store, min, max, order)
+            .take(max_items.saturating_add(1))
+            .collect::<StdResult<Vec<_>>>()?;
+        if items.len() > max_items {
+            return Err(StdError::generic_err(format!(
+                "Result exceeds max_items of {}, please paginate",
+                max_items
+            )));
+        }
+        Ok(items)
+    }
+
+    /// Returns up to `limit` entries in the given range, together with an exclusive bound just past
+    /// the last of them. This can be passed back as `min` (or `max` in descending order) to get
+    /// the next page. The bound is `None` if fewer than `limit` entries were found.
+    ///
+    /// The bound is built from the raw key, so it is exact for composite keys as well.
+    pub fn range_limited(
+        &self,
+        store: &dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+        limit: usize,
+    ) -> StdResult<RangePage<'a, K, T>> {
+        let mut items = Vec::with_capacity(limit);
+        let mut last_key = None;
+        for item in self.range_raw(store, min, max, order).take(limit) {
+            let (raw_key, value) = item?;
+            items.push((K::from_slice(&raw_key)?, value));
+            last_key = Some(raw_key);
+        }
+        let next = match items.len() < limit {
+            true => None,
+            false => last_key.map(Bound::ExclusiveRaw),
+        };
+        Ok((items, next))
+    }
+
+    /// Counts the keys in the given range per bucket, as computed by `f` from each key.
+    /// Only keys are read, so no value is deserialized, e.g. to count entries per day
+    /// in a map keyed by timestamps.
+    ///
+    /// The whole bucket map is held in memory, so the number of distinct buckets should be bounded.
+    pub fn group_by_key<B, F>(
+        &self,
+        store: &dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        f: F,
+    ) -> StdResult<BTreeMap<B, u64>>
+    where
+        B: Ord,
+        F: Fn(&K::Output) -> B,
+        K::Output: 'static,
+    {
+        let mut buckets = BTreeMap::new();
+        for key in self.keys(store, min, max, cosmwasm_std::Order::Ascending) {
+            *buckets.entry(f(&key?)).or_insert(0) += 1;
+        }
+        Ok(buckets)
+    }
+
+    /// Iterates over all entries of the map. Shorthand for `range(store, None, None, order)`.
+    /// For composite keys this returns full keys, like `sub_prefix(())` does for 2-tuples.
+    pub fn iter_all<'c>(
+        &self,
+        store: &'c dyn Storage,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = StdResult<(K::Output, T)>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        self.range(store, None, None, order)
+    }
+
+    /// Iterates over the entries after `start_after`, in the given order, following the
+    /// `start_after` pagination idiom: the key is an exclusive lower bound when ascending
+    /// and an exclusive upper bound when descending. `None` starts at the first entry
+    /// in the given order.
+    pub fn range_from<'c>(
+        &self,
+        store: &'c dyn Storage,
+        start_after: Option<K>,
+        order: cosmwasm_std::Order,
+    ) -> Box<dyn Iterator<Item = StdResult<(K::Output, T)>> + 'c>
+    where
+        T: 'c,
+        K: Bounder<'a>,
+        K::Output: 'static,
+    {
+        let bound = start_after.and_then(Bounder::exclusive_bound);
+        match order {
+            cosmwasm_std::Order::Ascending => self.range(store, bound, None, order),
+            cosmwasm_std::Order::Descending => self.range(store, None, bound, order),
+        }
+    }
+
+    /// Returns `true` if any entry matches `predicate`, scanning the map in ascending key order.
+    /// This is O(n) in the worst case, but stops reading at the first match.
+    pub fn any(
+        &self,
+        store: &dyn Storage,
+        predicate: impl Fn(&K::Output, &T) -> bool,
+    ) -> StdResult<bool>
+    where
+        K::Output: 'static,
+    {
+        Ok(self.find(store, predicate)?.is_some())
+    }
+
+    /// Returns the first entry in ascending key order that matches `predicate`, if any.
+    /// This is O(n) in the worst case, but stops reading at the first match.
+    pub fn find(
+        &self,
+        store: &dyn Storage,
+        predicate: impl Fn(&K::Output, &T) -> bool,
+    ) -> StdResult<Option<(K::Output, T)>>
+    where
+        K::Output: 'static,
+    {
+        for item in self.range(store, None, None, cosmwasm_std::Order::Ascending) {
+            let (key, value) = item?;
+            if predicate(&key, &value) {
+                return Ok(Some((key, value)));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Works like `range`, but yields the entries in chunks of up to `chunk` elements.
+    /// The last chunk may be shorter. This lets callers process the entries in batches
+    /// instead of crossing the iterator boundary for every single item.
+    ///
+    /// If an entry fails to deserialize, the chunk containing it is returned as an error and
+    /// iteration continues with the following entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `chunk` is 0.
+    pub fn range_chunked<'c>(
+        &self,
+        store: &'c dyn Storage,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: cosmwasm_std::Order,
+        chunk: usize,
+    ) -> Box<dyn Iterator<Item = StdResult<Chunk<K, T>>> + 'c>
+    where
+        T: 'c,
+        K::Output: 'static,
+    {
+        assert!(chunk != 0, "chunk size must be non-zero");
+        let mut iter = self.range(store, min, max, order);
+        let chunked = std::iter::from_fn(move || {
+            match iter.by_ref().take(chunk).collect::<StdResult<Vec<_>>>() {
+                Ok(items) if items.is_empty() => None,
+                res => Some(res),
+            }
+        });
+        Box::new(chunked)
+    }
+
     /// Returns the first key-value pair in the map.
     /// This is *not* according to insertion-order, but according to the key ordering.
     ///
@@ -341,6 +762,75 @@ where
             .next()
             .transpose()
     }
+
+    /// Returns the first key-value pair within the lower `bound`, like [`Map::first`] does for
+    /// the whole map. With an exclusive bound, this finds the entry following a given key.
+    pub fn first_after(
+        &self,
+        storage: &dyn Storage,
+        bound: Option<Bound<'a, K>>,
+    ) -> StdResult<Option<(K::Output, T)>>
+    where
+        K::Output: 'static,
+    {
+        self.range(storage, bound, None, Order::Ascending)
+            .next()
+            .transpose()
+    }
+
+    /// Returns the last key-value pair within the upper `bound`, like [`Map::last`] does for
+    /// the whole map. With an exclusive bound, this finds the entry preceding a given key.
+    pub fn last_before(
+        &self,
+        storage: &dyn Storage,
+        bound: Option<Bound<'a, K>>,
+    ) -> StdResult<Option<(K::Output, T)>>
+    where
+        K::Output: 'static,
+    {
+        self.range(storage, None, bound, Order::Descending)
+            .next()
+            .transpose()
+    }
+
+    /// Copies up to `limit` entries of this map into `new_map`, with the keys converted by
+    /// `transform`. This is meant for migrating to a new key type, e.g. from `&str` to `Addr`.
+    ///
+    /// Returns an exclusive bound just past the last copied entry, to be passed back as
+    /// `start_after` to continue a chunked migration, or `None` once all entries have been
+    /// copied. Each call only reads the entries it copies, so the gas per call is bounded
+    /// by `limit`. Entries whose new key is already set in `new_map` are not overwritten,
+    /// and the old entries are left untouched.
+    pub fn migrate_entries<'b, K2>(
+        &self,
+        store: &mut dyn Storage,
+        new_map: &Map<K2, T>,
+        transform: impl Fn(K::Output) -> K2,
+        start_after: Option<Bound<'a, K>>,
+        limit: usize,
+    ) -> StdResult<Option<Bound<'a, K>>>
+    where
+        K2: PrimaryKey<'b>,
+    {
+        let entries = self
+            .range_raw(store, start_after, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        let mut last_key = None;
+        for (raw_key, value) in &entries {
+            let k = transform(K::from_slice(raw_key)?);
+            if !new_map.has(store, k.clone()) {
+                new_map.save(store, k, value)?;
+            }
+            last_key = Some(raw_key);
+        }
+        let next = match entries.len() < limit {
+            true => None,
+            false => last_key.cloned().map(Bound::ExclusiveRaw),
+        };
+        Ok(next)
+    }
 }
 
 #[cfg(test)]
@@ -379,6 +869,8 @@ mod test {
     const ALLOWANCE: Map<(&[u8], &[u8]), u64> = Map::new("allow");
 
     const TRIPLE: Map<(&[u8], u8, &str), u64> = Map::new("triple");
+    #[cfg(feature = "iterator")]
+    const QUADRUPLE: Map<(&Addr, u64, u8, &str), u64> = Map::new("quadruple");
 
     #[test]
     fn create_path() {
@@ -413,6 +905,23 @@ mod test {
         assert_eq!(b"pedro".to_vec().as_slice(), &key[17..]);
     }
 
+    #[test]
+    fn raw_key_works() {
+        assert_eq!(PEOPLE.raw_key(b"john"), b"\x00\x06peoplejohn".to_vec());
+        assert_eq!(
+            ALLOWANCE.raw_key((b"john", b"maria")),
+            b"\x00\x05allow\x00\x04johnmaria".to_vec()
+        );
+        assert_eq!(
+            TRIPLE.raw_key((b"john", 8u8, "pedro")),
+            b"\x00\x06triple\x00\x04john\x00\x01\x08pedro".to_vec()
+        );
+        assert_eq!(
+            PEOPLE.raw_key(b"john"),
+            PEOPLE.key(b"john").deref().to_vec()
+        );
+    }
+
     #[test]
     fn save_and_load() {
         let mut store = MockStorage::new();
@@ -438,6 +947,43 @@ mod test {
         assert_eq!(None, john.may_load(&store).unwrap());
     }
 
+    #[test]
+    fn may_load_many_works() {
+        let mut store = MockStorage::new();
+
+        let john = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+        let maria = Data {
+            name: "Maria".to_string(),
+            age: 42,
+        };
+        PEOPLE.save(&mut store, b"john", &john).unwrap();
+        PEOPLE.save(&mut store, b"maria", &maria).unwrap();
+
+        let [a, b, c] = PEOPLE
+            .may_load_many(&store, [b"maria", b"jack", b"john"])
+            .unwrap();
+        assert_eq!(a, Some(maria.clone()));
+        assert_eq!(b, None);
+        assert_eq!(c, Some(john.clone()));
+
+        let keys: [&[u8]; 4] = [b"jack", b"john", b"maria", b"john"];
+        let values = PEOPLE.may_load_many_vec(&store, keys).unwrap();
+        assert_eq!(
+            values,
+            vec![None, Some(john.clone()), Some(maria), Some(john)]
+        );
+        assert_eq!(PEOPLE.may_load_many(&store, []).unwrap(), []);
+
+        // parse errors are returned
+        store.set(&PEOPLE.key(b"jack"), b"{broken");
+        PEOPLE
+            .may_load_many(&store, [b"john", b"jack"])
+            .unwrap_err();
+    }
+
     #[test]
     fn existence() {
         let mut store = MockStorage::new();
@@ -881,6 +1427,52 @@ mod test {
         assert_eq!(all, vec![(50, data3)]);
     }
 
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_int256_key() {
+        use cosmwasm_std::Int256;
+
+        const AMOUNTS: Map<Int256, u64> = Map::new("amounts");
+
+        let mut store = MockStorage::new();
+        let keys = [
+            Int256::MAX,
+            Int256::one(),
+            Int256::MIN,
+            Int256::zero(),
+            Int256::from(-1),
+        ];
+        for (i, key) in keys.into_iter().enumerate() {
+            AMOUNTS.save(&mut store, key, &(i as u64)).unwrap();
+        }
+
+        let all = AMOUNTS
+            .keys(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            all,
+            vec![
+                Int256::MIN,
+                Int256::from(-1),
+                Int256::zero(),
+                Int256::one(),
+                Int256::MAX
+            ]
+        );
+
+        let negative = AMOUNTS
+            .range(
+                &store,
+                None,
+                Some(Bound::exclusive(Int256::zero())),
+                Order::Descending,
+            )
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(negative, vec![(Int256::from(-1), 4), (Int256::MIN, 2)]);
+    }
+
     #[test]
     #[cfg(feature = "iterator")]
     fn range_simple_signed_integer_key_with_bounder_trait() {
@@ -1050,10 +1642,55 @@ mod test {
 
     #[test]
     #[cfg(feature = "iterator")]
-    fn range_raw_triple_key() {
+    fn unit_sub_prefix_on_pair() {
         let mut store = MockStorage::new();
 
-        // save and load on three keys, one under different owner
+        ALLOWANCE
+            .save(&mut store, (b"owner", b"spender"), &1000)
+            .unwrap();
+        ALLOWANCE
+            .save(&mut store, (b"owner", b"spender2"), &3000)
+            .unwrap();
+        ALLOWANCE
+            .save(&mut store, (b"owner2", b"spender"), &5000)
+            .unwrap();
+        let all = vec![
+            ((b"owner".to_vec(), b"spender".to_vec()), 1000),
+            ((b"owner".to_vec(), b"spender2".to_vec()), 3000),
+            ((b"owner2".to_vec(), b"spender".to_vec()), 5000),
+        ];
+
+        // the unit sub-prefix of a pair ranges over everything, with full keys
+        let sub: StdResult<Vec<_>> = ALLOWANCE
+            .sub_prefix(())
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        assert_eq!(sub.unwrap(), all);
+        let sub: StdResult<Vec<_>> = ALLOWANCE
+            .sub_prefix(())
+            .range(
+                &store,
+                Some(Bound::exclusive((
+                    b"owner".as_slice(),
+                    b"spender".as_slice(),
+                ))),
+                None,
+                Order::Descending,
+            )
+            .collect();
+        assert_eq!(sub.unwrap(), vec![all[2].clone(), all[1].clone()]);
+
+        // same as iterating over the whole map
+        let every: StdResult<Vec<_>> = ALLOWANCE.iter_all(&store, Order::Ascending).collect();
+        assert_eq!(every.unwrap(), all);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_raw_triple_key() {
+        let mut store = MockStorage::new();
+
+        // save and load on three keys, one under different owner
         TRIPLE
             .save(&mut store, (b"owner", 9, "recipient"), &1000)
             .unwrap();
@@ -1227,6 +1864,99 @@ mod test {
         assert_eq!(all, vec![("recipient2".to_string(), 3000),]);
     }
 
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_quadruple_key() {
+        let mut store = MockStorage::new();
+        let owner = Addr::unchecked("owner");
+        let owner2 = Addr::unchecked("owner2");
+
+        QUADRUPLE
+            .save(&mut store, (&owner, 1, 9, "recipient"), &1000)
+            .unwrap();
+        QUADRUPLE
+            .save(&mut store, (&owner, 1, 9, "recipient2"), &3000)
+            .unwrap();
+        QUADRUPLE
+            .save(&mut store, (&owner, 1, 10, "recipient3"), &3000)
+            .unwrap();
+        QUADRUPLE
+            .save(&mut store, (&owner, 2, 9, "recipient"), &4000)
+            .unwrap();
+        QUADRUPLE
+            .save(&mut store, (&owner2, 1, 9, "recipient"), &5000)
+            .unwrap();
+        assert_eq!(
+            QUADRUPLE
+                .load(&store, (&owner, 1, 10, "recipient3"))
+                .unwrap(),
+            3000
+        );
+
+        // let's try to iterate!
+        let all: StdResult<Vec<_>> = QUADRUPLE
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(
+            all,
+            vec![
+                ((owner.clone(), 1, 9, "recipient".to_string()), 1000),
+                ((owner.clone(), 1, 9, "recipient2".to_string()), 3000),
+                ((owner.clone(), 1, 10, "recipient3".to_string()), 3000),
+                ((owner.clone(), 2, 9, "recipient".to_string()), 4000),
+                ((owner2.clone(), 1, 9, "recipient".to_string()), 5000),
+            ]
+        );
+
+        // let's iterate over a sub_prefix
+        let all: StdResult<Vec<_>> = QUADRUPLE
+            .sub_prefix((&owner, 1))
+            .range(&store, None, None, Order::Ascending)
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(
+            all,
+            vec![
+                ((9, "recipient".to_string()), 1000),
+                ((9, "recipient2".to_string()), 3000),
+                ((10, "recipient3".to_string()), 3000),
+            ]
+        );
+
+        // let's iterate over a prefix
+        let all: StdResult<Vec<_>> = QUADRUPLE
+            .prefix((&owner, 1, 9))
+            .range(
+                &store,
+                "recipient".exclusive_bound(),
+                None,
+                Order::Ascending,
+            )
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(all, vec![("recipient2".to_string(), 3000)]);
+
+        // and over a range of prefixes
+        let all: StdResult<Vec<_>> = QUADRUPLE
+            .prefix_range(
+                &store,
+                Some(PrefixBound::exclusive((&owner, 1, 9))),
+                Some(PrefixBound::inclusive((&owner2, 1, 9))),
+                Order::Descending,
+            )
+            .collect();
+        let all = all.unwrap();
+        assert_eq!(
+            all,
+            vec![
+                ((owner2.clone(), 1, 9, "recipient".to_string()), 5000),
+                ((owner.clone(), 2, 9, "recipient".to_string()), 4000),
+                ((owner, 1, 10, "recipient3".to_string()), 3000),
+            ]
+        );
+    }
+
     #[test]
     fn basic_update() {
         let mut store = MockStorage::new();
@@ -1242,6 +1972,33 @@ mod test {
         assert_eq!(20, loaded);
     }
 
+    #[test]
+    fn update_returning_old_works() {
+        let mut store = MockStorage::new();
+
+        let add_ten = |a: Option<u64>| -> StdResult<_> { Ok(a.unwrap_or_default() + 10) };
+
+        let key: (&[u8], &[u8]) = (b"owner", b"spender");
+        let changed = ALLOWANCE
+            .update_returning_old(&mut store, key, add_ten)
+            .unwrap();
+        assert_eq!((None, 10), changed);
+        let changed = ALLOWANCE
+            .update_returning_old(&mut store, key, add_ten)
+            .unwrap();
+        assert_eq!((Some(10), 20), changed);
+        assert_eq!(20, ALLOWANCE.load(&store, key).unwrap());
+
+        // nothing is written on error
+        let err = ALLOWANCE
+            .update_returning_old(&mut store, key, |_| -> StdResult<u64> {
+                Err(StdError::generic_err("nope"))
+            })
+            .unwrap_err();
+        assert_eq!(err, StdError::generic_err("nope"));
+        assert_eq!(20, ALLOWANCE.load(&store, key).unwrap());
+    }
+
     #[test]
     fn readme_works() -> StdResult<()> {
         let mut store = MockStorage::new();
@@ -1355,6 +2112,27 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn path_value_len_works() {
+        let mut store = MockStorage::new();
+        let data = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+
+        let john = PEOPLE.key(b"john");
+        assert_eq!(john.value_len(&store), None);
+
+        john.save(&mut store, &data).unwrap();
+        assert_eq!(
+            john.value_len(&store),
+            Some(cosmwasm_std::to_json_vec(&data).unwrap().len())
+        );
+
+        john.remove(&mut store);
+        assert_eq!(john.value_len(&store), None);
+    }
+
     #[test]
     #[cfg(feature = "iterator")]
     fn readme_with_range_raw() -> StdResult<()> {
@@ -1595,6 +2373,283 @@ mod test {
         assert_eq!(include, vec![456]);
     }
 
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn prefixed_range_128_bit_works() {
+        const SIGNED: Map<(i128, &str), u64> = Map::new("signed");
+        const UNSIGNED: Map<(u128, &str), u64> = Map::new("unsigned");
+
+        let mut store = MockStorage::new();
+        let signed = [i128::MIN, -300, -1, 0, 1, 300, i128::MAX];
+        for (i, k) in signed.into_iter().enumerate() {
+            SIGNED.save(&mut store, (k, "a"), &(i as u64)).unwrap();
+            SIGNED.save(&mut store, (k, "b"), &(i as u64)).unwrap();
+        }
+        let unsigned = [0, 1, 300, u128::MAX - 1, u128::MAX];
+        for (i, k) in unsigned.into_iter().enumerate() {
+            UNSIGNED.save(&mut store, (k, "a"), &(i as u64)).unwrap();
+        }
+
+        let signed_range = |min: Option<PrefixBound<'static, i128>>,
+                            max: Option<PrefixBound<'static, i128>>,
+                            order: Order| {
+            SIGNED
+                .prefix_range(&store, min, max, order)
+                .map(|r| r.map(|((k, _), _)| k))
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap()
+        };
+
+        // negative prefixes sort before positive ones
+        let all = signed_range(None, None, Order::Ascending);
+        let expected: Vec<_> = signed.iter().flat_map(|k| [*k, *k]).collect();
+        assert_eq!(all, expected);
+
+        assert_eq!(
+            signed_range(
+                Some(PrefixBound::inclusive(-300i128)),
+                Some(PrefixBound::exclusive(1i128)),
+                Order::Descending
+            ),
+            vec![0, 0, -1, -1, -300, -300]
+        );
+
+        // regression test for the extremes
+        assert_eq!(
+            signed_range(
+                Some(PrefixBound::exclusive(300i128)),
+                None,
+                Order::Ascending
+            ),
+            vec![i128::MAX, i128::MAX]
+        );
+        assert_eq!(
+            signed_range(
+                Some(PrefixBound::exclusive(i128::MAX)),
+                None,
+                Order::Ascending
+            ),
+            vec![]
+        );
+        assert_eq!(
+            signed_range(
+                Some(PrefixBound::inclusive(i128::MAX)),
+                Some(PrefixBound::inclusive(i128::MAX)),
+                Order::Descending
+            ),
+            vec![i128::MAX, i128::MAX]
+        );
+        assert_eq!(
+            signed_range(
+                None,
+                Some(PrefixBound::exclusive(i128::MIN)),
+                Order::Ascending
+            ),
+            vec![]
+        );
+
+        let unsigned_range =
+            |min: Option<PrefixBound<'static, u128>>, max: Option<PrefixBound<'static, u128>>| {
+                UNSIGNED
+                    .prefix_range(&store, min, max, Order::Ascending)
+                    .map(|r| r.map(|((k, _), _)| k))
+                    .collect::<StdResult<Vec<_>>>()
+                    .unwrap()
+            };
+        assert_eq!(
+            unsigned_range(Some(PrefixBound::exclusive(300u128)), None),
+            vec![u128::MAX - 1, u128::MAX]
+        );
+        assert_eq!(
+            unsigned_range(Some(PrefixBound::inclusive(u128::MAX)), None),
+            vec![u128::MAX]
+        );
+        assert_eq!(
+            unsigned_range(Some(PrefixBound::exclusive(u128::MAX)), None),
+            vec![]
+        );
+        assert_eq!(
+            unsigned_range(None, Some(PrefixBound::inclusive(u128::MAX))),
+            unsigned.to_vec()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn sub_keys_are_distinct() {
+        const SCORES: Map<(&str, u32), u64> = Map::new("scores");
+        const TRIPLES: Map<(u8, &str, u32), u64> = Map::new("triples");
+
+        let mut store = MockStorage::new();
+        assert_eq!(SCORES.sub_keys(&store, Order::Ascending).count(), 0);
+
+        for (name, n) in [("a", 1), ("a", 2), ("ab", 1), ("b", 1), ("b", 7), ("b", 9)] {
+            SCORES.save(&mut store, (name, n), &0).unwrap();
+            TRIPLES.save(&mut store, (1, name, n), &0).unwrap();
+            TRIPLES.save(&mut store, (255, name, n), &0).unwrap();
+        }
+        // a neighbouring map is not included
+        let other: Map<&str, u64> = Map::new("scoret");
+        other.save(&mut store, "a", &0).unwrap();
+
+        let names = SCORES
+            .sub_keys(&store, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(names, vec!["a", "b", "ab"]);
+        let names = SCORES
+            .sub_keys(&store, Order::Descending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(names, vec!["ab", "b", "a"]);
+
+        let pairs = TRIPLES
+            .sub_keys(&store, Order::Descending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(pairs.len(), 6);
+        assert_eq!(pairs[0], (255, "ab".to_string()));
+        assert_eq!(pairs[5], (1, "a".to_string()));
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn prefix_count_works() {
+        use cosmwasm_std::Addr;
+
+        const LOCKS: Map<(&Addr, u64), u64> = Map::new("locks");
+
+        let mut store = MockStorage::new();
+        assert_eq!(LOCKS.prefix_count(&store).unwrap(), 0);
+
+        for addr in ["alice", "bob", "carl"] {
+            let addr = Addr::unchecked(addr);
+            LOCKS.save(&mut store, (&addr, 1), &10).unwrap();
+            LOCKS.save(&mut store, (&addr, 2), &20).unwrap();
+        }
+        assert_eq!(LOCKS.prefix_count(&store).unwrap(), 3);
+
+        LOCKS.remove(&mut store, (&Addr::unchecked("bob"), 1));
+        assert_eq!(LOCKS.prefix_count(&store).unwrap(), 3);
+        LOCKS.remove(&mut store, (&Addr::unchecked("bob"), 2));
+        assert_eq!(LOCKS.prefix_count(&store).unwrap(), 2);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn sub_prefix_sub_keys_are_distinct() {
+        const TRIPLES: Map<(u8, &str, u32), u64> = Map::new("triples");
+
+        let mut store = MockStorage::new();
+        assert_eq!(
+            TRIPLES
+                .sub_prefix(1)
+                .sub_keys(&store, Order::Ascending)
+                .count(),
+            0
+        );
+
+        for (name, n) in [("a", 1), ("a", 2), ("ab", 1), ("b", 1), ("b", 7), ("b", 9)] {
+            TRIPLES.save(&mut store, (1, name, n), &0).unwrap();
+            TRIPLES.save(&mut store, (0, name, n), &0).unwrap();
+            TRIPLES.save(&mut store, (2, name, n), &0).unwrap();
+        }
+        TRIPLES.save(&mut store, (2, "c", 1), &0).unwrap();
+
+        let names = TRIPLES
+            .sub_prefix(1)
+            .sub_keys(&store, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(names, vec!["a", "b", "ab"]);
+        let names = TRIPLES
+            .sub_prefix(2)
+            .sub_keys(&store, Order::Descending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(names, vec!["ab", "c", "b", "a"]);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn prefix_range_cursor_pagination() {
+        const SCORES: Map<(&str, u32), u64> = Map::new("scores");
+
+        let mut store = MockStorage::new();
+        SCORES.save(&mut store, ("a", 1), &1).unwrap();
+        SCORES.save(&mut store, ("a", 2), &2).unwrap();
+        SCORES.save(&mut store, ("ab", 1), &3).unwrap();
+        SCORES.save(&mut store, ("b", 1), &4).unwrap();
+        SCORES.save(&mut store, ("b", 7), &5).unwrap();
+
+        // pages of one prefix each, resuming with the last prefix seen
+        let mut pages = vec![];
+        let mut cursor: Option<String> = None;
+        loop {
+            let min = cursor.as_deref().map(PrefixBound::after);
+            let first = SCORES
+                .prefix_range(&store, min, None, Order::Ascending)
+                .next()
+                .transpose()
+                .unwrap();
+            let Some(((prefix, _), _)) = first else {
+                break;
+            };
+            let page = SCORES
+                .prefix(&prefix)
+                .keys(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            pages.push((prefix.clone(), page));
+            cursor = Some(prefix);
+        }
+        // shorter strings come first, and "ab" is not mistaken for being under "a"
+        assert_eq!(
+            pages,
+            vec![
+                ("a".to_string(), vec![1, 2]),
+                ("b".to_string(), vec![1, 7]),
+                ("ab".to_string(), vec![1]),
+            ]
+        );
+
+        let after_b = SCORES
+            .prefix_range(
+                &store,
+                Some(PrefixBound::after("b")),
+                None,
+                Order::Ascending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(after_b, vec![3]);
+
+        let before_b = SCORES
+            .prefix_range(
+                &store,
+                None,
+                Some(PrefixBound::before("b")),
+                Order::Descending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(before_b, vec![2, 1]);
+
+        let before_ab = SCORES
+            .prefix_range(
+                &store,
+                None,
+                Some(PrefixBound::before("ab")),
+                Order::Descending,
+            )
+            .map(|r| r.map(|(_, v)| v))
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(before_ab, vec![5, 4, 2, 1]);
+    }
+
     #[test]
     #[cfg(feature = "iterator")]
     fn clear_works() {
@@ -1607,7 +2662,7 @@ mod test {
         TEST_MAP.save(&mut storage, "key3", &3u32).unwrap();
         TEST_MAP.save(&mut storage, "key4", &4u32).unwrap();
 
-        TEST_MAP.clear(&mut storage);
+        assert_eq!(TEST_MAP.clear(&mut storage), 5);
 
         assert!(!TEST_MAP.has(&storage, "key0"));
         assert!(!TEST_MAP.has(&storage, "key1"));
@@ -1618,28 +2673,120 @@ mod test {
 
     #[test]
     #[cfg(feature = "iterator")]
-    fn is_empty_works() {
-        const TEST_MAP: Map<&str, u32> = Map::new("test_map");
+    fn bound_from_output_continues_pagination() {
+        const SCORES: Map<(&str, u32), u64> = Map::new("scores");
 
-        let mut storage = MockStorage::new();
+        let mut store = MockStorage::new();
+        for (name, n) in [("a", 1), ("a", 2), ("ab", 1), ("b", 1), ("b", 7)] {
+            SCORES.save(&mut store, (name, n), &(n as u64)).unwrap();
+        }
 
-        assert!(TEST_MAP.is_empty(&storage));
+        let last: (String, u32) = ("b".to_string(), 1);
+        // same bytes as the borrowed key
+        let raw = |bound: Bound<(&str, u32)>| match bound.to_raw_bound() {
+            crate::bound::RawBound::Exclusive(raw) => raw,
+            crate::bound::RawBound::Inclusive(_) => panic!("unexpected inclusive bound"),
+        };
+        assert_eq!(
+            raw(Bound::exclusive_from_output(&last)),
+            raw(Bound::exclusive(("b", 1)))
+        );
 
-        TEST_MAP.save(&mut storage, "key1", &1u32).unwrap();
-        TEST_MAP.save(&mut storage, "key2", &2u32).unwrap();
+        let mut pages = vec![];
+        let mut cursor: Option<(String, u32)> = None;
+        loop {
+            let min = cursor.as_ref().map(Bound::exclusive_from_output);
+            let page = SCORES
+                .keys(&store, min, None, Order::Ascending)
+                .take(2)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            match page.last() {
+                Some(last) => cursor = Some(last.clone()),
+                None => break,
+            }
+            pages.push(page);
+        }
+        assert_eq!(pages.concat().len(), 5);
+        assert_eq!(pages[1][0], ("b".to_string(), 1));
 
-        assert!(!TEST_MAP.is_empty(&storage));
+        let min = Bound::inclusive_from_output(&last);
+        let keys = SCORES
+            .keys(&store, Some(min), None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                ("b".to_string(), 1),
+                ("b".to_string(), 7),
+                ("ab".to_string(), 1)
+            ]
+        );
     }
 
     #[test]
-    #[cfg(feature = "iterator")]
-    fn first_last_work() {
-        let mut storage = cosmwasm_std::testing::MockStorage::new();
-        const MAP: Map<&str, u32> = Map::new("map");
+    fn take_works() {
+        let mut store = MockStorage::new();
+        let john = Data {
+            name: "John".to_string(),
+            age: 32,
+        };
+        PEOPLE.save(&mut store, b"john", &john).unwrap();
 
-        // empty map
-        assert_eq!(MAP.first(&storage), Ok(None));
-        assert_eq!(MAP.last(&storage), Ok(None));
+        assert_eq!(PEOPLE.take(&mut store, b"john").unwrap(), Some(john));
+        assert!(!PEOPLE.has(&store, b"john"));
+        assert_eq!(PEOPLE.take(&mut store, b"john").unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn clear_prefix_works() {
+        const BALANCES: Map<(&str, u64), u32> = Map::new("balances");
+
+        let mut storage = MockStorage::new();
+        for id in 0..25 {
+            BALANCES.save(&mut storage, ("alice", id), &1).unwrap();
+            BALANCES.save(&mut storage, ("bob", id), &2).unwrap();
+        }
+        // shares the first bytes of the prefix
+        BALANCES.save(&mut storage, ("alicea", 0), &3).unwrap();
+
+        BALANCES.clear_prefix(&mut storage, "alice");
+
+        assert!(BALANCES.prefix("alice").is_empty(&storage));
+        assert_eq!(BALANCES.prefix("bob").count(&storage, None, None), 25);
+        assert_eq!(BALANCES.load(&storage, ("alicea", 0)).unwrap(), 3);
+
+        // clearing an empty prefix is a no-op
+        BALANCES.clear_prefix(&mut storage, "carl");
+        assert_eq!(BALANCES.count(&storage, None, None), 26);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn is_empty_works() {
+        const TEST_MAP: Map<&str, u32> = Map::new("test_map");
+
+        let mut storage = MockStorage::new();
+
+        assert!(TEST_MAP.is_empty(&storage));
+
+        TEST_MAP.save(&mut storage, "key1", &1u32).unwrap();
+        TEST_MAP.save(&mut storage, "key2", &2u32).unwrap();
+
+        assert!(!TEST_MAP.is_empty(&storage));
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn first_last_work() {
+        let mut storage = cosmwasm_std::testing::MockStorage::new();
+        const MAP: Map<&str, u32> = Map::new("map");
+
+        // empty map
+        assert_eq!(MAP.first(&storage), Ok(None));
+        assert_eq!(MAP.last(&storage), Ok(None));
 
         // insert entries
         MAP.save(&mut storage, "ghi", &1).unwrap();
@@ -1649,4 +2796,1026 @@ mod test {
         assert_eq!(MAP.first(&storage), Ok(Some(("abc".to_string(), 2))));
         assert_eq!(MAP.last(&storage), Ok(Some(("ghi".to_string(), 1))));
     }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn first_after_last_before_work() {
+        let mut storage = MockStorage::new();
+        const MAP: Map<u32, u32> = Map::new("map");
+
+        assert_eq!(
+            MAP.first_after(&storage, Some(Bound::inclusive(1u32))),
+            Ok(None)
+        );
+        assert_eq!(
+            MAP.last_before(&storage, Some(Bound::inclusive(1u32))),
+            Ok(None)
+        );
+
+        for key in [10, 20, 30] {
+            MAP.save(&mut storage, key, &(key * 2)).unwrap();
+        }
+
+        // no bound is like first / last
+        assert_eq!(MAP.first_after(&storage, None), Ok(Some((10, 20))));
+        assert_eq!(MAP.last_before(&storage, None), Ok(Some((30, 60))));
+
+        assert_eq!(
+            MAP.first_after(&storage, Some(Bound::inclusive(20u32))),
+            Ok(Some((20, 40)))
+        );
+        assert_eq!(
+            MAP.first_after(&storage, Some(Bound::exclusive(20u32))),
+            Ok(Some((30, 60)))
+        );
+        assert_eq!(
+            MAP.first_after(&storage, Some(Bound::inclusive(15u32))),
+            Ok(Some((20, 40)))
+        );
+        assert_eq!(
+            MAP.first_after(&storage, Some(Bound::exclusive(30u32))),
+            Ok(None)
+        );
+
+        assert_eq!(
+            MAP.last_before(&storage, Some(Bound::inclusive(20u32))),
+            Ok(Some((20, 40)))
+        );
+        assert_eq!(
+            MAP.last_before(&storage, Some(Bound::exclusive(20u32))),
+            Ok(Some((10, 20)))
+        );
+        assert_eq!(
+            MAP.last_before(&storage, Some(Bound::inclusive(25u32))),
+            Ok(Some((20, 40)))
+        );
+        assert_eq!(
+            MAP.last_before(&storage, Some(Bound::exclusive(10u32))),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn prefix_first_last_work() {
+        let mut storage = MockStorage::new();
+        const BIDS: Map<(u32, u128), u64> = Map::new("bids");
+
+        assert_eq!(BIDS.prefix(2).first(&storage), Ok(None));
+        assert_eq!(BIDS.prefix(2).last(&storage), Ok(None));
+
+        BIDS.save(&mut storage, (1, 500), &1).unwrap();
+        BIDS.save(&mut storage, (2, 70), &2).unwrap();
+        BIDS.save(&mut storage, (2, 120), &3).unwrap();
+        BIDS.save(&mut storage, (2, 90), &4).unwrap();
+        BIDS.save(&mut storage, (3, 10), &5).unwrap();
+
+        // the highest bid for auction 2
+        assert_eq!(BIDS.prefix(2).last(&storage), Ok(Some((120, 3))));
+        assert_eq!(BIDS.prefix(2).first(&storage), Ok(Some((70, 2))));
+
+        // unlike the whole map
+        assert_eq!(BIDS.first(&storage), Ok(Some(((1, 500), 1))));
+        assert_eq!(BIDS.last(&storage), Ok(Some(((3, 10), 5))));
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_prefix_variable_length_bounds() {
+        let mut store = MockStorage::new();
+        const ITEMS: Map<(&str, &[u8]), u64> = Map::new("items");
+
+        let suffixes: [&[u8]; 7] = [b"a", b"ab", b"ab\x00", b"ab\x01", b"abc", b"b", b""];
+        for (i, suffix) in suffixes.iter().enumerate() {
+            ITEMS
+                .save(&mut store, ("owner", suffix), &(i as u64))
+                .unwrap();
+            // neighbours under other owners must never show up
+            ITEMS.save(&mut store, ("owne", suffix), &100).unwrap();
+            ITEMS.save(&mut store, ("owner2", suffix), &200).unwrap();
+        }
+
+        let range = |min: Option<Bound<'static, &'static [u8]>>,
+                     max: Option<Bound<'static, &'static [u8]>>,
+                     order: Order| {
+            ITEMS
+                .prefix("owner")
+                .keys(&store, min, max, order)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap()
+        };
+
+        // the inclusive upper bound includes "ab", but nothing longer starting with it
+        assert_eq!(
+            range(
+                None,
+                Some(Bound::inclusive(b"ab".as_slice())),
+                Order::Descending
+            ),
+            vec![b"ab".to_vec(), b"a".to_vec(), b"".to_vec()]
+        );
+        assert_eq!(
+            range(
+                None,
+                Some(Bound::inclusive(b"ab".as_slice())),
+                Order::Ascending
+            ),
+            vec![b"".to_vec(), b"a".to_vec(), b"ab".to_vec()]
+        );
+        assert_eq!(
+            range(
+                None,
+                Some(Bound::exclusive(b"ab".as_slice())),
+                Order::Descending
+            ),
+            vec![b"a".to_vec(), b"".to_vec()]
+        );
+        assert_eq!(
+            range(
+                None,
+                Some(Bound::inclusive(b"ab\x00".as_slice())),
+                Order::Descending
+            ),
+            vec![
+                b"ab\x00".to_vec(),
+                b"ab".to_vec(),
+                b"a".to_vec(),
+                b"".to_vec()
+            ]
+        );
+
+        // same for the lower bounds
+        assert_eq!(
+            range(
+                Some(Bound::exclusive(b"ab".as_slice())),
+                None,
+                Order::Descending
+            ),
+            vec![
+                b"b".to_vec(),
+                b"abc".to_vec(),
+                b"ab\x01".to_vec(),
+                b"ab\x00".to_vec()
+            ]
+        );
+        assert_eq!(
+            range(
+                Some(Bound::inclusive(b"ab".as_slice())),
+                Some(Bound::inclusive(b"ab\x01".as_slice())),
+                Order::Descending
+            ),
+            vec![b"ab\x01".to_vec(), b"ab\x00".to_vec(), b"ab".to_vec()]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_fixed_array_composite_key() {
+        let mut store = MockStorage::new();
+        const NAMES: Map<([u8; 32], String), u64> = Map::new("names");
+
+        let hash1 = [1u8; 32];
+        let mut hash2 = [0u8; 32];
+        hash2[31] = 0xff;
+        NAMES
+            .save(&mut store, (hash1, "bob".to_string()), &2)
+            .unwrap();
+        NAMES
+            .save(&mut store, (hash1, "alice".to_string()), &1)
+            .unwrap();
+        NAMES.save(&mut store, (hash2, String::new()), &3).unwrap();
+
+        let all: Vec<_> = NAMES
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(
+            all,
+            vec![
+                ((hash2, String::new()), 3),
+                ((hash1, "alice".to_string()), 1),
+                ((hash1, "bob".to_string()), 2),
+            ]
+        );
+
+        // prefix by the fixed array
+        let names: Vec<_> = NAMES
+            .prefix(hash1)
+            .keys(&store, None, None, Order::Descending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(names, vec!["bob".to_string(), "alice".to_string()]);
+
+        // and the other way around, with a fixed array as last element
+        const OWNERS: Map<(&str, [u8; 4]), u64> = Map::new("owners");
+        OWNERS.save(&mut store, ("john", [1, 2, 3, 4]), &7).unwrap();
+        let all: Vec<_> = OWNERS
+            .range(
+                &store,
+                Some(Bound::inclusive(("john", [0; 4]))),
+                None,
+                Order::Ascending,
+            )
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(all, vec![(("john".to_string(), [1, 2, 3, 4]), 7)]);
+
+        // a hash followed by a counter
+        const RECEIPTS: Map<([u8; 32], u64), u64> = Map::new("receipts");
+        for (hash, counter) in [([0u8; 32], 2), (hash1, 0), ([0u8; 32], 1), (hash2, 1)] {
+            RECEIPTS
+                .save(&mut store, (hash, counter), &(counter * 10))
+                .unwrap();
+        }
+        let receipts: Vec<_> = RECEIPTS
+            .prefix([0u8; 32])
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(receipts, vec![(1, 10), (2, 20)]);
+    }
+
+    #[test]
+    fn unit_key_works() {
+        let mut store = MockStorage::new();
+        const SINGLE: Map<(), u64> = Map::new("single");
+
+        assert_eq!(SINGLE.may_load(&store, ()).unwrap(), None);
+        SINGLE.save(&mut store, (), &42).unwrap();
+        assert!(SINGLE.has(&store, ()));
+        assert_eq!(SINGLE.load(&store, ()).unwrap(), 42);
+
+        // stored right at the length-prefixed namespace, so it doesn't collide with an `Item`
+        assert_eq!(&*SINGLE.key(()), b"\x00\x06single");
+        let item: crate::Item<u64> = crate::Item::new("single");
+        assert_eq!(item.may_load(&store).unwrap(), None);
+
+        #[cfg(feature = "iterator")]
+        {
+            let all: Vec<_> = SINGLE
+                .range(&store, None, None, Order::Ascending)
+                .collect::<StdResult<_>>()
+                .unwrap();
+            assert_eq!(all, vec![((), 42)]);
+            assert!(!SINGLE.is_empty(&store));
+        }
+
+        SINGLE.remove(&mut store, ());
+        assert!(!SINGLE.has(&store, ()));
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_suffix_byte_prefix() {
+        let mut store = MockStorage::new();
+        const TAGS: Map<(Addr, Vec<u8>), u64> = Map::new("tags");
+
+        let owner = Addr::unchecked("owner");
+        let other = Addr::unchecked("other");
+        let suffixes: [&[u8]; 6] = [b"ab", b"ab\xff", b"abc", b"abd", b"ac", b"b"];
+        for (i, suffix) in suffixes.iter().enumerate() {
+            TAGS.save(&mut store, (owner.clone(), suffix.to_vec()), &(i as u64))
+                .unwrap();
+            TAGS.save(&mut store, (other.clone(), suffix.to_vec()), &100)
+                .unwrap();
+        }
+
+        let (min, max) = Bound::prefix(b"ab");
+        let all: StdResult<Vec<_>> = TAGS
+            .prefix(owner.clone())
+            .range(&store, min, max, Order::Ascending)
+            .collect();
+        assert_eq!(
+            all.unwrap(),
+            vec![
+                (b"ab".to_vec(), 0),
+                (b"abc".to_vec(), 2),
+                (b"abd".to_vec(), 3),
+                (b"ab\xff".to_vec(), 1),
+            ]
+        );
+
+        let (min, max) = Bound::prefix(b"ab\xff");
+        let all: StdResult<Vec<_>> = TAGS
+            .prefix(owner.clone())
+            .range(&store, min, max, Order::Descending)
+            .collect();
+        assert_eq!(all.unwrap(), vec![(b"ab\xff".to_vec(), 1)]);
+
+        // a prefix of all 255 bytes has no upper bound
+        let (min, max) = Bound::prefix(b"\xff");
+        assert!(max.is_none());
+        let all: StdResult<Vec<_>> = TAGS
+            .prefix(owner)
+            .range(&store, min, max, Order::Ascending)
+            .collect();
+        assert_eq!(all.unwrap(), vec![]);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn migrate_entries_works() {
+        const OLD: Map<&str, u64> = Map::new("balances");
+        const NEW: Map<Addr, u64> = Map::new("balances_v2");
+
+        let mut store = MockStorage::new();
+        for (i, user) in ["carol", "alice", "bob"].into_iter().enumerate() {
+            OLD.save(&mut store, user, &(i as u64)).unwrap();
+        }
+        // already migrated entries are skipped
+        NEW.save(&mut store, Addr::unchecked("alice"), &10).unwrap();
+
+        // "alice" is first, and skipped
+        let next = OLD
+            .migrate_entries(&mut store, &NEW, Addr::unchecked, None, 2)
+            .unwrap();
+        assert!(next.is_some());
+        assert_eq!(NEW.load(&store, Addr::unchecked("alice")).unwrap(), 10);
+        assert_eq!(NEW.load(&store, Addr::unchecked("bob")).unwrap(), 2);
+        assert!(!NEW.has(&store, Addr::unchecked("carol")));
+
+        // continues after "bob", and finishes
+        let next = OLD
+            .migrate_entries(&mut store, &NEW, Addr::unchecked, next, 2)
+            .unwrap();
+        assert!(next.is_none());
+
+        let migrated = NEW
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            migrated,
+            vec![
+                (Addr::unchecked("alice"), 10),
+                (Addr::unchecked("bob"), 2),
+                (Addr::unchecked("carol"), 0),
+            ]
+        );
+
+        // the old entries are kept
+        assert_eq!(OLD.keys(&store, None, None, Order::Ascending).count(), 3);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn export_import_works() {
+        let mut store = MockStorage::new();
+
+        ALLOWANCE
+            .save(&mut store, (b"owner", b"spender"), &1000)
+            .unwrap();
+        ALLOWANCE
+            .save(&mut store, (b"owner", b"spender2"), &3000)
+            .unwrap();
+        ALLOWANCE
+            .save(&mut store, (b"owner2", b"spender"), &5000)
+            .unwrap();
+        // a neighbouring namespace must not be exported
+        const ALLOWANCE2: Map<(&[u8], &[u8]), u64> = Map::new("allow2");
+        ALLOWANCE2
+            .save(&mut store, (b"owner", b"spender"), &1)
+            .unwrap();
+
+        let exported: Vec<_> = ALLOWANCE.export(&store).collect();
+        assert_eq!(exported.len(), 3);
+        assert_eq!(
+            exported[0],
+            (
+                (b"owner".as_slice(), b"spender".as_slice()).joined_key(),
+                b"1000".to_vec()
+            )
+        );
+
+        // copy to another map, keys are re-namespaced
+        const COPY: Map<(&[u8], &[u8]), u64> = Map::new("copy");
+        COPY.import(&mut store, exported);
+        let original: Vec<_> = ALLOWANCE
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        let copied: Vec<_> = COPY
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<_>>()
+            .unwrap();
+        assert_eq!(original, copied);
+        assert_eq!(COPY.load(&store, (b"owner2", b"spender")).unwrap(), 5000);
+
+        // restore into a fresh store
+        let mut restored = MockStorage::new();
+        ALLOWANCE.import(&mut restored, ALLOWANCE.export(&store));
+        assert_eq!(
+            ALLOWANCE.export(&restored).collect::<Vec<_>>(),
+            ALLOWANCE.export(&store).collect::<Vec<_>>()
+        );
+        assert!(ALLOWANCE2.is_empty(&restored));
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn keys_vec_works() {
+        let mut store = MockStorage::new();
+
+        for i in 1..=5u32 {
+            PEOPLE_ID
+                .save(
+                    &mut store,
+                    i,
+                    &Data {
+                        name: format!("person {}", i),
+                        age: i as i32,
+                    },
+                )
+                .unwrap();
+        }
+
+        let keys = PEOPLE_ID
+            .keys_vec(&store, None, None, Order::Ascending, None)
+            .unwrap();
+        assert_eq!(keys, vec![1, 2, 3, 4, 5]);
+
+        let keys = PEOPLE_ID
+            .keys_vec(&store, None, None, Order::Descending, Some(2))
+            .unwrap();
+        assert_eq!(keys, vec![5, 4]);
+
+        let keys = PEOPLE_ID
+            .keys_vec(
+                &store,
+                Some(Bound::exclusive(2u32)),
+                None,
+                Order::Ascending,
+                Some(10),
+            )
+            .unwrap();
+        assert_eq!(keys, vec![3, 4, 5]);
+
+        let keys = PEOPLE_ID
+            .keys_vec(&store, None, None, Order::Ascending, Some(0))
+            .unwrap();
+        assert!(keys.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn collect_into_works() {
+        let mut store = MockStorage::new();
+
+        for i in 1..=5u32 {
+            PEOPLE_ID
+                .save(
+                    &mut store,
+                    i,
+                    &Data {
+                        name: format!("person {}", i),
+                        age: i as i32,
+                    },
+                )
+                .unwrap();
+        }
+
+        let mut out = vec![];
+        PEOPLE_ID
+            .collect_into(&store, None, None, Order::Ascending, Some(3), &mut out)
+            .unwrap();
+        assert_eq!(
+            out.iter().map(|(k, _)| *k).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(out[2].1.name, "person 3");
+
+        // the buffer is cleared before being refilled
+        let capacity = out.capacity();
+        PEOPLE_ID
+            .collect_into(
+                &store,
+                Some(Bound::exclusive(3u32)),
+                None,
+                Order::Descending,
+                None,
+                &mut out,
+            )
+            .unwrap();
+        assert_eq!(out.iter().map(|(k, _)| *k).collect::<Vec<_>>(), vec![5, 4]);
+        assert_eq!(out.capacity(), capacity);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn values_works() {
+        let mut store = MockStorage::new();
+        ALLOWANCE
+            .save(&mut store, (b"owner", b"spender"), &10)
+            .unwrap();
+        ALLOWANCE
+            .save(&mut store, (b"owner", b"spender2"), &20)
+            .unwrap();
+        ALLOWANCE
+            .save(&mut store, (b"owner2", b"spender"), &30)
+            .unwrap();
+
+        let total: u64 = ALLOWANCE
+            .values(&store, None, None, Order::Ascending)
+            .sum::<StdResult<u64>>()
+            .unwrap();
+        assert_eq!(total, 60);
+
+        let values = ALLOWANCE
+            .values(
+                &store,
+                Some(Bound::exclusive((
+                    b"owner".as_slice(),
+                    b"spender".as_slice(),
+                ))),
+                None,
+                Order::Descending,
+            )
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(values, vec![30, 20]);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_from_works() {
+        const SCORES: Map<u32, u64> = Map::new("scores");
+
+        let mut store = MockStorage::new();
+        for i in 1..=5u32 {
+            SCORES.save(&mut store, i, &(i as u64 * 10)).unwrap();
+        }
+
+        let keys = |start_after, order| {
+            SCORES
+                .range_from(&store, start_after, order)
+                .map(|item| item.map(|(k, _)| k))
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap()
+        };
+        assert_eq!(keys(None, Order::Ascending), vec![1, 2, 3, 4, 5]);
+        assert_eq!(keys(Some(3), Order::Ascending), vec![4, 5]);
+        assert_eq!(keys(None, Order::Descending), vec![5, 4, 3, 2, 1]);
+        assert_eq!(keys(Some(3), Order::Descending), vec![2, 1]);
+        assert_eq!(keys(Some(5), Order::Ascending), Vec::<u32>::new());
+
+        let first = SCORES
+            .range_from(&store, Some(1), Order::Ascending)
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(first, (2, 20));
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn any_and_find_stop_at_first_match() {
+        use std::cell::Cell;
+
+        const RECIPIENTS: Map<u32, String> = Map::new("recipients");
+
+        let mut store = MockStorage::new();
+        for (i, addr) in ["alice", "bob", "carl", "bob", "dave"]
+            .into_iter()
+            .enumerate()
+        {
+            RECIPIENTS
+                .save(&mut store, i as u32, &addr.to_string())
+                .unwrap();
+        }
+
+        let visited = Cell::new(0);
+        let is_bob = |_: &u32, addr: &String| {
+            visited.set(visited.get() + 1);
+            addr == "bob"
+        };
+        assert_eq!(
+            RECIPIENTS.find(&store, is_bob).unwrap(),
+            Some((1, "bob".to_string()))
+        );
+        assert_eq!(visited.get(), 2);
+
+        visited.set(0);
+        assert!(RECIPIENTS.any(&store, is_bob).unwrap());
+        assert_eq!(visited.get(), 2);
+
+        // without a match, every entry is visited
+        visited.set(0);
+        let is_emma = |_: &u32, addr: &String| {
+            visited.set(visited.get() + 1);
+            addr == "emma"
+        };
+        assert!(!RECIPIENTS.any(&store, is_emma).unwrap());
+        assert_eq!(RECIPIENTS.find(&store, is_emma).unwrap(), None);
+        assert_eq!(visited.get(), 10);
+
+        // the key is passed as well
+        assert_eq!(
+            RECIPIENTS
+                .find(&store, |k, addr| *k > 1 && addr == "bob")
+                .unwrap(),
+            Some((3, "bob".to_string()))
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_limited_works() {
+        const SCORES: Map<(&str, u32), u64> = Map::new("scores");
+
+        let mut store = MockStorage::new();
+        let entries = [("a", 1), ("a", 2), ("ab", 1), ("b", 300), ("b", 4)];
+        for (i, key) in entries.into_iter().enumerate() {
+            SCORES.save(&mut store, key, &(i as u64)).unwrap();
+        }
+
+        let mut pages = vec![];
+        let mut min = None;
+        loop {
+            let (items, next) = SCORES
+                .range_limited(&store, min, None, Order::Ascending, 2)
+                .unwrap();
+            pages.push(items);
+            match next {
+                Some(next) => min = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(
+            pages,
+            vec![
+                vec![(("a".to_string(), 1), 0), (("a".to_string(), 2), 1)],
+                vec![(("b".to_string(), 4), 4), (("b".to_string(), 300), 3)],
+                vec![(("ab".to_string(), 1), 2)],
+            ]
+        );
+
+        let mut keys = vec![];
+        let mut max = None;
+        loop {
+            let (items, next) = SCORES
+                .range_limited(&store, None, max, Order::Descending, 1)
+                .unwrap();
+            keys.extend(items.into_iter().map(|(k, _)| k));
+            match next {
+                Some(next) => max = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(
+            keys,
+            vec![
+                ("ab".to_string(), 1),
+                ("b".to_string(), 300),
+                ("b".to_string(), 4),
+                ("a".to_string(), 2),
+                ("a".to_string(), 1),
+            ]
+        );
+
+        // a full last page is followed by an empty one
+        let (items, next) = SCORES
+            .range_limited(&store, None, None, Order::Ascending, 5)
+            .unwrap();
+        assert_eq!(items.len(), 5);
+        let (items, next) = SCORES
+            .range_limited(&store, next, None, Order::Ascending, 5)
+            .unwrap();
+        assert!(items.is_empty());
+        assert!(next.is_none());
+
+        // triple keys
+        const TRIPLE: Map<(&str, u32, &str), u64> = Map::new("triple");
+        TRIPLE.save(&mut store, ("x", 1, "b"), &1).unwrap();
+        TRIPLE.save(&mut store, ("x", 1, "c"), &2).unwrap();
+        TRIPLE.save(&mut store, ("x", 2, "a"), &3).unwrap();
+        let (items, next) = TRIPLE
+            .range_limited(&store, None, None, Order::Ascending, 2)
+            .unwrap();
+        assert_eq!(items[1].0, ("x".to_string(), 1, "c".to_string()));
+        let (items, next) = TRIPLE
+            .range_limited(&store, next, None, Order::Ascending, 2)
+            .unwrap();
+        assert_eq!(items, vec![(("x".to_string(), 2, "a".to_string()), 3)]);
+        assert!(next.is_none());
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn bool_keys_work() {
+        const FLAGS: Map<(&Addr, bool), u32> = Map::new("flags");
+
+        let mut store = MockStorage::new();
+        let alice = Addr::unchecked("alice");
+        FLAGS.save(&mut store, (&alice, true), &1).unwrap();
+        FLAGS.save(&mut store, (&alice, false), &0).unwrap();
+        assert_eq!(FLAGS.load(&store, (&alice, true)).unwrap(), 1);
+
+        // false sorts before true
+        let all = FLAGS
+            .prefix(&alice)
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(all, vec![(false, 0), (true, 1)]);
+
+        let keys = FLAGS
+            .keys(&store, None, None, Order::Descending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(keys, vec![(alice.clone(), true), (alice, false)]);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn char_keys_sort_by_code_point() {
+        const LETTERS: Map<char, u32> = Map::new("letters");
+
+        let mut store = MockStorage::new();
+        for (i, c) in ['z', 'B', 'a', 'é', 'A'].into_iter().enumerate() {
+            LETTERS.save(&mut store, c, &(i as u32)).unwrap();
+        }
+        assert_eq!(LETTERS.load(&store, 'é').unwrap(), 3);
+
+        let keys = LETTERS
+            .keys(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(keys, vec!['A', 'B', 'a', 'z', 'é']);
+
+        let lowercase = LETTERS
+            .keys(
+                &store,
+                Some(Bound::inclusive('a')),
+                Some(Bound::inclusive('z')),
+                Order::Ascending,
+            )
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(lowercase, vec!['a', 'z']);
+    }
+
+    #[test]
+    fn binary_key_works() {
+        use cosmwasm_std::Binary;
+
+        const BLOBS: Map<&Binary, u64> = Map::new("blobs");
+
+        let mut store = MockStorage::new();
+        let hash = Binary::from(b"\x01\x02\x03");
+        BLOBS.save(&mut store, &hash, &42).unwrap();
+        assert_eq!(BLOBS.load(&store, &hash).unwrap(), 42);
+        // stored like the raw bytes
+        assert_eq!(&*BLOBS.key(&hash), b"\x00\x05blobs\x01\x02\x03");
+
+        #[cfg(feature = "iterator")]
+        {
+            let all = BLOBS
+                .range(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(all, vec![(hash, 42)]);
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn decimal_keys_sort_numerically() {
+        use cosmwasm_std::Decimal;
+
+        const ASKS: Map<Decimal, u32> = Map::new("asks");
+
+        let mut store = MockStorage::new();
+        for (i, percent) in [100, 90, 1000, 5].into_iter().enumerate() {
+            ASKS.save(&mut store, Decimal::percent(percent), &(i as u32))
+                .unwrap();
+        }
+        assert_eq!(ASKS.load(&store, Decimal::percent(90)).unwrap(), 1);
+
+        let prices = ASKS
+            .keys(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            prices,
+            vec![
+                Decimal::percent(5),
+                Decimal::percent(90),
+                Decimal::percent(100),
+                Decimal::percent(1000),
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn optional_sub_keys_sort_none_first() {
+        const LOCKS: Map<(&Addr, Option<u64>), u32> = Map::new("locks");
+
+        let mut store = MockStorage::new();
+        let addr = Addr::unchecked("addr");
+        LOCKS.save(&mut store, (&addr, Some(5)), &2).unwrap();
+        LOCKS.save(&mut store, (&addr, Some(0)), &1).unwrap();
+        LOCKS.save(&mut store, (&addr, None), &0).unwrap();
+        assert_eq!(LOCKS.load(&store, (&addr, None)).unwrap(), 0);
+
+        let locks = LOCKS
+            .prefix(&addr)
+            .range(&store, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(locks, vec![(None, 0), (Some(0), 1), (Some(5), 2)]);
+
+        let keys = LOCKS
+            .keys(&store, None, None, Order::Descending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            keys,
+            vec![
+                (addr.clone(), Some(5)),
+                (addr.clone(), Some(0)),
+                (addr, None)
+            ]
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn count_works() {
+        const NUMBERS: Map<u32, u32> = Map::new("numbers");
+        const PAIRS: Map<(&str, u32), u32> = Map::new("pairs");
+
+        let mut store = MockStorage::new();
+        assert_eq!(NUMBERS.count(&store, None, None), 0);
+
+        for i in 0..1000u32 {
+            NUMBERS.save(&mut store, i, &i).unwrap();
+        }
+        PAIRS.save(&mut store, ("a", 1), &1).unwrap();
+        PAIRS.save(&mut store, ("b", 1), &1).unwrap();
+        PAIRS.save(&mut store, ("b", 2), &1).unwrap();
+
+        assert_eq!(NUMBERS.count(&store, None, None), 1000);
+        assert_eq!(
+            NUMBERS.count(
+                &store,
+                Some(Bound::inclusive(100u32)),
+                Some(Bound::exclusive(200u32))
+            ),
+            100
+        );
+        assert_eq!(
+            NUMBERS.count(&store, Some(Bound::exclusive(999u32)), None),
+            0
+        );
+
+        assert_eq!(PAIRS.count(&store, None, None), 3);
+        assert_eq!(PAIRS.prefix("b").count(&store, None, None), 2);
+        assert_eq!(
+            PAIRS
+                .prefix("b")
+                .count(&store, Some(Bound::exclusive(1u32)), None),
+            1
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn group_by_key_works() {
+        const EVENTS: Map<(u64, &str), String> = Map::new("events");
+        const DAY: u64 = 86_400;
+
+        let mut store = MockStorage::new();
+        for (time, id) in [
+            (10, "a"),
+            (500, "b"),
+            (DAY + 3, "c"),
+            (3 * DAY, "d"),
+            (10, "e"),
+        ] {
+            EVENTS
+                .save(&mut store, (time, id), &"not json".to_string())
+                .unwrap();
+        }
+        // values are not deserialized
+        store.set(&EVENTS.key((10, "a")), b"{broken");
+
+        let per_day = EVENTS
+            .group_by_key(&store, None, None, |(time, _)| time / DAY)
+            .unwrap();
+        assert_eq!(per_day, BTreeMap::from([(0, 3), (1, 1), (3, 1)]));
+
+        let per_day = EVENTS
+            .group_by_key(
+                &store,
+                Some(Bound::exclusive((10, "e"))),
+                Some(Bound::exclusive((3 * DAY, ""))),
+                |(time, _)| time / DAY,
+            )
+            .unwrap();
+        assert_eq!(per_day, BTreeMap::from([(0, 1), (1, 1)]));
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_capped_works() {
+        let mut store = MockStorage::new();
+
+        for i in 1..=5u32 {
+            PEOPLE_ID
+                .save(
+                    &mut store,
+                    i,
+                    &Data {
+                        name: format!("person {}", i),
+                        age: i as i32,
+                    },
+                )
+                .unwrap();
+        }
+
+        let all = PEOPLE_ID
+            .range_capped(&store, None, None, Order::Ascending, 5)
+            .unwrap();
+        assert_eq!(all.len(), 5);
+
+        let err = PEOPLE_ID
+            .range_capped(&store, None, None, Order::Ascending, 4)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            StdError::generic_err("Result exceeds max_items of 4, please paginate")
+        );
+
+        // only the entries within the bounds count
+        let some = PEOPLE_ID
+            .range_capped(
+                &store,
+                Some(Bound::inclusive(4u32)),
+                None,
+                Order::Descending,
+                2,
+            )
+            .unwrap();
+        assert_eq!(some.iter().map(|(k, _)| *k).collect::<Vec<_>>(), vec![5, 4]);
+
+        let empty = PEOPLE_ID
+            .range_capped(
+                &store,
+                Some(Bound::exclusive(5u32)),
+                None,
+                Order::Ascending,
+                0,
+            )
+            .unwrap();
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_chunked_works() {
+        const MAP: Map<u32, u32> = Map::new("map");
+        let mut storage = MockStorage::new();
+
+        // empty map yields no chunks
+        assert_eq!(
+            MAP.range_chunked(&storage, None, None, Order::Ascending, 2)
+                .count(),
+            0
+        );
+
+        for i in 0..5u32 {
+            MAP.save(&mut storage, i, &(i * 10)).unwrap();
+        }
+
+        let chunks = MAP
+            .range_chunked(&storage, None, None, Order::Ascending, 2)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            chunks,
+            vec![vec![(0, 0), (1, 10)], vec![(2, 20), (3, 30)], vec![(4, 40)]]
+        );
+
+        // bounds and order are honored
+        let chunks = MAP
+            .range_chunked(
+                &storage,
+                Some(Bound::exclusive(0u32)),
+                None,
+                Order::Descending,
+                3,
+            )
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(chunks, vec![vec![(4, 40), (3, 30), (2, 20)], vec![(1, 10)]]);
+
+        // a chunk larger than the map returns everything at once
+        let chunks = MAP
+            .range_chunked(&storage, None, None, Order::Ascending, 100)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].len(), 5);
+    }
 }
@@ -0,0 +1,211 @@
+use std::cell::Cell;
+
+use cosmwasm_std::Storage;
+#[cfg(feature = "iterator")]
+use cosmwasm_std::{Order, Record};
+
+/// Number of storage calls made through a [`MeteredStorage`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StorageMetrics {
+    pub reads: u64,
+    pub writes: u64,
+    pub removes: u64,
+    /// Number of `range` and `range_keys` calls, regardless of how many records were consumed
+    pub ranges: u64,
+    /// Number of the `ranges` that were `range_keys` calls, which don't fetch any values
+    pub key_ranges: u64,
+    /// Number of records (or keys) consumed from all ranges
+    pub range_items: u64,
+}
+
+/// `MeteredStorage` wraps a storage and counts the calls made to it, so tests can assert
+/// the access pattern of a handler, e.g. that it performs exactly `N` reads, and catch
+/// accidentally quadratic code. As everything in this crate (`Item`, `Map`, `Path`, ...)
+/// goes through `Storage`, no further instrumentation is needed.
+///
+/// This is purely diagnostic and only available with the `metrics` feature.
+pub struct MeteredStorage<'a> {
+    inner: &'a mut dyn Storage,
+    metrics: Cell<StorageMetrics>,
+}
+
+impl<'a> MeteredStorage<'a> {
+    pub fn new(inner: &'a mut dyn Storage) -> Self {
+        MeteredStorage {
+            inner,
+            metrics: Cell::new(StorageMetrics::default()),
+        }
+    }
+
+    /// Returns the calls counted since creation or the last [`MeteredStorage::reset`].
+    pub fn metrics(&self) -> StorageMetrics {
+        self.metrics.get()
+    }
+
+    /// Sets all counters back to zero.
+    pub fn reset(&self) {
+        self.metrics.set(StorageMetrics::default());
+    }
+
+    fn count(&self, f: impl FnOnce(&mut StorageMetrics)) {
+        let mut metrics = self.metrics.get();
+        f(&mut metrics);
+        self.metrics.set(metrics);
+    }
+}
+
+impl<'a> Storage for MeteredStorage<'a> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        self.count(|m| m.reads += 1);
+        self.inner.get(key)
+    }
+
+    #[cfg(feature = "iterator")]
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'b> {
+        self.count(|m| m.ranges += 1);
+        Box::new(
+            self.inner
+                .range(start, end, order)
+                .inspect(move |_| self.count(|m| m.range_items += 1)),
+        )
+    }
+
+    #[cfg(feature = "iterator")]
+    fn range_keys<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Vec<u8>> + 'b> {
+        self.count(|m| {
+            m.ranges += 1;
+            m.key_ranges += 1;
+        });
+        Box::new(
+            self.inner
+                .range_keys(start, end, order)
+                .inspect(move |_| self.count(|m| m.range_items += 1)),
+        )
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.count(|m| m.writes += 1);
+        self.inner.set(key, value)
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.count(|m| m.removes += 1);
+        self.inner.remove(key)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Item, Map};
+    use cosmwasm_std::testing::MockStorage;
+    use cosmwasm_std::StdResult;
+
+    const FEE: Item<u64> = Item::new("fee");
+    const BALANCES: Map<&str, u64> = Map::new("balances");
+
+    fn transfer(store: &mut dyn Storage, from: &str, to: &str, amount: u64) -> StdResult<()> {
+        let fee = FEE.load(store)?;
+        BALANCES.update(store, from, |b| -> StdResult<_> {
+            Ok(b.unwrap_or_default() - amount - fee)
+        })?;
+        BALANCES.update(store, to, |b| -> StdResult<_> {
+            Ok(b.unwrap_or_default() + amount)
+        })?;
+        Ok(())
+    }
+
+    #[test]
+    fn counts_handler_access() {
+        let mut store = MockStorage::new();
+        FEE.save(&mut store, &1).unwrap();
+        BALANCES.save(&mut store, "alice", &100).unwrap();
+
+        let mut metered = MeteredStorage::new(&mut store);
+        transfer(&mut metered, "alice", "bob", 10).unwrap();
+        assert_eq!(
+            metered.metrics(),
+            StorageMetrics {
+                reads: 3,
+                writes: 2,
+                ..StorageMetrics::default()
+            }
+        );
+
+        metered.reset();
+        BALANCES.remove(&mut metered, "bob");
+        assert!(!BALANCES.has(&metered, "bob"));
+        assert_eq!(metered.metrics().removes, 1);
+        assert_eq!(metered.metrics().reads, 1);
+
+        assert_eq!(BALANCES.load(&store, "alice").unwrap(), 89);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn counts_range_items() {
+        use cosmwasm_std::Order;
+
+        let mut store = MockStorage::new();
+        for addr in ["a", "b", "c", "d"] {
+            BALANCES.save(&mut store, addr, &1).unwrap();
+        }
+
+        let metered = MeteredStorage::new(&mut store);
+        let first_two = BALANCES
+            .keys(&metered, None, None, Order::Ascending)
+            .take(2)
+            .count();
+        assert_eq!(first_two, 2);
+        assert_eq!(metered.metrics().ranges, 1);
+        assert_eq!(metered.metrics().range_items, 2);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn keys_do_not_read_values() {
+        use cosmwasm_std::Order;
+
+        let mut store = MockStorage::new();
+        for addr in ["a", "b", "c", "d"] {
+            BALANCES.save(&mut store, addr, &1).unwrap();
+        }
+
+        let metered = MeteredStorage::new(&mut store);
+        let keys = BALANCES
+            .keys(&metered, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(keys.len(), 4);
+        assert_eq!(
+            metered.metrics(),
+            StorageMetrics {
+                ranges: 1,
+                key_ranges: 1,
+                range_items: 4,
+                ..StorageMetrics::default()
+            }
+        );
+
+        // unlike range
+        metered.reset();
+        assert_eq!(
+            BALANCES
+                .range(&metered, None, None, Order::Ascending)
+                .count(),
+            4
+        );
+        assert_eq!(metered.metrics().key_ranges, 0);
+        assert_eq!(metered.metrics().ranges, 1);
+    }
+}
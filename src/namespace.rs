@@ -1,5 +1,7 @@
 use std::borrow::Cow;
 
+use cosmwasm_std::storage_keys::to_length_prefixed;
+
 /// The namespace of a storage container. Meant to be constructed from "stringy" types.
 ///
 /// This type is generally not meant to be constructed directly. It's exported for
@@ -16,6 +18,17 @@ impl Namespace {
     pub fn as_slice(&self) -> &[u8] {
         self.0.as_ref()
     }
+
+    /// Returns the namespace of `child` nested under this one, for containers that should
+    /// share a common parent, e.g. `Map::new_dyn(parent.join("balances"))`.
+    ///
+    /// The parent is length-prefixed, so the result differs from the namespaces of other
+    /// children and from `child` used as a top-level namespace.
+    pub fn join(&self, child: impl Into<Namespace>) -> Namespace {
+        let mut joined = to_length_prefixed(self.as_slice());
+        joined.extend_from_slice(child.into().as_slice());
+        Namespace(Cow::Owned(joined))
+    }
 }
 
 impl From<&'static str> for Namespace {
@@ -35,3 +48,42 @@ impl From<Cow<'static, [u8]>> for Namespace {
         Namespace(s)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Item, Map};
+    use cosmwasm_std::testing::MockStorage;
+
+    #[test]
+    fn joined_namespaces_do_not_collide() {
+        let parent = Namespace::from("module");
+        assert_eq!(
+            parent.join("balances").as_slice(),
+            b"\x00\x06modulebalances"
+        );
+
+        let balances: Map<&str, u64> = Map::new_dyn(parent.join("balances"));
+        let allowances: Map<&str, u64> = Map::new_dyn(parent.join("allowances"));
+        let top_level: Map<&str, u64> = Map::new("balances");
+        let parent_map: Map<&str, u64> = Map::new_dyn(parent.clone());
+
+        let mut store = MockStorage::new();
+        balances.save(&mut store, "alice", &1).unwrap();
+        allowances.save(&mut store, "alice", &2).unwrap();
+        top_level.save(&mut store, "alice", &3).unwrap();
+        parent_map.save(&mut store, "alice", &4).unwrap();
+
+        assert_eq!(balances.load(&store, "alice").unwrap(), 1);
+        assert_eq!(allowances.load(&store, "alice").unwrap(), 2);
+        assert_eq!(top_level.load(&store, "alice").unwrap(), 3);
+        assert_eq!(parent_map.load(&store, "alice").unwrap(), 4);
+
+        // nested joins and items work too
+        let nested = parent.join("sub".to_string()).join("config");
+        let config: Item<u64> = Item::new_dyn(nested.clone());
+        config.save(&mut store, &5).unwrap();
+        assert_eq!(config.load(&store).unwrap(), 5);
+        assert_ne!(nested, parent.join("subconfig"));
+    }
+}
@@ -0,0 +1,194 @@
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use cosmwasm_std::{StdError, StdResult, Storage};
+
+use crate::keys::PrimaryKey;
+use crate::map::Map;
+use crate::namespace::Namespace;
+use crate::path::Path;
+
+/// Hook called by [`ObservedMap`] on every write, with the old and the new value.
+/// `None` as old value means the entry is inserted, `None` as new value means it is removed.
+pub type OnChange<T> = fn(&mut dyn Storage, Option<&T>, Option<&T>) -> StdResult<()>;
+
+/// `ObservedMap` works like a `Map`, but calls a hook on every `save`, `remove` and `update`.
+/// This is useful to maintain derived data, like a running total or a count of entries,
+/// without having to remember updating it at every call site.
+///
+/// Writes bypassing the `ObservedMap` (e.g. through the underlying `Map`) are not observed.
+pub struct ObservedMap<K, T> {
+    primary: Map<K, T>,
+    on_change: OnChange<T>,
+}
+
+impl<K, T> ObservedMap<K, T> {
+    /// Creates a new [`ObservedMap`] with the given storage key and hook. This is a const fn only
+    /// suitable when you have the storage key in the form of a static string slice.
+    pub const fn new(namespace: &'static str, on_change: OnChange<T>) -> Self {
+        ObservedMap {
+            primary: Map::new(namespace),
+            on_change,
+        }
+    }
+
+    /// Creates a new [`ObservedMap`] with the given storage key and hook. Use this if you might
+    /// need to handle a dynamic string. Otherwise, you might prefer [`ObservedMap::new`].
+    pub fn new_dyn(namespace: impl Into<Namespace>, on_change: OnChange<T>) -> Self {
+        ObservedMap {
+            primary: Map::new_dyn(namespace),
+            on_change,
+        }
+    }
+
+    /// Returns the underlying `Map`, to be used for read access like ranges or prefixes.
+    pub fn map(&self) -> &Map<K, T> {
+        &self.primary
+    }
+}
+
+impl<'a, K, T> ObservedMap<K, T>
+where
+    T: Serialize + DeserializeOwned + Clone,
+    K: PrimaryKey<'a>,
+{
+    pub fn key(&self, k: K) -> Path<T> {
+        self.primary.key(k)
+    }
+
+    /// save will serialize the model and store, returns an error on serialization issues
+    /// or if the hook fails. This must load the old value to pass it to the hook.
+    pub fn save(&self, store: &mut dyn Storage, k: K, data: &T) -> StdResult<()> {
+        let old_data = self.primary.may_load(store, k.clone())?;
+        self.replace(store, k, Some(data), old_data.as_ref())
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage, k: K) -> StdResult<()> {
+        let old_data = self.primary.may_load(store, k.clone())?;
+        self.replace(store, k, None, old_data.as_ref())
+    }
+
+    /// replace writes data to key, and calls the hook. old_data must be the current stored value
+    /// (from a previous load). This is used by save, remove and update, and can be called
+    /// directly if you want to optimize
+    pub fn replace(
+        &self,
+        store: &mut dyn Storage,
+        k: K,
+        data: Option<&T>,
+        old_data: Option<&T>,
+    ) -> StdResult<()> {
+        (self.on_change)(store, old_data, data)?;
+        match data {
+            Some(updated) => self.primary.save(store, k, updated),
+            None => {
+                self.primary.remove(store, k);
+                Ok(())
+            }
+        }
+    }
+
+    /// Loads the data, perform the specified action, and store the result in the database,
+    /// calling the hook. If the data exists, `action(Some(value))` is called.
+    /// Otherwise `action(None)` is called.
+    pub fn update<A, E>(&self, store: &mut dyn Storage, k: K, action: A) -> Result<T, E>
+    where
+        A: FnOnce(Option<T>) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        let input = self.primary.may_load(store, k.clone())?;
+        let old_val = input.clone();
+        let output = action(input)?;
+        self.replace(store, k, Some(&output), old_val.as_ref())?;
+        Ok(output)
+    }
+
+    /// load will return an error if no data is set at the given key, or on parse error
+    pub fn load(&self, store: &dyn Storage, k: K) -> StdResult<T> {
+        self.primary.load(store, k)
+    }
+
+    /// may_load will parse the data stored at the key if present, returns Ok(None) if no data there.
+    /// returns an error on issues parsing
+    pub fn may_load(&self, store: &dyn Storage, k: K) -> StdResult<Option<T>> {
+        self.primary.may_load(store, k)
+    }
+
+    /// has returns true or false if any data is at this key, without parsing or interpreting the
+    /// contents.
+    pub fn has(&self, store: &dyn Storage, k: K) -> bool {
+        self.primary.has(store, k)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Item;
+    use cosmwasm_std::testing::MockStorage;
+
+    const TOTAL: Item<u64> = Item::new("total");
+    const CHANGES: Item<Vec<(Option<u64>, Option<u64>)>> = Item::new("changes");
+
+    fn track_total(store: &mut dyn Storage, old: Option<&u64>, new: Option<&u64>) -> StdResult<()> {
+        let total = TOTAL.may_load(store)?.unwrap_or_default();
+        TOTAL.save(store, &(total - old.unwrap_or(&0) + new.unwrap_or(&0)))?;
+
+        let mut changes = CHANGES.may_load(store)?.unwrap_or_default();
+        changes.push((old.copied(), new.copied()));
+        CHANGES.save(store, &changes)
+    }
+
+    const BALANCES: ObservedMap<&str, u64> = ObservedMap::new("balances", track_total);
+
+    #[test]
+    fn hook_sees_old_and_new() {
+        let mut store = MockStorage::new();
+
+        BALANCES.save(&mut store, "alice", &100).unwrap();
+        BALANCES.save(&mut store, "bob", &50).unwrap();
+        BALANCES.save(&mut store, "alice", &70).unwrap();
+        BALANCES
+            .update(&mut store, "bob", |v| -> StdResult<_> {
+                Ok(v.unwrap_or_default() + 5)
+            })
+            .unwrap();
+        BALANCES.remove(&mut store, "alice").unwrap();
+
+        assert_eq!(
+            CHANGES.load(&store).unwrap(),
+            vec![
+                (None, Some(100)),
+                (None, Some(50)),
+                (Some(100), Some(70)),
+                (Some(50), Some(55)),
+                (Some(70), None),
+            ]
+        );
+        assert_eq!(TOTAL.load(&store).unwrap(), 55);
+        assert_eq!(BALANCES.load(&store, "bob").unwrap(), 55);
+        assert!(!BALANCES.has(&store, "alice"));
+
+        // removing a missing entry still notifies the hook
+        BALANCES.remove(&mut store, "carol").unwrap();
+        assert_eq!(CHANGES.load(&store).unwrap().last(), Some(&(None, None)));
+        assert_eq!(TOTAL.load(&store).unwrap(), 55);
+    }
+
+    #[test]
+    fn hook_error_aborts_write() {
+        let mut store = MockStorage::new();
+        const CAPPED: ObservedMap<u32, u64> = ObservedMap::new("capped", |_, _, new| match new {
+            Some(v) if *v > 10 => Err(StdError::generic_err("too big")),
+            _ => Ok(()),
+        });
+
+        CAPPED.save(&mut store, 1, &5).unwrap();
+        assert_eq!(
+            CAPPED.save(&mut store, 1, &11).unwrap_err(),
+            StdError::generic_err("too big")
+        );
+        assert_eq!(CAPPED.load(&store, 1).unwrap(), 5);
+        assert_eq!(CAPPED.map().load(&store, 1).unwrap(), 5);
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::BTreeMap;
+#[cfg(feature = "iterator")]
+use std::iter::Peekable;
+
+use cosmwasm_std::Storage;
+#[cfg(feature = "iterator")]
+use cosmwasm_std::{Order, Record};
+
+/// `OverlayStorage` keeps writes in memory on top of a base storage, which is only read from
+/// until the overlay is flushed. As it implements `Storage`, it can be used with `Item`, `Map`
+/// and the other types of this crate to try out a sequence of writes without committing them.
+///
+/// Dropping the overlay discards the pending writes, while [`OverlayStorage::flush`]
+/// applies them to the base storage.
+pub struct OverlayStorage<'a> {
+    base: &'a mut dyn Storage,
+    /// Pending writes, where `None` marks a removed key
+    diff: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl<'a> OverlayStorage<'a> {
+    pub fn new(base: &'a mut dyn Storage) -> Self {
+        OverlayStorage {
+            base,
+            diff: BTreeMap::new(),
+        }
+    }
+
+    /// Returns `true` if no writes are pending.
+    pub fn is_clean(&self) -> bool {
+        self.diff.is_empty()
+    }
+
+    /// Applies all pending writes to the base storage.
+    pub fn flush(self) {
+        for (key, value) in self.diff {
+            match value {
+                Some(value) => self.base.set(&key, &value),
+                None => self.base.remove(&key),
+            }
+        }
+    }
+}
+
+impl<'a> Storage for OverlayStorage<'a> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.diff.get(key) {
+            Some(value) => value.clone(),
+            None => self.base.get(key),
+        }
+    }
+
+    #[cfg(feature = "iterator")]
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Record> + 'b> {
+        use std::ops::Bound;
+
+        if let (Some(start), Some(end)) = (start, end) {
+            // BTreeMap::range panics on inverted bounds
+            if start > end {
+                return Box::new(std::iter::empty());
+            }
+        }
+        let bounds = (
+            start.map_or(Bound::Unbounded, |s| Bound::Included(s.to_vec())),
+            end.map_or(Bound::Unbounded, |e| Bound::Excluded(e.to_vec())),
+        );
+        let diff = self.diff.range(bounds);
+        let diff: Box<dyn Iterator<Item = _>> = match order {
+            Order::Ascending => Box::new(diff),
+            Order::Descending => Box::new(diff.rev()),
+        };
+
+        Box::new(MergedIter {
+            base: self.base.range(start, end, order).peekable(),
+            diff: diff.peekable(),
+            order,
+        })
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.diff.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.diff.insert(key.to_vec(), None);
+    }
+}
+
+#[cfg(feature = "iterator")]
+type DiffIter<'b> = Box<dyn Iterator<Item = (&'b Vec<u8>, &'b Option<Vec<u8>>)> + 'b>;
+
+/// Merges the records of the base storage with the pending writes, which take precedence
+#[cfg(feature = "iterator")]
+struct MergedIter<'b> {
+    base: Peekable<Box<dyn Iterator<Item = Record> + 'b>>,
+    diff: Peekable<DiffIter<'b>>,
+    order: Order,
+}
+
+#[cfg(feature = "iterator")]
+impl<'b> Iterator for MergedIter<'b> {
+    type Item = Record;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        use std::cmp::Ordering;
+
+        loop {
+            let take_base = match (self.base.peek(), self.diff.peek()) {
+                (None, None) => return None,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (Some((base_key, _)), Some((diff_key, _))) => {
+                    let ordering = match self.order {
+                        Order::Ascending => base_key.as_slice().cmp(diff_key.as_slice()),
+                        Order::Descending => diff_key.as_slice().cmp(base_key.as_slice()),
+                    };
+                    match ordering {
+                        Ordering::Less => true,
+                        Ordering::Greater => false,
+                        Ordering::Equal => {
+                            // overwritten or removed in the overlay
+                            self.base.next();
+                            false
+                        }
+                    }
+                }
+            };
+
+            if take_base {
+                return self.base.next();
+            }
+            if let Some((key, Some(value))) = self.diff.next() {
+                return Some((key.clone(), value.clone()));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Item, Map};
+    use cosmwasm_std::testing::MockStorage;
+
+    const CONFIG: Item<String> = Item::new("config");
+    const BALANCES: Map<&str, u64> = Map::new("balances");
+
+    #[test]
+    fn writes_stay_in_overlay_until_flushed() {
+        let mut store = MockStorage::new();
+        CONFIG.save(&mut store, &"base".to_string()).unwrap();
+        BALANCES.save(&mut store, "alice", &10).unwrap();
+
+        let mut overlay = OverlayStorage::new(&mut store);
+        assert!(overlay.is_clean());
+        CONFIG.save(&mut overlay, &"overlay".to_string()).unwrap();
+        BALANCES.save(&mut overlay, "bob", &5).unwrap();
+        BALANCES.remove(&mut overlay, "alice");
+
+        assert_eq!(CONFIG.load(&overlay).unwrap(), "overlay");
+        assert_eq!(BALANCES.may_load(&overlay, "alice").unwrap(), None);
+        assert_eq!(BALANCES.load(&overlay, "bob").unwrap(), 5);
+        assert!(!overlay.is_clean());
+
+        // dropping discards the writes
+        drop(overlay);
+        assert_eq!(CONFIG.load(&store).unwrap(), "base");
+        assert_eq!(BALANCES.load(&store, "alice").unwrap(), 10);
+        assert_eq!(BALANCES.may_load(&store, "bob").unwrap(), None);
+
+        let mut overlay = OverlayStorage::new(&mut store);
+        CONFIG.save(&mut overlay, &"overlay".to_string()).unwrap();
+        BALANCES.remove(&mut overlay, "alice");
+        overlay.flush();
+        assert_eq!(CONFIG.load(&store).unwrap(), "overlay");
+        assert_eq!(BALANCES.may_load(&store, "alice").unwrap(), None);
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn range_merges_overlay_and_base() {
+        use cosmwasm_std::StdResult;
+
+        let mut store = MockStorage::new();
+        for (addr, amount) in [("alice", 1u64), ("bob", 2), ("carl", 3), ("dave", 4)] {
+            BALANCES.save(&mut store, addr, &amount).unwrap();
+        }
+
+        let mut overlay = OverlayStorage::new(&mut store);
+        BALANCES.save(&mut overlay, "bob", &20).unwrap();
+        BALANCES.remove(&mut overlay, "carl");
+        BALANCES.save(&mut overlay, "emma", &5).unwrap();
+        BALANCES.save(&mut overlay, "aaron", &0).unwrap();
+        // removing a key that only exists in the overlay
+        BALANCES.save(&mut overlay, "zed", &9).unwrap();
+        BALANCES.remove(&mut overlay, "zed");
+
+        let all = BALANCES
+            .range(&overlay, None, None, Order::Ascending)
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            all,
+            vec![
+                ("aaron".to_string(), 0),
+                ("alice".to_string(), 1),
+                ("bob".to_string(), 20),
+                ("dave".to_string(), 4),
+                ("emma".to_string(), 5),
+            ]
+        );
+
+        let keys = BALANCES
+            .keys(
+                &overlay,
+                Some(crate::Bound::exclusive("alice")),
+                Some(crate::Bound::inclusive("dave")),
+                Order::Descending,
+            )
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(keys, vec!["dave".to_string(), "bob".to_string()]);
+    }
+}
@@ -0,0 +1,157 @@
+use std::fmt;
+
+use cosmwasm_std::{StdError, StdResult};
+
+use crate::de::KeyDeserialize;
+use crate::keys::{Key, PrimaryKey};
+use crate::Prefixer;
+
+/// A string key that is zero-padded to `N` bytes when stored.
+///
+/// Variable-length elements of composite keys are length-prefixed, so they sort by length
+/// first: `("zing", 1)` comes before `("grow1", 1)`. As all `PaddedString<N>` keys have the same
+/// length, they sort lexicographically instead, at the cost of always taking `N` bytes.
+///
+/// Padded strings can be at most `N` bytes long and must not contain NUL characters.
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PaddedString<const N: usize>([u8; N]);
+
+impl<const N: usize> PaddedString<N> {
+    /// Returns an error if `value` is longer than `N` bytes or contains a NUL character.
+    pub fn new(value: &str) -> StdResult<Self> {
+        if value.len() > N {
+            return Err(StdError::generic_err(format!(
+                "String of {} bytes does not fit a key of {} bytes",
+                value.len(),
+                N
+            )));
+        }
+        if value.contains('\0') {
+            return Err(StdError::generic_err(
+                "Padded string keys cannot contain NUL characters",
+            ));
+        }
+        let mut bytes = [0; N];
+        bytes[..value.len()].copy_from_slice(value.as_bytes());
+        Ok(PaddedString(bytes))
+    }
+
+    pub fn as_str(&self) -> &str {
+        let len = self.0.iter().position(|b| *b == 0).unwrap_or(N);
+        // only ever constructed from a valid str followed by padding
+        std::str::from_utf8(&self.0[..len]).unwrap()
+    }
+}
+
+impl<const N: usize> fmt::Display for PaddedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl<const N: usize> fmt::Debug for PaddedString<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "PaddedString<{}>({:?})", N, self.as_str())
+    }
+}
+
+impl<'a, const N: usize> PrimaryKey<'a> for PaddedString<N> {
+    type Prefix = ();
+    type SubPrefix = ();
+    type Suffix = Self;
+    type SuperSuffix = Self;
+
+    fn key(&self) -> Vec<Key> {
+        vec![Key::Ref(&self.0)]
+    }
+}
+
+impl<'a, const N: usize> Prefixer<'a> for PaddedString<N> {
+    fn prefix(&self) -> Vec<Key> {
+        vec![Key::Ref(&self.0)]
+    }
+}
+
+impl<const N: usize> KeyDeserialize for PaddedString<N> {
+    type Output = PaddedString<N>;
+
+    const KEY_ELEMS: u16 = 1;
+
+    #[inline(always)]
+    fn from_vec(value: Vec<u8>) -> StdResult<Self::Output> {
+        let bytes = <[u8; N]>::from_vec(value)?;
+        let len = bytes.iter().position(|b| *b == 0).unwrap_or(N);
+        if bytes[len..].iter().any(|b| *b != 0) {
+            return Err(StdError::generic_err(
+                "Invalid padding in padded string key",
+            ));
+        }
+        let value = std::str::from_utf8(&bytes[..len])
+            .map_err(|e| StdError::invalid_utf8(e.to_string()))?;
+        PaddedString::new(value)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn padded_string_works() {
+        let key = PaddedString::<8>::new("grow").unwrap();
+        assert_eq!(key.as_str(), "grow");
+        assert_eq!(key.to_string(), "grow");
+        assert_eq!(key.joined_key(), b"grow\0\0\0\0".to_vec());
+        assert_eq!(PaddedString::<8>::from_vec(key.joined_key()).unwrap(), key);
+
+        // the full length is allowed
+        assert_eq!(PaddedString::<4>::new("zing").unwrap().as_str(), "zing");
+
+        assert!(PaddedString::<4>::new("grow1").is_err());
+        assert!(PaddedString::<8>::new("gr\0w").is_err());
+        assert!(PaddedString::<8>::from_vec(b"grow".to_vec()).is_err());
+        assert!(PaddedString::<4>::from_vec(b"g\0ow".to_vec()).is_err());
+    }
+
+    #[test]
+    #[cfg(feature = "iterator")]
+    fn padded_prefixes_sort_lexicographically() {
+        use crate::Map;
+        use cosmwasm_std::testing::MockStorage;
+        use cosmwasm_std::Order;
+
+        let names = ["zing", "grow1", "grow"];
+
+        // length prefixing sorts shorter strings first
+        let plain: Map<(String, u32), u64> = Map::new("plain");
+        let mut store = MockStorage::new();
+        for name in names {
+            plain.save(&mut store, (name.to_string(), 1), &0).unwrap();
+        }
+        let keys = plain
+            .keys(&store, None, None, Order::Ascending)
+            .map(|k| k.unwrap().0)
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["grow", "zing", "grow1"]);
+
+        let padded: Map<(PaddedString<16>, u32), u64> = Map::new("padded");
+        for name in names {
+            padded
+                .save(&mut store, (PaddedString::new(name).unwrap(), 1), &0)
+                .unwrap();
+        }
+        let keys = padded
+            .keys(&store, None, None, Order::Ascending)
+            .map(|k| k.unwrap().0.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, vec!["grow", "grow1", "zing"]);
+
+        // prefixes still work
+        let suffixes = padded
+            .prefix(PaddedString::new("grow1").unwrap())
+            .keys(&store, None, None, Order::Ascending)
+            .collect::<cosmwasm_std::StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(suffixes, vec![1]);
+    }
+}
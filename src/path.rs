@@ -3,7 +3,9 @@ use serde::de::DeserializeOwned;
 use serde::Serialize;
 use std::marker::PhantomData;
 
-use crate::helpers::not_found_object_info;
+#[cfg(feature = "load-into")]
+use crate::helpers::from_json_in_place;
+use crate::helpers::not_found_error;
 use cosmwasm_std::{from_json, to_json_vec, StdError, StdResult, Storage};
 use std::ops::Deref;
 
@@ -35,6 +37,13 @@ where
 {
     pub fn new(namespace: &[u8], keys: &[&[u8]]) -> Self {
         let l = keys.len();
+        if l == 0 {
+            // empty key (like `()`), the data is stored right at the length-prefixed namespace
+            return Path {
+                storage_key: namespace_with_key(&[namespace], &[]),
+                data: PhantomData,
+            };
+        }
 
         // Combine namespace and all but last keys.
         // This is a single vector allocation with references as elements.
@@ -65,8 +74,26 @@ where
         if let Some(value) = store.get(&self.storage_key) {
             from_json(value)
         } else {
-            let object_info = not_found_object_info::<T>(&self.storage_key);
-            Err(StdError::not_found(object_info))
+            Err(not_found_error::<T>(&self.storage_key))
+        }
+    }
+
+    /// load_into works like `load`, but deserializes into an existing value instead of returning
+    /// a new one. Types supporting in-place deserialization (like `Vec` or `String`) reuse their
+    /// allocations, which saves some work when repeatedly loading large values into the same buffer.
+    /// The content of `out` is unspecified if an error is returned.
+    ///
+    /// Note that this uses a different JSON parser than `load` (`serde_json`), which is slower
+    /// on numbers and may accept values `load` rejects, e.g. floats. Whether this pays off
+    /// depends on the data, so measure it first. See the `Item load` benchmark.
+    ///
+    /// This is only available with the `load-into` feature.
+    #[cfg(feature = "load-into")]
+    pub fn load_into(&self, store: &dyn Storage, out: &mut T) -> StdResult<()> {
+        if let Some(value) = store.get(&self.storage_key) {
+            from_json_in_place(&value, out)
+        } else {
+            Err(not_found_error::<T>(&self.storage_key))
         }
     }
 
@@ -83,6 +110,12 @@ where
         store.get(&self.storage_key).is_some()
     }
 
+    /// Returns the length in bytes of the raw value stored at this key, without parsing it.
+    /// Returns `None` if there is no data at this key.
+    pub fn value_len(&self, store: &dyn Storage) -> Option<usize> {
+        store.get(&self.storage_key).map(|v| v.len())
+    }
+
     /// Loads the data, perform the specified action, and store the result
     /// in the database. This is shorthand for some common sequences, which may be useful.
     ///
@@ -10,7 +10,7 @@ use cosmwasm_std::{Order, Record, StdResult, Storage};
 use std::ops::Deref;
 
 use crate::bound::{PrefixBound, RawBound};
-use crate::de::KeyDeserialize;
+use crate::de::{split_first_key, KeyDeserialize};
 use crate::iter_helpers::{concat, deserialize_kv, deserialize_v, trim};
 use crate::keys::Key;
 use crate::{Bound, Prefixer, PrimaryKey};
@@ -114,9 +114,11 @@ where
     }
 
     /// Clears the prefix, removing the first `limit` elements (or all if `limit == None`).
-    pub fn clear(&self, store: &mut dyn Storage, limit: Option<usize>) {
+    /// Returns how many elements were removed, so clearing in chunks can stop once this is `0`.
+    pub fn clear(&self, store: &mut dyn Storage, limit: Option<usize>) -> usize {
         const TAKE: usize = 10;
         let mut cleared = false;
+        let mut removed = 0;
 
         let mut left_to_clear = limit.unwrap_or(usize::MAX);
 
@@ -133,9 +135,28 @@ where
                 store.remove(path);
             }
             left_to_clear -= paths.len();
+            removed += paths.len();
 
             cleared = paths.len() < take || left_to_clear == 0;
         }
+        removed
+    }
+
+    /// Counts the keys under the prefix within the given bounds, without deserializing anything.
+    pub fn count(
+        &self,
+        store: &dyn Storage,
+        min: Option<Bound<'b, B>>,
+        max: Option<Bound<'b, B>>,
+    ) -> usize {
+        keys_full(
+            store,
+            &self.storage_prefix,
+            min.map(|b| b.to_raw_bound()),
+            max.map(|b| b.to_raw_bound()),
+            Order::Ascending,
+        )
+        .count()
     }
 
     /// Returns `true` if the prefix is empty.
@@ -188,6 +209,56 @@ where
         .map(|k| K::from_vec(k));
         Box::new(mapped)
     }
+
+    /// Returns the smallest and the largest key under this prefix, or `None` if it is empty.
+    /// Both are the same key if the prefix holds a single element.
+    pub fn bounds(&self, store: &dyn Storage) -> StdResult<Option<(K::Output, K::Output)>> {
+        let first =
+            keys_with_prefix(store, &self.storage_prefix, None, None, Order::Ascending).next();
+        let first = match first {
+            Some(first) => first,
+            None => return Ok(None),
+        };
+        let last = keys_with_prefix(store, &self.storage_prefix, None, None, Order::Descending)
+            .next()
+            .unwrap_or_else(|| first.clone());
+        Ok(Some((K::from_vec(first)?, K::from_vec(last)?)))
+    }
+
+    /// Iterates over the distinct values of the first element of the remaining keys, e.g. every
+    /// `B` of `map.sub_prefix(a)` for a `Map<(A, B, C), T>`, exactly once. Values are never read,
+    /// see `Map::sub_keys`.
+    pub fn sub_keys<'a>(
+        &self,
+        store: &'a dyn Storage,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = StdResult<<B::Prefix as KeyDeserialize>::Output>> + 'a>
+    where
+        B::Prefix: KeyDeserialize,
+        <B::Prefix as KeyDeserialize>::Output: 'static,
+    {
+        distinct_prefixes::<B::Prefix>(store, self.storage_prefix.clone(), order)
+    }
+
+    /// Returns the first key-value pair under this prefix, according to the key ordering.
+    pub fn first(&self, store: &dyn Storage) -> StdResult<Option<(K::Output, T)>>
+    where
+        K::Output: 'static,
+    {
+        self.range(store, None, None, Order::Ascending)
+            .next()
+            .transpose()
+    }
+
+    /// Returns the last key-value pair under this prefix, according to the key ordering.
+    pub fn last(&self, store: &dyn Storage) -> StdResult<Option<(K::Output, T)>>
+    where
+        K::Output: 'static,
+    {
+        self.range(store, None, None, Order::Descending)
+            .next()
+            .transpose()
+    }
 }
 
 /// Returns an iterator through all records in storage with the given prefix and
@@ -253,6 +324,52 @@ pub(crate) fn keys_full<'a>(
     store.range_keys(Some(&start), Some(&end), order)
 }
 
+/// Returns an iterator through the distinct first `P::KEY_ELEMS` elements of the keys under
+/// the given namespace. After each of them, the remaining keys sharing it are skipped by
+/// starting a new range after them, so this costs one storage read per distinct value.
+pub(crate) fn distinct_prefixes<'a, P>(
+    store: &'a dyn Storage,
+    namespace: Vec<u8>,
+    order: Order,
+) -> Box<dyn Iterator<Item = StdResult<P::Output>> + 'a>
+where
+    P: KeyDeserialize,
+    P::Output: 'static,
+{
+    let mut start = namespace.clone();
+    let mut end = increment_last_byte(&namespace);
+    let mut done = false;
+
+    let iter = std::iter::from_fn(move || {
+        if done {
+            return None;
+        }
+        let full_key = store.range_keys(Some(&start), Some(&end), order).next()?;
+        let (prefix, remainder) = match split_first_key(P::KEY_ELEMS, &full_key[namespace.len()..])
+        {
+            Ok(split) => split,
+            Err(e) => {
+                done = true;
+                return Some(Err(e));
+            }
+        };
+
+        // all keys starting with the raw prefix are skipped by the next range
+        let raw_prefix = &full_key[..full_key.len() - remainder.len()];
+        if raw_prefix.len() == namespace.len() {
+            // keys without a prefix
+            done = true;
+        } else {
+            match order {
+                Order::Ascending => start = increment_last_byte(raw_prefix),
+                Order::Descending => end = raw_prefix.to_vec(),
+            }
+        }
+        Some(P::from_vec(prefix))
+    });
+    Box::new(iter)
+}
+
 fn calc_start_bound(namespace: &[u8], bound: Option<RawBound>) -> Vec<u8> {
     match bound {
         None => namespace.to_vec(),
@@ -504,28 +621,28 @@ mod test {
         }
 
         // clearing less than `TAKE` should work
-        prefix.clear(&mut store, Some(1));
+        assert_eq!(prefix.clear(&mut store, Some(1)), 1);
         assert_eq!(
             prefix.range(&store, None, None, Order::Ascending).count(),
             99
         );
 
         // clearing more than `TAKE` should work
-        prefix.clear(&mut store, Some(12));
+        assert_eq!(prefix.clear(&mut store, Some(12)), 12);
         assert_eq!(
             prefix.range(&store, None, None, Order::Ascending).count(),
             99 - 12
         );
 
         // clearing an exact multiple of `TAKE` should work
-        prefix.clear(&mut store, Some(20));
+        assert_eq!(prefix.clear(&mut store, Some(20)), 20);
         assert_eq!(
             prefix.range(&store, None, None, Order::Ascending).count(),
             99 - 12 - 20
         );
 
         // clearing more than available should work
-        prefix.clear(&mut store, Some(1000));
+        assert_eq!(prefix.clear(&mut store, Some(1000)), 99 - 12 - 20);
         assert_eq!(
             prefix.range(&store, None, None, Order::Ascending).count(),
             0
@@ -566,6 +683,32 @@ mod test {
         );
     }
 
+    #[test]
+    fn prefix_clear_in_chunks() {
+        let mut store = MockStorage::new();
+        // manually create this - not testing nested prefixes here
+        let prefix: Prefix<Vec<u8>, u64> = Prefix {
+            storage_prefix: b"foo".to_vec(),
+            data: PhantomData,
+        };
+
+        for i in 0..95u32 {
+            store.set(format!("foo{}", i).as_bytes(), b"1");
+        }
+
+        let mut counts = vec![];
+        loop {
+            let removed = prefix.clear(&mut store, Some(10));
+            if removed == 0 {
+                break;
+            }
+            counts.push(removed);
+        }
+        assert_eq!(counts, vec![10, 10, 10, 10, 10, 10, 10, 10, 10, 5]);
+        assert_eq!(counts.iter().sum::<usize>(), 95);
+        assert!(prefix.is_empty(&store));
+    }
+
     #[test]
     fn is_empty_works() {
         // manually create this - not testing nested prefixes here
@@ -584,6 +727,34 @@ mod test {
         assert!(!prefix.is_empty(&storage));
     }
 
+    #[test]
+    fn bounds_works() {
+        // manually create this - not testing nested prefixes here
+        let prefix: Prefix<Vec<u8>, u64> = Prefix {
+            storage_prefix: b"foo".to_vec(),
+            data: PhantomData,
+        };
+
+        let mut storage = MockStorage::new();
+        assert_eq!(prefix.bounds(&storage).unwrap(), None);
+
+        storage.set(b"fookey2", b"2");
+        // outside of the prefix
+        storage.set(b"fo", b"0");
+        storage.set(b"fop", b"0");
+        assert_eq!(
+            prefix.bounds(&storage).unwrap(),
+            Some((b"key2".to_vec(), b"key2".to_vec()))
+        );
+
+        storage.set(b"fookey1", b"1");
+        storage.set(b"fookey3", b"3");
+        assert_eq!(
+            prefix.bounds(&storage).unwrap(),
+            Some((b"key1".to_vec(), b"key3".to_vec()))
+        );
+    }
+
     #[test]
     fn keys_raw_works() {
         // manually create this - not testing nested prefixes here
@@ -86,16 +86,68 @@ impl<T> SnapshotItem<T> {
         self.snapshots.remove_checkpoint(store, height)
     }
 
+    /// Adds a checkpoint at every height from `from` to `to` (inclusive), or only at the first
+    /// `limit` of them. Returns the last height processed, or `None` if there was none.
+    pub fn add_checkpoints(
+        &self,
+        store: &mut dyn Storage,
+        from: u64,
+        to: u64,
+        limit: Option<u64>,
+    ) -> StdResult<Option<u64>> {
+        self.snapshots.add_checkpoints(store, from, to, limit)
+    }
+
+    /// Removes a checkpoint at every height from `from` to `to` (inclusive), or only at the first
+    /// `limit` of them. Returns the last height processed, or `None` if there was none.
+    pub fn remove_checkpoints(
+        &self,
+        store: &mut dyn Storage,
+        from: u64,
+        to: u64,
+        limit: Option<u64>,
+    ) -> StdResult<Option<u64>> {
+        self.snapshots.remove_checkpoints(store, from, to, limit)
+    }
+
     pub fn changelog(&self) -> Map<u64, ChangeSet<T>> {
         // Build and return a compatible Map with the proper key type
         Map::new_dyn(self.changelog_namespace.clone())
     }
+
+    /// Returns the strategy the item was created with.
+    pub fn strategy(&self) -> Strategy {
+        self.snapshots.strategy
+    }
 }
 
 impl<T> SnapshotItem<T>
 where
     T: Serialize + DeserializeOwned + Clone,
 {
+    /// Removes a checkpoint like `remove_checkpoint`. When the checkpoint is removed for good,
+    /// changelog entries that can no longer be read at any remaining checkpoint are removed too.
+    /// This scans the whole changelog.
+    pub fn remove_checkpoint_safe(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        self.snapshots.remove_checkpoint_safe(store, height)
+    }
+
+    /// Removes up to `limit` changelog entries and checkpoints (or all if `limit == None`) below
+    /// `up_to_height`, and returns how many were removed. Queries at `up_to_height` or later
+    /// keep working, earlier ones fail or return later values.
+    ///
+    /// Note that `limit` only bounds the removals, not the reads: every call scans the changelog
+    /// from the start until it has found `limit` entries to remove, including all the entries
+    /// kept at `up_to_height` or later, so batched calls read the kept entries again each time.
+    pub fn prune(
+        &self,
+        store: &mut dyn Storage,
+        up_to_height: u64,
+        limit: Option<usize>,
+    ) -> StdResult<usize> {
+        self.snapshots.prune(store, up_to_height, limit)
+    }
+
     /// load old value and store changelog
     fn write_change(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
         // if there is already data in the changelog for this block, do not write more
@@ -110,6 +162,8 @@ where
     pub fn save(&self, store: &mut dyn Storage, data: &T, height: u64) -> StdResult<()> {
         if self.snapshots.should_checkpoint(store, &())? {
             self.write_change(store, height)?;
+            self.snapshots
+                .write_new_value(store, (), height, Some(data))?;
         }
         self.primary.save(store, data)
     }
@@ -117,6 +171,7 @@ where
     pub fn remove(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
         if self.snapshots.should_checkpoint(store, &())? {
             self.write_change(store, height)?;
+            self.snapshots.write_new_value(store, (), height, None)?;
         }
         self.primary.remove(store);
         Ok(())
@@ -250,6 +305,13 @@ mod tests {
         assert!(item.may_load_at_height(storage, height).is_err());
     }
 
+    #[test]
+    fn strategy_is_exposed() {
+        assert_eq!(NEVER.strategy(), Strategy::Never);
+        assert_eq!(EVERY.strategy(), Strategy::EveryBlock);
+        assert_eq!(SELECT.strategy(), Strategy::Selected);
+    }
+
     #[test]
     fn never_works_like_normal_item() {
         let mut storage = MockStorage::new();
@@ -344,10 +406,34 @@ mod tests {
         assert_eq!(
             all,
             vec![
-                (1, ChangeSet { old: None }),
-                (2, ChangeSet { old: Some(5) }),
-                (3, ChangeSet { old: Some(7) }),
-                (4, ChangeSet { old: Some(8) })
+                (
+                    1,
+                    ChangeSet {
+                        old: None,
+                        new: None
+                    }
+                ),
+                (
+                    2,
+                    ChangeSet {
+                        old: Some(5),
+                        new: None
+                    }
+                ),
+                (
+                    3,
+                    ChangeSet {
+                        old: Some(7),
+                        new: None
+                    }
+                ),
+                (
+                    4,
+                    ChangeSet {
+                        old: Some(8),
+                        new: None
+                    }
+                )
             ]
         );
 
@@ -358,6 +444,15 @@ mod tests {
             .collect();
         let all = all.unwrap();
         assert_eq!(1, all.len());
-        assert_eq!(all, vec![(4, ChangeSet { old: Some(8) }),]);
+        assert_eq!(
+            all,
+            vec![(
+                4,
+                ChangeSet {
+                    old: Some(8),
+                    new: None
+                }
+            ),]
+        );
     }
 }
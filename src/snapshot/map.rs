@@ -1,9 +1,10 @@
 use serde::de::DeserializeOwned;
 use serde::Serialize;
+use std::collections::BTreeMap;
 
-use cosmwasm_std::{StdError, StdResult, Storage};
+use cosmwasm_std::{Env, Order, Record, StdError, StdResult, Storage};
 
-use crate::bound::PrefixBound;
+use crate::bound::{PrefixBound, RawBound};
 use crate::de::KeyDeserialize;
 use crate::iter_helpers::deserialize_kv;
 use crate::keys::PrimaryKey;
@@ -14,6 +15,9 @@ use crate::prefix::{namespaced_prefix_range, Prefix};
 use crate::snapshot::{ChangeSet, Snapshot};
 use crate::{Bound, Prefixer, Strategy};
 
+type HistoricalRange<'c, K, T> =
+    Box<dyn Iterator<Item = StdResult<(<K as KeyDeserialize>::Output, T)>> + 'c>;
+
 /// Map that maintains a snapshots of one or more checkpoints.
 /// We can query historical data as well as current state.
 /// What data is snapshotted depends on the Strategy.
@@ -85,6 +89,11 @@ impl<K, T> SnapshotMap<K, T> {
     pub fn changelog(&self) -> &Map<(K, u64), ChangeSet<T>> {
         &self.snapshots.changelog
     }
+
+    /// Returns the strategy the map was created with.
+    pub fn strategy(&self) -> Strategy {
+        self.snapshots.strategy
+    }
 }
 
 impl<'a, K, T> SnapshotMap<K, T>
@@ -99,6 +108,30 @@ where
     pub fn remove_checkpoint(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
         self.snapshots.remove_checkpoint(store, height)
     }
+
+    /// Adds a checkpoint at every height from `from` to `to` (inclusive), or only at the first
+    /// `limit` of them. Returns the last height processed, or `None` if there was none.
+    pub fn add_checkpoints(
+        &self,
+        store: &mut dyn Storage,
+        from: u64,
+        to: u64,
+        limit: Option<u64>,
+    ) -> StdResult<Option<u64>> {
+        self.snapshots.add_checkpoints(store, from, to, limit)
+    }
+
+    /// Removes a checkpoint at every height from `from` to `to` (inclusive), or only at the first
+    /// `limit` of them. Returns the last height processed, or `None` if there was none.
+    pub fn remove_checkpoints(
+        &self,
+        store: &mut dyn Storage,
+        from: u64,
+        to: u64,
+        limit: Option<u64>,
+    ) -> StdResult<Option<u64>> {
+        self.snapshots.remove_checkpoints(store, from, to, limit)
+    }
 }
 
 impl<'a, K, T> SnapshotMap<K, T>
@@ -106,6 +139,29 @@ where
     T: Serialize + DeserializeOwned + Clone,
     K: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
 {
+    /// Removes a checkpoint like `remove_checkpoint`. When the checkpoint is removed for good,
+    /// changelog entries that can no longer be read at any remaining checkpoint are removed too.
+    /// This scans the whole changelog.
+    pub fn remove_checkpoint_safe(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        self.snapshots.remove_checkpoint_safe(store, height)
+    }
+
+    /// Removes up to `limit` changelog entries and checkpoints (or all if `limit == None`) below
+    /// `up_to_height`, and returns how many were removed. Queries at `up_to_height` or later
+    /// keep working, earlier ones fail or return later values.
+    ///
+    /// Note that `limit` only bounds the removals, not the reads: every call scans the changelog
+    /// from the start until it has found `limit` entries to remove, including all the entries
+    /// kept at `up_to_height` or later, so batched calls read the kept entries again each time.
+    pub fn prune(
+        &self,
+        store: &mut dyn Storage,
+        up_to_height: u64,
+        limit: Option<usize>,
+    ) -> StdResult<usize> {
+        self.snapshots.prune(store, up_to_height, limit)
+    }
+
     pub fn key(&self, k: K) -> Path<T> {
         self.primary.key(k)
     }
@@ -126,15 +182,51 @@ where
     }
 
     pub fn save(&self, store: &mut dyn Storage, k: K, data: &T, height: u64) -> StdResult<()> {
-        if self.snapshots.should_checkpoint(store, &k)? {
+        let archive = self.snapshots.should_checkpoint(store, &k)?;
+        self.save_with_strategy(store, k, data, height, archive)
+    }
+
+    pub fn remove(&self, store: &mut dyn Storage, k: K, height: u64) -> StdResult<()> {
+        let archive = self.snapshots.should_checkpoint(store, &k)?;
+        self.remove_with_strategy(store, k, height, archive)
+    }
+
+    /// Works like `save`, but the caller decides whether this write is recorded in the changelog,
+    /// instead of the configured `Strategy`.
+    ///
+    /// Skipping the changelog makes historical queries inaccurate for the key: a query at a height
+    /// before a skipped write returns the value written by it, unless another change was
+    /// recorded in between. This is the same as `Selected` without a checkpoint, except that
+    /// the query doesn't fail. Queries still require a checkpoint as per the configured strategy.
+    pub fn save_with_strategy(
+        &self,
+        store: &mut dyn Storage,
+        k: K,
+        data: &T,
+        height: u64,
+        archive: bool,
+    ) -> StdResult<()> {
+        if archive {
             self.write_change(store, k.clone(), height)?;
+            self.snapshots
+                .write_new_value(store, k.clone(), height, Some(data))?;
         }
         self.primary.save(store, k, data)
     }
 
-    pub fn remove(&self, store: &mut dyn Storage, k: K, height: u64) -> StdResult<()> {
-        if self.snapshots.should_checkpoint(store, &k)? {
+    /// Works like `remove`, but the caller decides whether this write is recorded in the changelog,
+    /// instead of the configured `Strategy`. See [`SnapshotMap::save_with_strategy`].
+    pub fn remove_with_strategy(
+        &self,
+        store: &mut dyn Storage,
+        k: K,
+        height: u64,
+        archive: bool,
+    ) -> StdResult<()> {
+        if archive {
             self.write_change(store, k.clone(), height)?;
+            self.snapshots
+                .write_new_value(store, k.clone(), height, None)?;
         }
         self.primary.remove(store, k);
         Ok(())
@@ -173,6 +265,95 @@ where
         self.snapshots.assert_checkpointed(store, height)
     }
 
+    /// Returns all the entries as they were at the beginning of the given height, sorted by raw key.
+    /// Keys are relative to the primary namespace, like in `range_raw`.
+    ///
+    /// Note that this reads the whole changelog and the whole primary map, so it is only
+    /// suitable for small maps or off-chain queries.
+    pub fn range_raw_at_height(
+        &self,
+        store: &dyn Storage,
+        height: u64,
+    ) -> StdResult<Vec<Record<T>>> {
+        self.snapshots.assert_checkpointed(store, height)?;
+        let full_history = self.snapshots.strategy == Strategy::FullHistory;
+
+        // the historical value of every key changed since the given height
+        // (or before it, when the new values are recorded)
+        let mut values = BTreeMap::new();
+        for change in self
+            .changelog()
+            .range_raw(store, None, None, Order::Ascending)
+        {
+            let (key, changeset) = change?;
+            let (pk, changed_at) = split_changelog_key(&key)?;
+            if changed_at < height {
+                // changes come in ascending height order, so the last one before the height wins
+                if full_history {
+                    values.insert(pk, changeset.new);
+                }
+            } else {
+                values.entry(pk).or_insert(changeset.old);
+            }
+        }
+        // keys without any relevant change still have their current value
+        for item in self.primary.range_raw(store, None, None, Order::Ascending) {
+            let (pk, value) = item?;
+            values.entry(pk).or_insert(Some(value));
+        }
+
+        Ok(values
+            .into_iter()
+            .filter_map(|(pk, value)| value.map(|v| (pk, v)))
+            .collect())
+    }
+
+    /// Iterates over the entries between `min` and `max` as they were at the beginning of
+    /// the given height. Returns an error if there is no checkpoint at that height.
+    ///
+    /// This is built on [`SnapshotMap::range_raw_at_height`], so it has the same cost,
+    /// regardless of the bounds.
+    pub fn range_at_height<'c>(
+        &self,
+        store: &dyn Storage,
+        height: u64,
+        min: Option<Bound<'a, K>>,
+        max: Option<Bound<'a, K>>,
+        order: Order,
+    ) -> StdResult<HistoricalRange<'c, K, T>>
+    where
+        T: 'c,
+        K::Output: 'c,
+    {
+        let min = min.map(|b| b.to_raw_bound());
+        let max = max.map(|b| b.to_raw_bound());
+        let in_range = move |pk: &Vec<u8>| {
+            let above_min = match &min {
+                Some(RawBound::Inclusive(b)) => pk >= b,
+                Some(RawBound::Exclusive(b)) => pk > b,
+                None => true,
+            };
+            let below_max = match &max {
+                Some(RawBound::Inclusive(b)) => pk <= b,
+                Some(RawBound::Exclusive(b)) => pk < b,
+                None => true,
+            };
+            above_min && below_max
+        };
+
+        let entries = self
+            .range_raw_at_height(store, height)?
+            .into_iter()
+            .filter(move |(pk, _)| in_range(pk));
+        let entries: Box<dyn Iterator<Item = Record<T>>> = match order {
+            Order::Ascending => Box::new(entries),
+            Order::Descending => Box::new(entries.rev()),
+        };
+        Ok(Box::new(
+            entries.map(|(pk, v)| K::from_vec(pk).map(|k| (k, v))),
+        ))
+    }
+
     /// Loads the data, perform the specified action, and store the result
     /// in the database. This is shorthand for some common sequences, which may be useful.
     ///
@@ -195,6 +376,62 @@ where
         self.save(store, k, &output, height)?;
         Ok(output)
     }
+
+    /// Works like `save`, at the height of the current block.
+    pub fn save_env(&self, store: &mut dyn Storage, env: &Env, k: K, data: &T) -> StdResult<()> {
+        self.save(store, k, data, env.block.height)
+    }
+
+    /// Works like `remove`, at the height of the current block.
+    pub fn remove_env(&self, store: &mut dyn Storage, env: &Env, k: K) -> StdResult<()> {
+        self.remove(store, k, env.block.height)
+    }
+
+    /// Works like `update`, at the height of the current block.
+    pub fn update_env<A, E>(
+        &self,
+        store: &mut dyn Storage,
+        env: &Env,
+        k: K,
+        action: A,
+    ) -> Result<T, E>
+    where
+        A: FnOnce(Option<T>) -> Result<T, E>,
+        E: From<StdError>,
+    {
+        self.update(store, k, env.block.height, action)
+    }
+}
+
+/// Splits a raw changelog key, relative to the changelog namespace, into the corresponding key
+/// relative to the primary namespace and the height of the change.
+/// Changelog keys are `(K, u64)`, so every element of `K` is length-prefixed, while the last
+/// element of a primary key is not.
+fn split_changelog_key(key: &[u8]) -> StdResult<(Vec<u8>, u64)> {
+    let invalid = || StdError::generic_err("Invalid changelog key");
+    if key.len() < 8 {
+        return Err(invalid());
+    }
+    let (elems, height) = key.split_at(key.len() - 8);
+    let height = u64::from_be_bytes(height.try_into().map_err(|_| invalid())?);
+
+    // find the start of the last element
+    let mut pos = 0;
+    let mut last = 0;
+    while pos < elems.len() {
+        if elems.len() < pos + 2 {
+            return Err(invalid());
+        }
+        last = pos;
+        pos += 2 + u16::from_be_bytes([elems[pos], elems[pos + 1]]) as usize;
+    }
+    if pos != elems.len() || elems.is_empty() {
+        return Err(invalid());
+    }
+
+    let mut pk = elems[..last].to_vec();
+    pk.extend_from_slice(&elems[last + 2..]);
+    Ok((pk, height))
 }
 
 // short-cut for simple keys, rather than .prefix(()).range_raw(...)
@@ -332,6 +569,8 @@ mod tests {
         "select__change",
         Strategy::Selected,
     );
+    const FULL: TestMap =
+        SnapshotMap::new("full", "full__check", "full__change", Strategy::FullHistory);
 
     // Fills a map &[u8] -> u64 with the following writes:
     // 1: A = 5
@@ -423,6 +662,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn strategy_is_exposed() {
+        assert_eq!(NEVER.strategy(), Strategy::Never);
+        assert_eq!(EVERY.strategy(), Strategy::EveryBlock);
+        assert_eq!(SELECT.strategy(), Strategy::Selected);
+        assert_eq!(FULL.strategy(), Strategy::FullHistory);
+    }
+
+    #[test]
+    fn env_variants_use_block_height() {
+        use cosmwasm_std::testing::mock_env;
+
+        let mut storage = MockStorage::new();
+        let mut env = mock_env();
+
+        env.block.height = 3;
+        EVERY.save_env(&mut storage, &env, "A", &5).unwrap();
+        EVERY.save_env(&mut storage, &env, "B", &7).unwrap();
+
+        env.block.height = 5;
+        EVERY
+            .update_env(&mut storage, &env, "A", |v| -> StdResult<_> {
+                Ok(v.unwrap_or_default() + 1)
+            })
+            .unwrap();
+        EVERY.remove_env(&mut storage, &env, "B").unwrap();
+
+        assert_eq!(EVERY.may_load(&storage, "A").unwrap(), Some(6));
+        assert_eq!(EVERY.may_load(&storage, "B").unwrap(), None);
+
+        // the changes are recorded at the given heights
+        let at = |k, height| EVERY.may_load_at_height(&storage, k, height).unwrap();
+        assert_eq!(at("A", 3), None);
+        assert_eq!(at("A", 4), Some(5));
+        assert_eq!(at("A", 5), Some(5));
+        assert_eq!(at("A", 6), Some(6));
+        assert_eq!(at("B", 5), Some(7));
+        assert_eq!(at("B", 6), None);
+    }
+
     #[test]
     fn never_works_like_normal_map() {
         let mut storage = MockStorage::new();
@@ -459,6 +738,319 @@ mod tests {
         assert_missing_checkpoint(&NEVER, &storage, 5);
     }
 
+    #[test]
+    fn remove_checkpoint_safe_prunes_orphaned_changelog() {
+        let mut storage = MockStorage::new();
+
+        SELECT.save(&mut storage, "A", &1, 1).unwrap();
+        SELECT.add_checkpoint(&mut storage, 2).unwrap();
+        SELECT.save(&mut storage, "A", &2, 2).unwrap();
+        SELECT.add_checkpoint(&mut storage, 4).unwrap();
+        SELECT.save(&mut storage, "A", &3, 4).unwrap();
+        SELECT.save(&mut storage, "B", &1, 4).unwrap();
+        assert!(SELECT.changelog().has(&storage, ("A", 4)));
+
+        // A at 4 only served checkpoint 4, while B at 4 also holds the value at 2
+        SELECT.remove_checkpoint_safe(&mut storage, 4).unwrap();
+        assert!(!SELECT.changelog().has(&storage, ("A", 4)));
+        assert!(SELECT.changelog().has(&storage, ("A", 2)));
+        assert!(SELECT.changelog().has(&storage, ("B", 4)));
+
+        assert!(SELECT.may_load_at_height(&storage, "A", 4).is_err());
+        assert_eq!(
+            Some(1),
+            SELECT.may_load_at_height(&storage, "A", 2).unwrap()
+        );
+        assert_eq!(None, SELECT.may_load_at_height(&storage, "B", 2).unwrap());
+        assert_eq!(Some(3), SELECT.may_load(&storage, "A").unwrap());
+
+        // nothing is pruned while the checkpoint is still referenced
+        SELECT.add_checkpoint(&mut storage, 2).unwrap();
+        SELECT.remove_checkpoint_safe(&mut storage, 2).unwrap();
+        assert_eq!(
+            Some(1),
+            SELECT.may_load_at_height(&storage, "A", 2).unwrap()
+        );
+
+        // without checkpoints, no changelog is reachable
+        SELECT.remove_checkpoint_safe(&mut storage, 2).unwrap();
+        assert_eq!(
+            SELECT
+                .changelog()
+                .keys_raw(&storage, None, None, Order::Ascending)
+                .count(),
+            0
+        );
+        assert_eq!(Some(3), SELECT.may_load(&storage, "A").unwrap());
+        assert_eq!(Some(1), SELECT.may_load(&storage, "B").unwrap());
+
+        // other strategies read their changelog without checkpoints, so it is kept
+        EVERY.save(&mut storage, "A", &1, 1).unwrap();
+        EVERY.add_checkpoint(&mut storage, 2).unwrap();
+        EVERY.save(&mut storage, "A", &2, 2).unwrap();
+        EVERY.remove_checkpoint_safe(&mut storage, 2).unwrap();
+        assert_eq!(Some(1), EVERY.may_load_at_height(&storage, "A", 2).unwrap());
+    }
+
+    #[test]
+    fn bulk_checkpoints_work() {
+        let mut storage = MockStorage::new();
+
+        assert_eq!(
+            SELECT.add_checkpoints(&mut storage, 5, 4, None).unwrap(),
+            None
+        );
+        assert_eq!(
+            SELECT.add_checkpoints(&mut storage, 1, 4, Some(0)).unwrap(),
+            None
+        );
+
+        // paginated
+        assert_eq!(
+            SELECT
+                .add_checkpoints(&mut storage, 1, 10, Some(4))
+                .unwrap(),
+            Some(4)
+        );
+        assert!(SELECT.assert_checkpointed(&storage, 4).is_ok());
+        assert!(SELECT.assert_checkpointed(&storage, 5).is_err());
+        assert_eq!(
+            SELECT
+                .add_checkpoints(&mut storage, 5, 10, Some(100))
+                .unwrap(),
+            Some(10)
+        );
+        for height in 1..=10 {
+            assert!(SELECT.assert_checkpointed(&storage, height).is_ok());
+        }
+        assert!(SELECT.assert_checkpointed(&storage, 11).is_err());
+
+        // counters are incremented, so overlapping ranges need as many removals
+        SELECT.add_checkpoints(&mut storage, 3, 4, None).unwrap();
+        assert_eq!(
+            SELECT
+                .remove_checkpoints(&mut storage, 1, 10, None)
+                .unwrap(),
+            Some(10)
+        );
+        assert!(SELECT.assert_checkpointed(&storage, 2).is_err());
+        assert!(SELECT.assert_checkpointed(&storage, 3).is_ok());
+        assert!(SELECT.assert_checkpointed(&storage, 4).is_ok());
+        assert!(SELECT.assert_checkpointed(&storage, 5).is_err());
+
+        assert_eq!(
+            SELECT.remove_checkpoints(&mut storage, 3, 3, None).unwrap(),
+            Some(3)
+        );
+        assert!(SELECT.assert_checkpointed(&storage, 3).is_err());
+        assert!(SELECT.assert_checkpointed(&storage, 4).is_ok());
+
+        // the upper end of the range is not overflowed
+        assert_eq!(
+            SELECT
+                .add_checkpoints(&mut storage, u64::MAX - 1, u64::MAX, Some(u64::MAX))
+                .unwrap(),
+            Some(u64::MAX)
+        );
+    }
+
+    #[test]
+    fn prune_keeps_history_from_horizon() {
+        let mut storage = MockStorage::new();
+        init_data(&EVERY, &mut storage);
+
+        // paginated, 6 changes and the checkpoint at 3
+        assert_eq!(EVERY.prune(&mut storage, 5, Some(4)).unwrap(), 4);
+        assert_eq!(EVERY.prune(&mut storage, 5, Some(4)).unwrap(), 3);
+        assert_eq!(EVERY.prune(&mut storage, 5, None).unwrap(), 0);
+
+        let heights = EVERY
+            .changelog()
+            .keys(&storage, None, None, Order::Ascending)
+            .map(|k| k.unwrap().1)
+            .collect::<Vec<_>>();
+        assert_eq!(heights, vec![5, 5]);
+        assert_final_values(&EVERY, &storage);
+        assert_values_at_height(&EVERY, &storage, 5, VALUES_START_5);
+        // earlier heights fall back to the oldest known values
+        assert_eq!(EVERY.may_load_at_height(&storage, "B", 3).unwrap(), None);
+
+        let mut storage = MockStorage::new();
+        init_data(&SELECT, &mut storage);
+        SELECT.add_checkpoint(&mut storage, 6).unwrap();
+        assert_eq!(SELECT.prune(&mut storage, 5, None).unwrap(), 4);
+        assert_missing_checkpoint(&SELECT, &storage, 3);
+        assert!(SELECT.may_load_at_height(&storage, "A", 6).is_ok());
+        assert_final_values(&SELECT, &storage);
+    }
+
+    #[test]
+    fn range_raw_at_height_works() {
+        let mut storage = MockStorage::new();
+        init_data(&EVERY, &mut storage);
+        init_data(&FULL, &mut storage);
+        init_data(&SELECT, &mut storage);
+
+        let expected = |values: &[(&str, Option<u64>)]| -> Vec<_> {
+            values
+                .iter()
+                .filter_map(|(k, v)| v.map(|v| (k.as_bytes().to_vec(), v)))
+                .collect()
+        };
+        for map in [&EVERY, &FULL] {
+            assert_eq!(
+                map.range_raw_at_height(&storage, 3).unwrap(),
+                expected(VALUES_START_3)
+            );
+            assert_eq!(
+                map.range_raw_at_height(&storage, 5).unwrap(),
+                expected(VALUES_START_5)
+            );
+            assert_eq!(
+                map.range_raw_at_height(&storage, 6).unwrap(),
+                expected(FINAL_VALUES)
+            );
+        }
+        assert_eq!(
+            SELECT.range_raw_at_height(&storage, 3).unwrap(),
+            expected(VALUES_START_3)
+        );
+        assert!(SELECT.range_raw_at_height(&storage, 5).is_err());
+        assert!(NEVER.range_raw_at_height(&storage, 3).is_err());
+
+        // composite keys are relative to the primary namespace
+        let mut storage = MockStorage::new();
+        init_data_composite_key(&EVERY_COMPOSITE_KEY, &mut storage);
+        assert_eq!(
+            EVERY_COMPOSITE_KEY
+                .range_raw_at_height(&storage, 5)
+                .unwrap(),
+            vec![(("A", "B").joined_key(), 8), (("B", "B").joined_key(), 13),]
+        );
+    }
+
+    #[test]
+    fn range_at_height_works() {
+        let mut storage = MockStorage::new();
+        init_data(&EVERY, &mut storage);
+        init_data(&FULL, &mut storage);
+
+        let range = |map: &TestMap,
+                     height: u64,
+                     min: Option<Bound<&str>>,
+                     max: Option<Bound<&str>>,
+                     order: Order| {
+            map.range_at_height(&storage, height, min, max, order)
+                .unwrap()
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap()
+        };
+        let expected = |values: &[(&str, Option<u64>)]| -> Vec<_> {
+            values
+                .iter()
+                .filter_map(|(k, v)| v.map(|v| (k.to_string(), v)))
+                .collect()
+        };
+
+        for map in [&EVERY, &FULL] {
+            for (height, values) in [(3, VALUES_START_3), (5, VALUES_START_5), (6, FINAL_VALUES)] {
+                assert_eq!(
+                    range(map, height, None, None, Order::Ascending),
+                    expected(values)
+                );
+
+                // consistent with loading the keys one by one
+                for (k, v) in values {
+                    assert_eq!(map.may_load_at_height(&storage, k, height).unwrap(), *v);
+                }
+            }
+
+            assert_eq!(
+                range(map, 3, None, None, Order::Descending),
+                vec![("B".to_string(), 7), ("A".to_string(), 5)]
+            );
+            assert_eq!(
+                range(
+                    map,
+                    5,
+                    Some(Bound::exclusive("A")),
+                    Some(Bound::inclusive("D")),
+                    Order::Ascending
+                ),
+                vec![("C".to_string(), 13)]
+            );
+            assert_eq!(
+                range(
+                    map,
+                    5,
+                    Some(Bound::inclusive("A")),
+                    Some(Bound::exclusive("C")),
+                    Order::Descending
+                ),
+                vec![("A".to_string(), 8)]
+            );
+        }
+
+        // no checkpoint
+        assert!(SELECT
+            .range_at_height(&storage, 3, None, None, Order::Ascending)
+            .is_err());
+
+        // composite keys are deserialized
+        let mut storage = MockStorage::new();
+        init_data_composite_key(&EVERY_COMPOSITE_KEY, &mut storage);
+        let entries = EVERY_COMPOSITE_KEY
+            .range_at_height(&storage, 3, None, None, Order::Ascending)
+            .unwrap()
+            .collect::<StdResult<Vec<_>>>()
+            .unwrap();
+        assert_eq!(
+            entries,
+            vec![
+                (("A".to_string(), "B".to_string()), 5),
+                (("B".to_string(), "A".to_string()), 7)
+            ]
+        );
+    }
+
+    #[test]
+    fn archive_selectable_per_write() {
+        let mut storage = MockStorage::new();
+
+        EVERY
+            .save_with_strategy(&mut storage, "A", &1, 1, true)
+            .unwrap();
+        EVERY
+            .save_with_strategy(&mut storage, "A", &2, 2, false)
+            .unwrap();
+        EVERY
+            .save_with_strategy(&mut storage, "A", &3, 3, true)
+            .unwrap();
+        EVERY
+            .remove_with_strategy(&mut storage, "A", 4, true)
+            .unwrap();
+
+        assert!(EVERY.changelog().has(&storage, ("A", 1)));
+        assert!(!EVERY.changelog().has(&storage, ("A", 2)));
+        assert!(EVERY.changelog().has(&storage, ("A", 3)));
+        assert!(EVERY.changelog().has(&storage, ("A", 4)));
+
+        assert_eq!(None, EVERY.may_load_at_height(&storage, "A", 1).unwrap());
+        // the write at 2 was not archived, so the value at the beginning of 2 is lost
+        assert_eq!(Some(2), EVERY.may_load_at_height(&storage, "A", 2).unwrap());
+        assert_eq!(Some(2), EVERY.may_load_at_height(&storage, "A", 3).unwrap());
+        assert_eq!(Some(3), EVERY.may_load_at_height(&storage, "A", 4).unwrap());
+        assert_eq!(None, EVERY.may_load_at_height(&storage, "A", 5).unwrap());
+
+        // archiving with a strategy that never checkpoints records the change,
+        // but historical queries are still refused
+        NEVER
+            .save_with_strategy(&mut storage, "A", &1, 1, true)
+            .unwrap();
+        assert!(NEVER.changelog().has(&storage, ("A", 1)));
+        assert!(NEVER.may_load_at_height(&storage, "A", 1).is_err());
+    }
+
     #[test]
     fn handle_multiple_writes_in_one_block() {
         let mut storage = MockStorage::new();
@@ -500,6 +1092,107 @@ mod tests {
         );
     }
 
+    #[test]
+    fn full_history_reads_like_every_block() {
+        let mut storage = MockStorage::new();
+        init_data(&EVERY, &mut storage);
+        init_data(&FULL, &mut storage);
+        assert_final_values(&FULL, &storage);
+        assert_values_at_height(&FULL, &storage, 3, VALUES_START_3);
+        assert_values_at_height(&FULL, &storage, 5, VALUES_START_5);
+
+        for height in 0..=6 {
+            for k in ["A", "B", "C", "D"] {
+                assert_eq!(
+                    EVERY.may_load_at_height(&storage, k, height).unwrap(),
+                    FULL.may_load_at_height(&storage, k, height).unwrap(),
+                    "key {} at height {}",
+                    k,
+                    height
+                );
+            }
+        }
+
+        // every block only keeps the old value, full history keeps both
+        assert_eq!(
+            EVERY.changelog().load(&storage, ("A", 3)).unwrap(),
+            ChangeSet {
+                old: Some(5),
+                new: None
+            }
+        );
+        assert_eq!(
+            FULL.changelog().load(&storage, ("A", 3)).unwrap(),
+            ChangeSet {
+                old: Some(5),
+                new: Some(8)
+            }
+        );
+        assert_eq!(
+            FULL.changelog().load(&storage, ("A", 5)).unwrap(),
+            ChangeSet {
+                old: Some(8),
+                new: None
+            }
+        );
+    }
+
+    #[test]
+    fn full_history_reads_last_change_before_height() {
+        let mut storage = MockStorage::new();
+
+        FULL.save(&mut storage, "A", &5, 1).unwrap();
+        FULL.save(&mut storage, "A", &9, 3).unwrap();
+        FULL.save(&mut storage, "A", &12, 3).unwrap();
+        FULL.remove(&mut storage, "A", 6).unwrap();
+
+        // the value at a given height is found from the last change before it,
+        // without looking at any later change
+        FULL.changelog().remove(&mut storage, ("A", 6));
+        assert_eq!(None, FULL.may_load_at_height(&storage, "A", 1).unwrap());
+        assert_eq!(Some(5), FULL.may_load_at_height(&storage, "A", 3).unwrap());
+        assert_eq!(Some(12), FULL.may_load_at_height(&storage, "A", 4).unwrap());
+        assert_eq!(Some(12), FULL.may_load_at_height(&storage, "A", 7).unwrap());
+
+        // every block needs the first change after the height instead
+        EVERY.save(&mut storage, "A", &5, 1).unwrap();
+        EVERY.save(&mut storage, "A", &12, 3).unwrap();
+        EVERY.remove(&mut storage, "A", 6).unwrap();
+        EVERY.changelog().remove(&mut storage, ("A", 6));
+        assert_eq!(None, EVERY.may_load_at_height(&storage, "A", 4).unwrap());
+    }
+
+    #[test]
+    #[cfg(feature = "metrics")]
+    fn full_history_read_cost() {
+        use crate::{MeteredStorage, StorageMetrics};
+
+        let mut storage = MockStorage::new();
+        init_data(&EVERY, &mut storage);
+        init_data(&FULL, &mut storage);
+        let storage = MeteredStorage::new(&mut storage);
+
+        let cost = |map: &TestMap, k, height| {
+            storage.reset();
+            map.may_load_at_height(&storage, k, height).unwrap();
+            let StorageMetrics { reads, ranges, .. } = storage.metrics();
+            (reads, ranges)
+        };
+
+        // after the last change, every block has to load the current value,
+        // while full history finds it in the changelog
+        assert_eq!(cost(&EVERY, "C", 6), (1, 1));
+        assert_eq!(cost(&FULL, "C", 6), (0, 1));
+
+        // between changes, both need a single lookup
+        assert_eq!(cost(&EVERY, "A", 2), (0, 1));
+        assert_eq!(cost(&FULL, "A", 2), (0, 1));
+
+        // before the first change, full history needs a second lookup
+        assert_eq!(cost(&EVERY, "D", 2), (0, 1));
+        assert_eq!(cost(&FULL, "D", 2), (0, 2));
+    }
+
     #[test]
     #[cfg(feature = "iterator")]
     fn changelog_range_works() {
@@ -525,10 +1218,34 @@ mod tests {
         assert_eq!(
             all,
             vec![
-                (("A".into(), 1), ChangeSet { old: None }),
-                (("A".into(), 3), ChangeSet { old: Some(5) }),
-                (("B".into(), 2), ChangeSet { old: None }),
-                (("B".into(), 4), ChangeSet { old: Some(7) })
+                (
+                    ("A".into(), 1),
+                    ChangeSet {
+                        old: None,
+                        new: None
+                    }
+                ),
+                (
+                    ("A".into(), 3),
+                    ChangeSet {
+                        old: Some(5),
+                        new: None
+                    }
+                ),
+                (
+                    ("B".into(), 2),
+                    ChangeSet {
+                        old: None,
+                        new: None
+                    }
+                ),
+                (
+                    ("B".into(), 4),
+                    ChangeSet {
+                        old: Some(7),
+                        new: None
+                    }
+                )
             ]
         );
 
@@ -543,8 +1260,20 @@ mod tests {
         assert_eq!(
             all,
             vec![
-                (2, ChangeSet { old: None }),
-                (4, ChangeSet { old: Some(7) })
+                (
+                    2,
+                    ChangeSet {
+                        old: None,
+                        new: None
+                    }
+                ),
+                (
+                    4,
+                    ChangeSet {
+                        old: Some(7),
+                        new: None
+                    }
+                )
             ]
         );
 
@@ -556,7 +1285,16 @@ mod tests {
             .collect();
         let all = all.unwrap();
         assert_eq!(1, all.len());
-        assert_eq!(all, vec![(3, ChangeSet { old: Some(5) }),]);
+        assert_eq!(
+            all,
+            vec![(
+                3,
+                ChangeSet {
+                    old: Some(5),
+                    new: None
+                }
+            ),]
+        );
     }
 
     #[test]
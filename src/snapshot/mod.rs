@@ -7,11 +7,14 @@ pub use map::SnapshotMap;
 
 use crate::bound::Bound;
 use crate::de::KeyDeserialize;
+use crate::int_key::IntKey;
 use crate::namespace::Namespace;
 use crate::{Map, Prefixer, PrimaryKey};
+use cosmwasm_std::storage_keys::namespace_with_key;
 use cosmwasm_std::{Order, StdError, StdResult, Storage};
 use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::ops::RangeInclusive;
 
 /// Structure holding a map of checkpoints composited from
 /// height (as u64) and counter of how many times it has
@@ -78,6 +81,57 @@ impl<K, T> Snapshot<K, T> {
             self.checkpoints.save(store, height, &(count - 1))
         }
     }
+
+    /// Adds a checkpoint at every height from `from` to `to` (inclusive), or only at the first
+    /// `limit` of them. Returns the last height processed, or `None` if there was none.
+    /// Large ranges can be processed in several calls by continuing after that height.
+    pub fn add_checkpoints(
+        &self,
+        store: &mut dyn Storage,
+        from: u64,
+        to: u64,
+        limit: Option<u64>,
+    ) -> StdResult<Option<u64>> {
+        let Some(heights) = bulk_heights(from, to, limit) else {
+            return Ok(None);
+        };
+        let last = *heights.end();
+        for height in heights {
+            self.add_checkpoint(store, height)?;
+        }
+        Ok(Some(last))
+    }
+
+    /// Removes a checkpoint at every height from `from` to `to` (inclusive) like
+    /// `remove_checkpoint`, paginated like `add_checkpoints`.
+    pub fn remove_checkpoints(
+        &self,
+        store: &mut dyn Storage,
+        from: u64,
+        to: u64,
+        limit: Option<u64>,
+    ) -> StdResult<Option<u64>> {
+        let Some(heights) = bulk_heights(from, to, limit) else {
+            return Ok(None);
+        };
+        let last = *heights.end();
+        for height in heights {
+            self.remove_checkpoint(store, height)?;
+        }
+        Ok(Some(last))
+    }
+}
+
+/// The heights processed by a bulk checkpoint operation, or `None` if there are none
+fn bulk_heights(from: u64, to: u64, limit: Option<u64>) -> Option<RangeInclusive<u64>> {
+    if from > to || limit == Some(0) {
+        return None;
+    }
+    let last = match limit {
+        Some(limit) => to.min(from.saturating_add(limit - 1)),
+        None => to,
+    };
+    Some(from..=last)
 }
 
 impl<'a, K, T> Snapshot<K, T>
@@ -85,10 +139,106 @@ where
     T: Serialize + DeserializeOwned + Clone,
     K: PrimaryKey<'a> + Prefixer<'a> + KeyDeserialize,
 {
+    /// Like `remove_checkpoint`, but once the checkpoint is gone for good, it also removes the
+    /// changelog entries that were only kept to answer queries at that height.
+    /// This only applies to `Strategy::Selected`, as the other strategies don't depend on
+    /// checkpoints to read their changelog.
+    ///
+    /// Note that this scans the whole changelog.
+    pub fn remove_checkpoint_safe(&self, store: &mut dyn Storage, height: u64) -> StdResult<()> {
+        self.remove_checkpoint(store, height)?;
+        if self.strategy != Strategy::Selected || self.checkpoints.has(store, height) {
+            return Ok(());
+        }
+
+        // The first change of a key at or after a checkpoint holds the value at that checkpoint.
+        // It is unreachable if no checkpoint is left between the previous change of the same key
+        // (exclusive) and itself (inclusive).
+        let mut orphaned = vec![];
+        let mut prev: Option<(Vec<u8>, u64)> = None;
+        for raw_key in self.changelog.keys_raw(store, None, None, Order::Ascending) {
+            let (elems, change_height) = split_height(&raw_key)?;
+            let prev_height = match &prev {
+                Some((prev_elems, prev_height)) if prev_elems.as_slice() == elems => {
+                    Some(*prev_height)
+                }
+                _ => None,
+            };
+            if change_height >= height && prev_height.map_or(true, |h| h < height) {
+                let checkpoint = self
+                    .checkpoints
+                    .keys_raw(
+                        store,
+                        prev_height.map(Bound::exclusive),
+                        Some(Bound::inclusive(change_height)),
+                        Order::Ascending,
+                    )
+                    .next();
+                if checkpoint.is_none() {
+                    orphaned.push(raw_key.clone());
+                }
+            }
+            prev = Some((elems.to_vec(), change_height));
+        }
+
+        let namespace = self.changelog.namespace_bytes();
+        for raw_key in orphaned {
+            store.remove(&namespace_with_key(&[namespace], &raw_key));
+        }
+        Ok(())
+    }
+
+    /// Removes up to `limit` changelog entries (or all if `limit == None`) below `up_to_height`,
+    /// then the checkpoints below it with what is left of the limit, and returns how many were
+    /// removed in total. Call it until it returns less than `limit` to prune everything.
+    ///
+    /// Queries at `up_to_height` or later are answered as before. Earlier ones fail where they
+    /// require a checkpoint, and otherwise return values from later heights, like the current one.
+    ///
+    /// Note that `limit` only bounds the removals, not the reads: this scans the changelog from
+    /// the start, including the kept entries, until `limit` entries to remove are found.
+    pub fn prune(
+        &self,
+        store: &mut dyn Storage,
+        up_to_height: u64,
+        limit: Option<usize>,
+    ) -> StdResult<usize> {
+        let limit = limit.unwrap_or(usize::MAX);
+        let mut changes = vec![];
+        for raw_key in self.changelog.keys_raw(store, None, None, Order::Ascending) {
+            if changes.len() == limit {
+                break;
+            }
+            if split_height(&raw_key)?.1 < up_to_height {
+                changes.push(raw_key);
+            }
+        }
+        let namespace = self.changelog.namespace_bytes();
+        for raw_key in &changes {
+            store.remove(&namespace_with_key(&[namespace], raw_key));
+        }
+
+        let checkpoints = self
+            .checkpoints
+            .keys(
+                store,
+                None,
+                Some(Bound::exclusive(up_to_height)),
+                Order::Ascending,
+            )
+            .take(limit - changes.len())
+            .collect::<StdResult<Vec<_>>>()?;
+        for height in &checkpoints {
+            self.checkpoints.remove(store, *height);
+        }
+
+        Ok(changes.len() + checkpoints.len())
+    }
+
     /// should_checkpoint looks at the strategy and determines if we want to checkpoint
     pub fn should_checkpoint(&self, store: &dyn Storage, k: &K) -> StdResult<bool> {
         match self.strategy {
-            Strategy::EveryBlock => Ok(true),
+            Strategy::EveryBlock | Strategy::FullHistory => Ok(true),
             Strategy::Never => Ok(false),
             Strategy::Selected => self.should_checkpoint_selected(store, k),
         }
@@ -123,7 +273,7 @@ where
     // If there is no checkpoint for that height, then we return StdError::NotFound
     pub fn assert_checkpointed(&self, store: &dyn Storage, height: u64) -> StdResult<()> {
         let has = match self.strategy {
-            Strategy::EveryBlock => true,
+            Strategy::EveryBlock | Strategy::FullHistory => true,
             Strategy::Never => false,
             Strategy::Selected => self.checkpoints.may_load(store, height)?.is_some(),
         };
@@ -145,7 +295,28 @@ where
         old: Option<T>,
     ) -> StdResult<()> {
         self.changelog
-            .save(store, (key, height), &ChangeSet { old })
+            .save(store, (key, height), &ChangeSet { old, new: None })
+    }
+
+    /// Stores the value written at the given height in the changelog entry for that height,
+    /// which must already exist. This is a no-op unless the strategy is `FullHistory`.
+    pub fn write_new_value(
+        &self,
+        store: &mut dyn Storage,
+        key: K,
+        height: u64,
+        new: Option<&T>,
+    ) -> StdResult<()> {
+        if self.strategy != Strategy::FullHistory {
+            return Ok(());
+        }
+        self.changelog
+            .update::<_, StdError>(store, (key, height), |changeset| {
+                let mut changeset = changeset.ok_or_else(|| StdError::not_found("changeset"))?;
+                changeset.new = new.cloned();
+                Ok(changeset)
+            })?;
+        Ok(())
     }
 
     // may_load_at_height reads historical data from given checkpoints.
@@ -161,6 +332,20 @@ where
     ) -> StdResult<Option<Option<T>>> {
         self.assert_checkpointed(store, height)?;
 
+        if self.strategy == Strategy::FullHistory {
+            // the value written by the last change before the given height is the one we want.
+            // If there is none, fall through and look at the first change since then
+            let end = Bound::exclusive(height);
+            let last = self
+                .changelog
+                .prefix(key.clone())
+                .range_raw(store, None, Some(end), Order::Descending)
+                .next();
+            if let Some(r) = last {
+                return r.map(|(_, v)| Some(v.new));
+            }
+        }
+
         // this will look for the first snapshot of height >= given height
         // If None, there is no snapshot since that time.
         let start = Bound::inclusive(height);
@@ -179,6 +364,15 @@ where
     }
 }
 
+/// Splits a raw changelog key into the key elements and the height of the change
+fn split_height(raw_key: &[u8]) -> StdResult<(&[u8], u64)> {
+    if raw_key.len() < 8 {
+        return Err(StdError::generic_err("Invalid changelog key"));
+    }
+    let (elems, height) = raw_key.split_at(raw_key.len() - 8);
+    Ok((elems, u64::from_cw_bytes(height.try_into().unwrap())))
+}
+
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub enum Strategy {
     EveryBlock,
@@ -189,11 +383,22 @@ pub enum Strategy {
     /// Note that you need a trusted source (eg. own contract) to set/remove checkpoints.
     /// Useful when the checkpoint setting happens in the same contract as the snapshotting.
     Selected,
+    /// Like `EveryBlock`, but the changelog also records the value written at each height.
+    /// Historical reads then only need to look up the last change before the requested height,
+    /// at the cost of storing every value twice.
+    ///
+    /// This must be used from the start, as changes recorded with another strategy
+    /// do not contain the new value.
+    FullHistory,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
 pub struct ChangeSet<T> {
     pub old: Option<T>,
+    /// The value at the end of the block. Only recorded by `Strategy::FullHistory`,
+    /// where `None` means the value was removed.
+    #[serde(default = "Option::default", skip_serializing_if = "Option::is_none")]
+    pub new: Option<T>,
 }
 
 #[cfg(test)]
@@ -0,0 +1,119 @@
+#[cfg(all(test, feature = "macro"))]
+mod test {
+    use cosmwasm_std::{testing::MockStorage, Addr};
+    use cw_storage_plus::{CompositeKey, KeyDeserialize, Map, PrimaryKey};
+
+    #[derive(CompositeKey, Clone, Debug, PartialEq)]
+    struct Allowance {
+        owner: Addr,
+        spender: Addr,
+        expires: u64,
+    }
+
+    #[derive(CompositeKey, Clone, Debug, PartialEq)]
+    struct Pair(String, u8);
+
+    #[derive(CompositeKey, Clone, Copy, Debug, PartialEq)]
+    struct TestKeyU64(u64);
+
+    #[test]
+    fn struct_key_matches_tuple_layout() {
+        let key = Allowance {
+            owner: Addr::unchecked("alice"),
+            spender: Addr::unchecked("bob"),
+            expires: 1234,
+        };
+        assert_eq!(Allowance::KEY_ELEMS, 3);
+        assert_eq!(
+            key.joined_key(),
+            (Addr::unchecked("alice"), Addr::unchecked("bob"), 1234u64).joined_key()
+        );
+        assert_eq!(Allowance::from_vec(key.joined_key()).unwrap(), key);
+
+        let pair = Pair("denom".to_string(), 7);
+        assert_eq!(pair.joined_key(), ("denom", 7u8).joined_key());
+        assert_eq!(Pair::from_slice(&pair.joined_key()).unwrap(), pair);
+    }
+
+    #[test]
+    fn struct_key_round_trips_through_map() {
+        const ALLOWANCES: Map<Allowance, u128> = Map::new("allowances");
+
+        let mut store = MockStorage::new();
+        let key = Allowance {
+            owner: Addr::unchecked("alice"),
+            spender: Addr::unchecked("bob"),
+            expires: 1234,
+        };
+        ALLOWANCES.save(&mut store, key.clone(), &100).unwrap();
+        assert_eq!(ALLOWANCES.load(&store, key.clone()).unwrap(), 100);
+
+        #[cfg(feature = "iterator")]
+        {
+            use cosmwasm_std::{Order, StdResult};
+
+            let all = ALLOWANCES
+                .range(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(all, vec![(key.clone(), 100)]);
+
+            // also works as a prefix in a tuple
+            const BY_KEY: Map<(Allowance, u8), u64> = Map::new("by_key");
+            BY_KEY.save(&mut store, (key.clone(), 1), &1).unwrap();
+            BY_KEY.save(&mut store, (key.clone(), 2), &2).unwrap();
+            let values = BY_KEY
+                .prefix(key)
+                .range(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(values, vec![(1, 1), (2, 2)]);
+        }
+    }
+
+    #[test]
+    fn struct_key_by_reference() {
+        const NAMES: Map<&TestKeyU64, String> = Map::new("names");
+
+        let mut store = MockStorage::new();
+        let (one, two) = (TestKeyU64(1), TestKeyU64(2));
+        assert_eq!(<&TestKeyU64>::joined_key(&&one), one.joined_key());
+        assert_eq!(<&TestKeyU64>::from_vec(one.joined_key()).unwrap(), one);
+
+        NAMES.save(&mut store, &two, &"two".to_string()).unwrap();
+        NAMES.save(&mut store, &one, &"one".to_string()).unwrap();
+        assert_eq!(NAMES.load(&store, &one).unwrap(), "one");
+
+        #[cfg(feature = "iterator")]
+        {
+            use cosmwasm_std::{Order, StdResult};
+
+            let all = NAMES
+                .range(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(
+                all,
+                vec![(one, "one".to_string()), (two, "two".to_string())]
+            );
+
+            // and as a prefix
+            const BY_KEY: Map<(&TestKeyU64, u8), u64> = Map::new("by_key");
+            BY_KEY.save(&mut store, (&one, 1), &1).unwrap();
+            BY_KEY.save(&mut store, (&two, 2), &2).unwrap();
+            BY_KEY.save(&mut store, (&two, 3), &3).unwrap();
+            let values = BY_KEY
+                .prefix(&two)
+                .range(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(values, vec![(2, 2), (3, 3)]);
+
+            let keys = BY_KEY
+                .keys(&store, None, None, Order::Ascending)
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(keys, vec![(one, 1), (two, 2), (two, 3)]);
+        }
+    }
+}
@@ -0,0 +1,82 @@
+#[cfg(all(test, feature = "macro"))]
+mod test {
+    use cosmwasm_std::{testing::MockStorage, Addr};
+    use cw_storage_plus::{CwKey, KeyDeserialize, Map, PrimaryKey};
+
+    #[derive(CwKey, Clone, Debug, PartialEq)]
+    enum Denom {
+        Native(String),
+        Cw20(Addr),
+    }
+
+    #[test]
+    fn enum_key_matches_tuple_layout() {
+        let native = Denom::Native("ujuno".to_string());
+        let cw20 = Denom::Cw20(Addr::unchecked("token"));
+        assert_eq!(Denom::KEY_ELEMS, 2);
+        assert_eq!(native.joined_key(), (0u8, "ujuno").joined_key());
+        assert_eq!(
+            cw20.joined_key(),
+            (1u8, Addr::unchecked("token")).joined_key()
+        );
+
+        assert_eq!(Denom::from_vec(native.joined_key()).unwrap(), native);
+        assert_eq!(Denom::from_vec(cw20.joined_key()).unwrap(), cw20);
+        assert!(Denom::from_vec((2u8, "ujuno").joined_key()).is_err());
+    }
+
+    #[test]
+    fn enum_key_round_trips_through_map() {
+        const BALANCES: Map<Denom, u128> = Map::new("balances");
+
+        let mut store = MockStorage::new();
+        let denoms = [
+            Denom::Cw20(Addr::unchecked("token")),
+            Denom::Native("uatom".to_string()),
+            Denom::Native("ujuno".to_string()),
+            Denom::Cw20(Addr::unchecked("other")),
+        ];
+        for (i, denom) in denoms.iter().enumerate() {
+            BALANCES
+                .save(&mut store, denom.clone(), &(i as u128))
+                .unwrap();
+        }
+        assert_eq!(BALANCES.load(&store, denoms[2].clone()).unwrap(), 2);
+
+        #[cfg(feature = "iterator")]
+        {
+            use cosmwasm_std::{Order, StdResult};
+            use cw_storage_plus::PrefixBound;
+
+            // the keys of a single variant by its discriminant
+            let cw20 = BALANCES
+                .prefix_range(
+                    &store,
+                    Some(PrefixBound::inclusive(1u8)),
+                    Some(PrefixBound::inclusive(1u8)),
+                    Order::Ascending,
+                )
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(
+                cw20,
+                vec![
+                    (Denom::Cw20(Addr::unchecked("other")), 3),
+                    (Denom::Cw20(Addr::unchecked("token")), 0),
+                ]
+            );
+
+            // a prefix yields the raw keys of the wrapped values
+            let native = BALANCES
+                .prefix(0)
+                .range(&store, None, None, Order::Ascending)
+                .map(|item| item.map(|(raw, v)| (String::from_vec(raw).unwrap(), v)))
+                .collect::<StdResult<Vec<_>>>()
+                .unwrap();
+            assert_eq!(
+                native,
+                vec![("uatom".to_string(), 1), ("ujuno".to_string(), 2)]
+            );
+        }
+    }
+}
@@ -3,7 +3,7 @@ mod test {
     use cosmwasm_std::{testing::MockStorage, Addr};
     use cw_storage_macro::index_list;
     use cw_storage_plus::{IndexedMap, MultiIndex, UniqueIndex};
-    use serde::{Deserialize, Serialize};
+    use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
     #[test]
     fn index_list_compiles() {
@@ -73,4 +73,82 @@ mod test {
             }
         );
     }
+
+    #[test]
+    fn index_list_supports_generic_structs() {
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct TestStruct {
+            id: u64,
+            id2: u32,
+            addr: Addr,
+        }
+
+        // the bounds of `Index` on the stored type are stated on the struct
+        #[index_list(T)]
+        struct GenericIndexes<'a, T, PK>
+        where
+            T: Serialize + DeserializeOwned + Clone,
+        {
+            id: MultiIndex<'a, u32, T, PK>,
+            addr: UniqueIndex<'a, Addr, T, PK>,
+        }
+
+        let mut storage = MockStorage::new();
+        let idm: IndexedMap<u64, TestStruct, GenericIndexes<TestStruct, u64>> = IndexedMap::new(
+            "t",
+            GenericIndexes {
+                id: MultiIndex::new(|_pk, t: &TestStruct| t.id2, "t", "t_2"),
+                addr: UniqueIndex::new(|t: &TestStruct| t.addr.clone(), "t_addr"),
+            },
+        );
+
+        let data = TestStruct {
+            id: 0,
+            id2: 100,
+            addr: Addr::unchecked("1"),
+        };
+        idm.save(&mut storage, 0, &data).unwrap();
+        assert_eq!(idm.load(&storage, 0).unwrap(), data);
+        assert_eq!(
+            idm.idx
+                .addr
+                .item(&storage, Addr::unchecked("1"))
+                .unwrap()
+                .map(|(_, t)| t),
+            Some(data.clone())
+        );
+
+        // the unique index rejects a second entry for the same address
+        assert!(idm.save(&mut storage, 1, &data).is_err());
+    }
+
+    #[test]
+    fn index_list_supports_static_lifetimes() {
+        #[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+        struct TestStruct {
+            id: u64,
+            addr: Addr,
+        }
+
+        #[index_list(TestStruct)]
+        struct StaticIndexes {
+            addr: UniqueIndex<'static, Addr, TestStruct, u64>,
+        }
+
+        let mut storage = MockStorage::new();
+        let idm: IndexedMap<u64, TestStruct, StaticIndexes> = IndexedMap::new(
+            "t",
+            StaticIndexes {
+                addr: UniqueIndex::new(|t| t.addr.clone(), "t_addr"),
+            },
+        );
+
+        let data = TestStruct {
+            id: 0,
+            addr: Addr::unchecked("1"),
+        };
+        idm.save(&mut storage, 0, &data).unwrap();
+        assert_eq!(idm.load(&storage, 0).unwrap(), data);
+        assert!(idm.save(&mut storage, 1, &data).is_err());
+    }
 }